@@ -0,0 +1,505 @@
+//! Tool-using agents.
+//!
+//! [`AgentStep`] runs a ReAct-style loop: call the model, execute any tool
+//! calls it requests, feed the results back, and repeat until the model
+//! answers without requesting tools or a limit is reached. It is a
+//! [`StateStep`] whose [`Scratchpad`] state holds the conversation so far, so
+//! a run can be continued, persisted, or resumed after a checkpoint.
+//!
+//! The [`team`] module composes named agents under a supervisor, with
+//! [`Handoff`]s letting one agent transfer the conversation to another.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::future::Future;
+use std::time::Instant;
+
+use crate::chat::{ChatModel, Message, Role, ToolCall};
+use crate::tool::ToolExecutorStep;
+use crate::{Error, ExecutionContext, Result, StateStep, WorkflowEvent, step::Step};
+
+pub mod team;
+
+pub use team::Handoff;
+
+/// The working state of an [`AgentStep`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Scratchpad {
+    /// The conversation, including tool calls and results.
+    pub messages: Vec<Message>,
+    /// Number of model calls made so far.
+    pub iterations: usize,
+    /// Prompt plus completion tokens reported by model calls so far.
+    pub tokens_used: usize,
+    /// Set when the run paused at a checkpoint; resuming runs the pending
+    /// tool calls without pausing again.
+    #[serde(default)]
+    pub checkpointed: bool,
+}
+
+impl Scratchpad {
+    /// A scratchpad starting from the given messages.
+    pub fn new(messages: Vec<Message>) -> Self {
+        Self {
+            messages,
+            ..Self::default()
+        }
+    }
+
+    /// Tool calls from the last assistant message that have not been answered yet.
+    pub fn pending_tool_calls(&self) -> &[ToolCall] {
+        match self.messages.last() {
+            Some(last) if last.role == Role::Assistant => &last.tool_calls,
+            _ => &[],
+        }
+    }
+}
+
+/// Why an [`AgentStep`] run stopped.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AgentStop {
+    /// The model replied without requesting tools.
+    Answered,
+    /// The model called a [`Handoff`] tool to transfer control to another agent.
+    Handoff {
+        /// The agent taking over.
+        to: String,
+    },
+    /// The maximum number of model calls was reached.
+    MaxIterations,
+    /// The token budget was exhausted.
+    TokenBudget,
+}
+
+/// The result of an [`AgentStep`] run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgentOutcome {
+    /// The model's final reply, when it answered.
+    pub answer: Option<Message>,
+    /// Why the run stopped.
+    pub stop: AgentStop,
+}
+
+/// A model-and-tools loop.
+///
+/// Each run appends its input messages to the scratchpad, then alternates
+/// model calls and tool execution. The run stops when:
+///
+/// - the model replies without tool calls ([`AgentStop::Answered`]);
+/// - `max_iterations` model calls have been made in total
+///   ([`AgentStop::MaxIterations`]);
+/// - the token budget set with [`AgentStep::with_token_budget`] is used up
+///   ([`AgentStop::TokenBudget`]);
+/// - the model calls a handoff tool registered with [`AgentStep::with_handoff`]
+///   ([`AgentStop::Handoff`]); or
+/// - the model requests a tool marked with [`AgentStep::with_checkpoint_tool`],
+///   in which case an [`Error::Checkpoint`] is returned whose `data` holds the
+///   `pending` tool calls and the `scratchpad`. Running again with that
+///   scratchpad executes the pending calls and continues; any new input is
+///   appended after their results.
+///
+/// A checkpoint raised while executing tools — e.g. a call awaiting approval
+/// under [`ToolExecutorStep::require_approval`] — is re-raised the same way,
+/// with the tool's checkpoint data under `awaiting`. Record the decision and
+/// resume with the scratchpad.
+///
+/// Model calls run in a child context so their token usage can be measured;
+/// each emits a [`WorkflowEvent::ModelCall`] event, and each iteration a
+/// [`WorkflowEvent::AgentIteration`] event.
+///
+/// # Example
+///
+/// ```rust
+/// use serde_json::json;
+/// use llm_workflow::{ExecutionContext, LambdaStep, StateStep};
+/// use llm_workflow::agent::{AgentStep, AgentStop, Scratchpad};
+/// use llm_workflow::chat::{Message, Role, ToolCall};
+/// use llm_workflow::tool::{FnTool, ToolExecutorStep, ToolRegistry};
+///
+/// # tokio_test::block_on(async {
+/// // A scripted "model": call the clock tool once, then answer.
+/// let model = LambdaStep::new(|messages: Vec<Message>| async move {
+///     Ok::<Message, llm_workflow::Error>(match messages.last().unwrap().role {
+///         Role::Tool => Message::assistant(format!("It is {}.", messages.last().unwrap().text())),
+///         _ => Message::tool_calls(vec![ToolCall::new("c1", "clock", json!({}))]),
+///     })
+/// });
+/// let clock = FnTool::new("clock", "Current time.", json!({"type": "object"}),
+///     |_ctx: ExecutionContext, _args: serde_json::Value| async move { Ok(json!("noon")) });
+/// let agent = AgentStep::new(model, ToolExecutorStep::new(ToolRegistry::new().with(clock)), 5);
+///
+/// let (outcome, pad) = agent
+///     .run(&ExecutionContext::new(), Scratchpad::default(), vec![Message::user("Time?")])
+///     .await
+///     .unwrap();
+/// assert_eq!(outcome.stop, AgentStop::Answered);
+/// assert_eq!(outcome.answer.unwrap().text(), "It is noon.");
+/// assert_eq!(pad.iterations, 2);
+/// # });
+/// ```
+pub struct AgentStep<M> {
+    model: M,
+    tools: ToolExecutorStep,
+    max_iterations: usize,
+    token_budget: Option<usize>,
+    checkpoint_tools: BTreeSet<String>,
+    handoffs: BTreeMap<String, String>,
+    name: String,
+}
+
+impl<M: ChatModel> AgentStep<M> {
+    /// Create an agent making at most `max_iterations` model calls.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_iterations` is zero.
+    pub fn new(model: M, tools: ToolExecutorStep, max_iterations: usize) -> Self {
+        assert!(max_iterations > 0, "max_iterations must be greater than zero");
+        Self {
+            model,
+            tools,
+            max_iterations,
+            token_budget: None,
+            checkpoint_tools: BTreeSet::new(),
+            handoffs: BTreeMap::new(),
+            name: "agent".to_string(),
+        }
+    }
+
+    /// Stop once model calls have used `tokens` prompt plus completion tokens.
+    pub fn with_token_budget(mut self, tokens: usize) -> Self {
+        self.token_budget = Some(tokens);
+        self
+    }
+
+    /// Pause at a checkpoint before running the named tool.
+    pub fn with_checkpoint_tool(mut self, tool_name: impl Into<String>) -> Self {
+        self.checkpoint_tools.insert(tool_name.into());
+        self
+    }
+
+    /// Stop with [`AgentStop::Handoff`] when the model calls `handoff`'s tool.
+    ///
+    /// The handoff call is answered with a short confirmation so the
+    /// conversation stays well-formed for the next agent; other tool calls in
+    /// the same reply still run.
+    pub fn with_handoff(mut self, handoff: Handoff) -> Self {
+        self.handoffs.insert(handoff.tool_name(), handoff.agent);
+        self
+    }
+
+    /// Set a human-readable name, used in emitted events.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// The name used in emitted events.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn run_tools(&self, ctx: &ExecutionContext, pending: Vec<ToolCall>) -> Result<(Vec<Message>, Option<String>)> {
+        let (transfers, calls): (Vec<_>, Vec<_>) =
+            pending.iter().cloned().partition(|call| self.handoffs.contains_key(&call.name));
+        let mut results = match calls.is_empty() {
+            true => Vec::new(),
+            false => self.tools.run(ctx, calls).await?,
+        }
+        .into_iter();
+
+        let handoff = transfers.first().map(|call| self.handoffs[&call.name].clone());
+        let messages = pending
+            .iter()
+            .filter_map(|call| match self.handoffs.get(&call.name) {
+                Some(to) => Some(Message::tool_result(&call.id, format!("Transferred to {to}."))),
+                None => results.next(),
+            })
+            .collect();
+        Ok((messages, handoff))
+    }
+
+    fn checkpoint(&self, mut pad: Scratchpad) -> Error {
+        pad.checkpointed = true;
+        Error::Checkpoint {
+            step_name: self.name.clone(),
+            data: serde_json::json!({
+                "pending": pad.pending_tool_calls(),
+                "scratchpad": pad,
+            }),
+        }
+    }
+
+    fn tool_checkpoint(&self, mut pad: Scratchpad, awaiting: serde_json::Value) -> Error {
+        pad.checkpointed = true;
+        Error::Checkpoint {
+            step_name: self.name.clone(),
+            data: serde_json::json!({
+                "pending": pad.pending_tool_calls(),
+                "awaiting": awaiting,
+                "scratchpad": pad,
+            }),
+        }
+    }
+
+    async fn call_model(&self, ctx: &ExecutionContext, pad: &mut Scratchpad) -> Result<Message> {
+        let child = ctx.child();
+        let start = Instant::now();
+        let result = self.model.run(&child, pad.messages.clone()).await;
+        let usage = child.snapshot();
+        ctx.merge(&child);
+
+        pad.iterations += 1;
+        pad.tokens_used += usage.prompt_token_count + usage.completion_token_count;
+        ctx.emit(WorkflowEvent::ModelCall {
+            step_name: self.name.clone(),
+            messages: pad.messages.len(),
+            prompt_tokens: usage.prompt_token_count,
+            completion_tokens: usage.completion_token_count,
+            duration_ms: start.elapsed().as_millis(),
+        });
+        result
+    }
+
+    async fn run_loop(
+        &self,
+        ctx: &ExecutionContext,
+        mut pad: Scratchpad,
+        input: Vec<Message>,
+    ) -> Result<(AgentOutcome, Scratchpad)> {
+        // Input given on resume follows the results of the calls still pending,
+        // so the tool-call message stays last until they have run.
+        let mut deferred = Vec::new();
+        if pad.pending_tool_calls().is_empty() {
+            pad.messages.extend(input);
+        } else {
+            deferred = input;
+        }
+        loop {
+            let pending = pad.pending_tool_calls().to_vec();
+            if !pending.is_empty() {
+                let needs_checkpoint = pending.iter().any(|call| self.checkpoint_tools.contains(&call.name));
+                if needs_checkpoint && !pad.checkpointed {
+                    return Err(self.checkpoint(pad));
+                }
+                pad.checkpointed = false;
+                let (results, handoff) = match self.run_tools(ctx, pending).await {
+                    Err(Error::Checkpoint { data, .. }) => return Err(self.tool_checkpoint(pad, data)),
+                    results => results?,
+                };
+                pad.messages.extend(results);
+                pad.messages.append(&mut deferred);
+                if let Some(to) = handoff {
+                    return Ok((AgentOutcome { answer: None, stop: AgentStop::Handoff { to } }, pad));
+                }
+            }
+
+            let stop = if pad.iterations >= self.max_iterations {
+                Some(AgentStop::MaxIterations)
+            } else if self.token_budget.is_some_and(|budget| pad.tokens_used >= budget) {
+                Some(AgentStop::TokenBudget)
+            } else {
+                None
+            };
+            if let Some(stop) = stop {
+                return Ok((AgentOutcome { answer: None, stop }, pad));
+            }
+
+            let reply = self.call_model(ctx, &mut pad).await?;
+            ctx.emit(WorkflowEvent::AgentIteration {
+                step_name: self.name.clone(),
+                iteration: pad.iterations,
+                tool_calls: reply.tool_calls.iter().map(|call| call.name.clone()).collect(),
+                tokens_used: pad.tokens_used,
+            });
+            let answered = reply.tool_calls.is_empty();
+            pad.messages.push(reply.clone());
+            if answered {
+                let outcome = AgentOutcome {
+                    answer: Some(reply),
+                    stop: AgentStop::Answered,
+                };
+                return Ok((outcome, pad));
+            }
+        }
+    }
+}
+
+impl<M: ChatModel> StateStep for AgentStep<M> {
+    type Input = Vec<Message>;
+    type Output = AgentOutcome;
+    type State = Scratchpad;
+
+    #[allow(clippy::type_complexity)]
+    fn run<'life0, 'async_trait>(
+        &'life0 self,
+        ctx: &'life0 ExecutionContext,
+        state: Scratchpad,
+        input: Vec<Message>,
+    ) -> std::pin::Pin<
+        Box<dyn Future<Output = Result<(AgentOutcome, Scratchpad)>> + Send + 'async_trait>,
+    >
+    where
+        'life0: 'async_trait,
+        Self: 'async_trait,
+    {
+        Box::pin(self.run_loop(ctx, state, input))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool::{FnTool, ToolRegistry};
+    use crate::LambdaStep;
+    use serde_json::{json, Value};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn ctx() -> ExecutionContext {
+        ExecutionContext::default()
+    }
+
+    /// A model that requests `tool` until it has seen `rounds` tool results, then answers.
+    fn scripted(tool: &'static str, rounds: usize) -> impl ChatModel {
+        LambdaStep::new(move |messages: Vec<Message>| async move {
+            let results = messages.iter().filter(|m| m.role == Role::Tool).count();
+            Ok(if results >= rounds {
+                Message::assistant(format!("done after {results}"))
+            } else {
+                Message::tool_calls(vec![ToolCall::new(format!("c{results}"), tool, json!({}))])
+            })
+        })
+    }
+
+    fn tools(calls: Arc<AtomicUsize>) -> ToolExecutorStep {
+        let counter = FnTool::new("count", "Count calls.", json!({"type": "object"}), move |_ctx: ExecutionContext, _args: Value| {
+            let calls = Arc::clone(&calls);
+            async move { Ok(json!(calls.fetch_add(1, Ordering::SeqCst) + 1)) }
+        });
+        let refund = FnTool::new("refund", "Issue a refund.", json!({"type": "object"}), |_ctx: ExecutionContext, _args: Value| async move {
+            Ok(json!("refunded"))
+        });
+        ToolExecutorStep::new(ToolRegistry::new().with(counter).with(refund))
+    }
+
+    #[tokio::test]
+    async fn test_loops_until_answer() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let ctx = ctx();
+        let agent = AgentStep::new(scripted("count", 3), tools(Arc::clone(&calls)), 10);
+        let (outcome, pad) = agent.run(&ctx, Scratchpad::default(), vec![Message::user("go")]).await.unwrap();
+
+        assert_eq!(outcome.stop, AgentStop::Answered);
+        assert_eq!(outcome.answer.unwrap().text(), "done after 3");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(pad.iterations, 4);
+        // user, then 3 x (call + result), then the answer
+        assert_eq!(pad.messages.len(), 8);
+
+        let iterations = ctx
+            .trace_snapshot()
+            .iter()
+            .filter(|e| matches!(e.event, WorkflowEvent::AgentIteration { .. }))
+            .count();
+        assert_eq!(iterations, 4);
+    }
+
+    #[tokio::test]
+    async fn test_stops_at_max_iterations() {
+        let agent = AgentStep::new(scripted("count", 100), tools(Arc::default()), 3);
+        let (outcome, pad) = agent.run(&ctx(), Scratchpad::default(), vec![Message::user("go")]).await.unwrap();
+        assert_eq!(outcome, AgentOutcome { answer: None, stop: AgentStop::MaxIterations });
+        assert_eq!(pad.iterations, 3);
+    }
+
+    #[tokio::test]
+    async fn test_stops_at_token_budget() {
+        struct Metered;
+        #[async_trait::async_trait]
+        impl Step for Metered {
+            type Input = Vec<Message>;
+            type Output = Message;
+            async fn run(&self, ctx: &ExecutionContext, _input: Vec<Message>) -> Result<Message> {
+                ctx.record_tokens(40, 10);
+                Ok(Message::tool_calls(vec![ToolCall::new("c", "count", json!({}))]))
+            }
+        }
+
+        let ctx = ctx();
+        let agent = AgentStep::new(Metered, tools(Arc::default()), 10).with_token_budget(120);
+        let (outcome, pad) = agent.run(&ctx, Scratchpad::default(), vec![]).await.unwrap();
+        assert_eq!(outcome.stop, AgentStop::TokenBudget);
+        assert_eq!(pad.tokens_used, 150);
+        assert_eq!(ctx.snapshot().prompt_token_count, 120);
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_tool_pauses_and_resumes() {
+        let agent = AgentStep::new(scripted("refund", 1), tools(Arc::default()), 5)
+            .with_checkpoint_tool("refund")
+            .with_name("support");
+        let err = agent
+            .run(&ctx(), Scratchpad::default(), vec![Message::user("refund me")])
+            .await
+            .unwrap_err();
+
+        let Error::Checkpoint { step_name, data } = err else {
+            panic!("expected checkpoint");
+        };
+        assert_eq!(step_name, "support");
+        assert_eq!(data["pending"][0]["name"], "refund");
+
+        let pad: Scratchpad = serde_json::from_value(data["scratchpad"].clone()).unwrap();
+        let (outcome, pad) = agent.run(&ctx(), pad, vec![]).await.unwrap();
+        assert_eq!(outcome.stop, AgentStop::Answered);
+        assert_eq!(pad.messages[2], Message::tool_result("c0", "refunded"));
+        assert!(!pad.checkpointed);
+    }
+
+    #[tokio::test]
+    async fn test_resume_input_follows_pending_results() {
+        let agent = AgentStep::new(scripted("refund", 1), tools(Arc::default()), 5).with_checkpoint_tool("refund");
+        let Error::Checkpoint { data, .. } = agent
+            .run(&ctx(), Scratchpad::default(), vec![Message::user("refund me")])
+            .await
+            .unwrap_err()
+        else {
+            panic!("expected checkpoint");
+        };
+
+        let pad: Scratchpad = serde_json::from_value(data["scratchpad"].clone()).unwrap();
+        let (outcome, pad) = agent
+            .run(&ctx(), pad, vec![Message::user("approved by manager")])
+            .await
+            .unwrap();
+        assert_eq!(outcome.stop, AgentStop::Answered);
+        assert_eq!(pad.messages[2], Message::tool_result("c0", "refunded"));
+        assert_eq!(pad.messages[3], Message::user("approved by manager"));
+    }
+
+    #[tokio::test]
+    async fn test_tool_approval_checkpoint_carries_scratchpad() {
+        let executor = tools(Arc::default()).require_approval("refund");
+        let approvals = executor.approvals().clone();
+        let agent = AgentStep::new(scripted("refund", 1), executor, 5);
+        let err = agent
+            .run(&ctx(), Scratchpad::default(), vec![Message::user("refund me")])
+            .await
+            .unwrap_err();
+
+        let Error::Checkpoint { step_name, data } = err else {
+            panic!("expected checkpoint");
+        };
+        assert_eq!(step_name, "agent");
+        assert_eq!(data["awaiting"]["id"], "c0");
+        let pad: Scratchpad = serde_json::from_value(data["scratchpad"].clone()).unwrap();
+
+        let awaiting: ToolCall = serde_json::from_value(data["awaiting"].clone()).unwrap();
+        approvals.reject_call(&awaiting, "over limit");
+        let (outcome, pad) = agent.run(&ctx(), pad, vec![]).await.unwrap();
+        assert_eq!(outcome.stop, AgentStop::Answered);
+        assert!(pad.messages[2].tool_result.as_ref().unwrap().is_error);
+        assert_eq!(pad.messages[2].text(), "Execution error: call rejected by reviewer: over limit");
+    }
+}
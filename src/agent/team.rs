@@ -0,0 +1,527 @@
+//! Multi-agent orchestration.
+//!
+//! A [`SupervisorStep`] holds a set of named agents sharing one conversation.
+//! A router step — typically a supervisor model — picks which agent takes the
+//! next turn, and an agent can pass control directly to another by calling a
+//! [`Handoff`] tool. Each agent's work runs under
+//! [`ExecutionContext::with_agent`], so every model call and tool invocation
+//! in the trace is attributed to the agent that made it.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::future::Future;
+
+use super::{AgentOutcome, AgentStop, Scratchpad};
+use crate::chat::{Message, Role};
+use crate::tool::ToolDefinition;
+use crate::{Error, ExecutionContext, Result, StateStep, WorkflowEvent, step::Step};
+
+/// A tool that transfers the conversation to another agent.
+///
+/// Advertise [`Handoff::definition`] to the model and register the handoff
+/// with [`AgentStep::with_handoff`](super::AgentStep::with_handoff); when the
+/// model calls it, the agent stops with [`AgentStop::Handoff`].
+///
+/// # Example
+///
+/// ```rust
+/// use llm_workflow::agent::Handoff;
+///
+/// let handoff = Handoff::new("billing", "Questions about invoices and refunds.");
+/// assert_eq!(handoff.tool_name(), "transfer_to_billing");
+/// assert_eq!(handoff.definition().to_openai()["function"]["name"], "transfer_to_billing");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Handoff {
+    /// The agent taking over.
+    pub agent: String,
+    /// When the model should hand off to this agent.
+    pub description: String,
+}
+
+impl Handoff {
+    /// Create a handoff to the named agent.
+    pub fn new(agent: impl Into<String>, description: impl Into<String>) -> Self {
+        Self {
+            agent: agent.into(),
+            description: description.into(),
+        }
+    }
+
+    /// The tool name the model calls, `transfer_to_<agent>`.
+    pub fn tool_name(&self) -> String {
+        format!("transfer_to_{}", self.agent)
+    }
+
+    /// The tool definition advertised to the model.
+    pub fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: self.tool_name(),
+            description: format!("Transfer the conversation to the {} agent. {}", self.agent, self.description),
+            parameters: json!({
+                "type": "object",
+                "properties": {"reason": {"type": "string"}},
+            }),
+        }
+    }
+}
+
+/// The supervisor's choice of what happens next.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Next {
+    /// Give the next turn to the named agent.
+    Agent(String),
+    /// End the run with the latest assistant reply.
+    Finish,
+}
+
+/// The shared state of a [`SupervisorStep`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TeamState {
+    /// The conversation shared by all agents.
+    pub messages: Vec<Message>,
+    /// The agent that takes the next turn without consulting the router,
+    /// set by a handoff or a checkpoint.
+    pub active: Option<String>,
+    /// The active agent's scratchpad when it paused at a checkpoint.
+    #[serde(default)]
+    pub suspended: Option<Scratchpad>,
+    /// Number of agent turns taken so far.
+    pub turns: usize,
+}
+
+impl TeamState {
+    /// A state starting from the given messages.
+    pub fn new(messages: Vec<Message>) -> Self {
+        Self {
+            messages,
+            ..Self::default()
+        }
+    }
+}
+
+/// Why a [`SupervisorStep`] run stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TeamStop {
+    /// The router chose [`Next::Finish`].
+    Finished,
+    /// The maximum number of agent turns was reached.
+    MaxTurns,
+}
+
+/// The result of a [`SupervisorStep`] run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TeamOutcome {
+    /// The latest assistant reply, when the router finished the run.
+    pub answer: Option<Message>,
+    /// The agent that took the last turn.
+    pub agent: Option<String>,
+    /// Why the run stopped.
+    pub stop: TeamStop,
+}
+
+/// A member of a [`SupervisorStep`], such as an [`AgentStep`](super::AgentStep).
+pub type TeamMember = Box<dyn StateStep<Input = Vec<Message>, Output = AgentOutcome, State = Scratchpad>>;
+
+/// A supervisor loop over named agents sharing one conversation.
+///
+/// Each run appends its input to the conversation and then repeats:
+///
+/// 1. pick the next agent — the target of the last handoff if there was one,
+///    otherwise whatever the router returns for the conversation so far;
+/// 2. run that agent on a fresh [`Scratchpad`] holding the conversation, and
+///    keep the messages it added, naming its replies after it.
+///
+/// The run stops when the router returns [`Next::Finish`] or after
+/// `max_turns` agent turns; a handoff made on the last turn stays in
+/// [`TeamState::active`] for the next run. Each agent runs under
+/// [`ExecutionContext::with_agent`] with its name and the router under the
+/// supervisor's name; every change of agent emits a
+/// [`WorkflowEvent::AgentHandoff`] event.
+///
+/// An [`Error::Checkpoint`] from an agent is re-raised with `data` holding the
+/// `agent`, its original checkpoint data as `pending`, and the `state` to
+/// resume from; the agent's scratchpad is kept in [`TeamState::suspended`]
+/// and the agent resumes first on the next run.
+///
+/// # Example
+///
+/// ```rust
+/// use llm_workflow::{ExecutionContext, LambdaStep, StateStep};
+/// use llm_workflow::agent::AgentStep;
+/// use llm_workflow::agent::team::{Next, SupervisorStep, TeamState, TeamStop};
+/// use llm_workflow::chat::{Message, Role};
+/// use llm_workflow::tool::{ToolExecutorStep, ToolRegistry};
+///
+/// # tokio_test::block_on(async {
+/// let writer = AgentStep::new(
+///     LambdaStep::new(|_messages: Vec<Message>| async move {
+///         Ok::<Message, llm_workflow::Error>(Message::assistant("A haiku."))
+///     }),
+///     ToolExecutorStep::new(ToolRegistry::new()),
+///     3,
+/// );
+/// // Route to the writer until it has replied, then finish.
+/// let router = LambdaStep::new(|messages: Vec<Message>| async move {
+///     Ok::<Next, llm_workflow::Error>(match messages.last().unwrap().role {
+///         Role::Assistant => Next::Finish,
+///         _ => Next::Agent("writer".to_string()),
+///     })
+/// });
+/// let team = SupervisorStep::new(router, 5).with_agent("writer", writer);
+///
+/// let ctx = ExecutionContext::new();
+/// let (outcome, state) = team.run(&ctx, TeamState::default(), vec![Message::user("Poem?")]).await.unwrap();
+/// assert_eq!(outcome.stop, TeamStop::Finished);
+/// assert_eq!(outcome.answer.unwrap().name.as_deref(), Some("writer"));
+/// assert_eq!(state.turns, 1);
+/// # });
+/// ```
+pub struct SupervisorStep<R> {
+    router: R,
+    agents: BTreeMap<String, TeamMember>,
+    max_turns: usize,
+    name: String,
+}
+
+impl<R> SupervisorStep<R>
+where
+    R: Step<Input = Vec<Message>, Output = Next>,
+{
+    /// Create a supervisor taking at most `max_turns` agent turns per run.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_turns` is zero.
+    pub fn new(router: R, max_turns: usize) -> Self {
+        assert!(max_turns > 0, "max_turns must be greater than zero");
+        Self {
+            router,
+            agents: BTreeMap::new(),
+            max_turns,
+            name: "supervisor".to_string(),
+        }
+    }
+
+    /// Add a named agent, replacing any existing agent with the same name.
+    pub fn with_agent<A>(mut self, name: impl Into<String>, agent: A) -> Self
+    where
+        A: StateStep<Input = Vec<Message>, Output = AgentOutcome, State = Scratchpad> + 'static,
+    {
+        self.agents.insert(name.into(), Box::new(agent));
+        self
+    }
+
+    /// Set a human-readable name, used in emitted events.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Names of the agents, sorted.
+    pub fn agents(&self) -> impl Iterator<Item = &str> {
+        self.agents.keys().map(String::as_str)
+    }
+
+    fn member(&self, name: &str) -> Result<&TeamMember> {
+        self.agents
+            .get(name)
+            .ok_or_else(|| Error::Validation(format!("unknown agent '{name}'")))
+    }
+
+    fn hand_off(&self, ctx: &ExecutionContext, from: Option<String>, to: &str) {
+        ctx.emit(WorkflowEvent::AgentHandoff {
+            step_name: self.name.clone(),
+            from,
+            to: to.to_string(),
+        });
+    }
+
+    async fn run_loop(
+        &self,
+        ctx: &ExecutionContext,
+        mut state: TeamState,
+        input: Vec<Message>,
+    ) -> Result<(TeamOutcome, TeamState)> {
+        // A suspended agent receives the input itself when it resumes.
+        let mut resume_input = match state.suspended {
+            Some(_) => input,
+            None => {
+                state.messages.extend(input);
+                Vec::new()
+            }
+        };
+        let mut last = None;
+        let mut turns = 0;
+
+        loop {
+            // Checked before picking an agent so a pending handoff stays in
+            // `active` for the next run.
+            if turns >= self.max_turns {
+                let outcome = TeamOutcome {
+                    answer: None,
+                    agent: last,
+                    stop: TeamStop::MaxTurns,
+                };
+                return Ok((outcome, state));
+            }
+
+            let name = match state.active.take() {
+                Some(name) => name,
+                None => match self.router.run(&ctx.with_agent(&self.name), state.messages.clone()).await? {
+                    Next::Finish => {
+                        let answer = state.messages.iter().rev().find(|m| m.role == Role::Assistant).cloned();
+                        let outcome = TeamOutcome {
+                            answer,
+                            agent: last,
+                            stop: TeamStop::Finished,
+                        };
+                        return Ok((outcome, state));
+                    }
+                    Next::Agent(name) => {
+                        self.hand_off(ctx, last.clone(), &name);
+                        name
+                    }
+                },
+            };
+            let agent = self.member(&name)?;
+
+            // The shared conversation only changes once a turn completes, so
+            // even a resumed agent's messages begin at its current end.
+            let start = state.messages.len();
+            let pad = state
+                .suspended
+                .take()
+                .unwrap_or_else(|| Scratchpad::new(state.messages.clone()));
+            let input = std::mem::take(&mut resume_input);
+            let (outcome, pad) = match agent.run(&ctx.with_agent(&name), pad, input).await {
+                Err(Error::Checkpoint { data, .. }) => {
+                    state.suspended = data
+                        .get("scratchpad")
+                        .and_then(|pad| serde_json::from_value(pad.clone()).ok());
+                    state.active = Some(name.clone());
+                    return Err(Error::Checkpoint {
+                        step_name: self.name.clone(),
+                        data: json!({"agent": name, "pending": data, "state": state}),
+                    });
+                }
+                result => result?,
+            };
+
+            turns += 1;
+            state.turns += 1;
+            state.messages = pad.messages;
+            let start = start.min(state.messages.len());
+            for message in &mut state.messages[start..] {
+                if message.role == Role::Assistant && message.name.is_none() {
+                    message.name = Some(name.clone());
+                }
+            }
+            if let AgentStop::Handoff { to } = outcome.stop {
+                self.hand_off(ctx, Some(name.clone()), &to);
+                state.active = Some(to);
+            }
+            last = Some(name);
+        }
+    }
+}
+
+impl<R> StateStep for SupervisorStep<R>
+where
+    R: Step<Input = Vec<Message>, Output = Next>,
+{
+    type Input = Vec<Message>;
+    type Output = TeamOutcome;
+    type State = TeamState;
+
+    #[allow(clippy::type_complexity)]
+    fn run<'life0, 'async_trait>(
+        &'life0 self,
+        ctx: &'life0 ExecutionContext,
+        state: Self::State,
+        input: Self::Input,
+    ) -> std::pin::Pin<
+        Box<dyn Future<Output = Result<(TeamOutcome, TeamState)>> + Send + 'async_trait>,
+    >
+    where
+        'life0: 'async_trait,
+        Self: 'async_trait,
+    {
+        Box::pin(self.run_loop(ctx, state, input))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::AgentStep;
+    use crate::chat::ToolCall;
+    use crate::checkpoint::ApprovalStore;
+    use crate::tool::{FnTool, ToolExecutorStep, ToolRegistry};
+    use crate::LambdaStep;
+    use serde_json::Value;
+
+    /// A triage agent that hands off to billing, and a billing agent that
+    /// looks up an invoice and answers.
+    fn team(approvals: ApprovalStore, max_turns: usize) -> SupervisorStep<impl Step<Input = Vec<Message>, Output = Next>> {
+        let triage = AgentStep::new(
+            LambdaStep::new(|_messages: Vec<Message>| async move {
+                Ok::<Message, Error>(Message::tool_calls(vec![ToolCall::new("h1", "transfer_to_billing", json!({}))]))
+            }),
+            ToolExecutorStep::new(ToolRegistry::new()),
+            3,
+        )
+        .with_handoff(Handoff::new("billing", "Invoices."));
+
+        let lookup = FnTool::new("invoice", "Look up an invoice.", json!({"type": "object"}), |ctx: ExecutionContext, _args: Value| async move {
+            ctx.record_tokens(1, 1);
+            Ok(json!("paid"))
+        });
+        let billing = AgentStep::new(
+            LambdaStep::new(|messages: Vec<Message>| async move {
+                Ok::<Message, Error>(match messages.last().unwrap().role {
+                    Role::Tool if messages.last().unwrap().text() == "paid" => Message::assistant("Your invoice is paid."),
+                    _ => Message::tool_calls(vec![ToolCall::new("i1", "invoice", json!({}))]),
+                })
+            }),
+            ToolExecutorStep::new(ToolRegistry::new().with(lookup)).require_approval("invoice")
+                .with_approvals(approvals),
+            3,
+        );
+
+        let router = LambdaStep::new(|messages: Vec<Message>| async move {
+            Ok::<Next, Error>(match messages.last().unwrap().role {
+                Role::User => Next::Agent("triage".to_string()),
+                _ => Next::Finish,
+            })
+        });
+        SupervisorStep::new(router, max_turns)
+            .with_agent("triage", triage)
+            .with_agent("billing", billing)
+    }
+
+    #[tokio::test]
+    async fn test_handoff_checkpoint_and_attribution() {
+        let approvals = ApprovalStore::new();
+        let team = team(approvals.clone(), 5);
+        let ctx = ExecutionContext::new();
+        let err = team
+            .run(&ctx, TeamState::default(), vec![Message::user("Is my invoice paid?")])
+            .await
+            .unwrap_err();
+        let Error::Checkpoint { step_name, data } = err else {
+            panic!("expected checkpoint");
+        };
+        assert_eq!(step_name, "supervisor");
+        assert_eq!(data["agent"], "billing");
+        assert_eq!(data["pending"]["awaiting"]["id"], "i1");
+        let state: TeamState = serde_json::from_value(data["state"].clone()).unwrap();
+        assert_eq!(state.active.as_deref(), Some("billing"));
+        assert!(state.suspended.is_some());
+
+        let awaiting: ToolCall = serde_json::from_value(data["pending"]["awaiting"].clone()).unwrap();
+        approvals.approve_call(&awaiting);
+        let (outcome, state) = team.run(&ctx, state, vec![]).await.unwrap();
+        assert_eq!(outcome.stop, TeamStop::Finished);
+        assert_eq!(outcome.agent.as_deref(), Some("billing"));
+        let answer = outcome.answer.unwrap();
+        assert_eq!(answer.text(), "Your invoice is paid.");
+        assert_eq!(answer.name.as_deref(), Some("billing"));
+        assert_eq!(state.turns, 2);
+        assert_eq!(state.messages[1].name.as_deref(), Some("triage"));
+        // The tool call billing made before pausing is still attributed to it.
+        assert_eq!(state.messages[3].tool_calls[0].id, "i1");
+        assert_eq!(state.messages[3].name.as_deref(), Some("billing"));
+
+        let traces = ctx.trace_snapshot();
+        let handoffs: Vec<_> = traces
+            .iter()
+            .filter_map(|e| match &e.event {
+                WorkflowEvent::AgentHandoff { from, to, .. } => Some((from.clone(), to.clone())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(handoffs, [(None, "triage".to_string()), (Some("triage".to_string()), "billing".to_string())]);
+        for entry in &traces {
+            match &entry.event {
+                WorkflowEvent::ModelCall { .. } => assert!(entry.agent.is_some()),
+                WorkflowEvent::ToolInvocation { tool_name, .. } => {
+                    assert_eq!(tool_name, "invoice");
+                    assert_eq!(entry.agent.as_deref(), Some("billing"));
+                }
+                _ => {}
+            }
+        }
+        let model_calls = |agent: &str| {
+            traces
+                .iter()
+                .filter(|e| matches!(e.event, WorkflowEvent::ModelCall { .. }) && e.agent.as_deref() == Some(agent))
+                .count()
+        };
+        assert_eq!((model_calls("triage"), model_calls("billing")), (1, 2));
+    }
+
+    #[tokio::test]
+    async fn test_stops_at_max_turns() {
+        let router = LambdaStep::new(|_messages: Vec<Message>| async move { Ok::<Next, Error>(Next::Agent("echo".to_string())) });
+        let echo = AgentStep::new(
+            LambdaStep::new(|_messages: Vec<Message>| async move { Ok::<Message, Error>(Message::assistant("hi")) }),
+            ToolExecutorStep::new(ToolRegistry::new()),
+            1,
+        );
+        let team = SupervisorStep::new(router, 2).with_agent("echo", echo);
+        let ctx = ExecutionContext::new();
+        let (outcome, state) = team.run(&ctx, TeamState::default(), vec![]).await.unwrap();
+        assert_eq!(outcome.stop, TeamStop::MaxTurns);
+        assert_eq!(outcome.agent.as_deref(), Some("echo"));
+        assert_eq!(state.turns, 2);
+        assert_eq!(state.messages.len(), 2);
+
+        // Routed handoffs name the agent that ran before.
+        let handoffs: Vec<_> = ctx
+            .trace_snapshot()
+            .into_iter()
+            .filter_map(|e| match e.event {
+                WorkflowEvent::AgentHandoff { from, to, .. } => Some((from, to)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(handoffs, [(None, "echo".to_string()), (Some("echo".to_string()), "echo".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn test_max_turns_after_handoff_keeps_active_agent() {
+        let team = team(ApprovalStore::new(), 1);
+        let ctx = ExecutionContext::new();
+        let (outcome, state) = team
+            .run(&ctx, TeamState::default(), vec![Message::user("Is my invoice paid?")])
+            .await
+            .unwrap();
+        assert_eq!(outcome.stop, TeamStop::MaxTurns);
+        assert_eq!(outcome.agent.as_deref(), Some("triage"));
+        assert_eq!(state.active.as_deref(), Some("billing"));
+        assert_eq!(state.turns, 1);
+
+        // The next run picks up with billing without consulting the router.
+        let err = team.run(&ctx, state, vec![]).await.unwrap_err();
+        let Error::Checkpoint { data, .. } = err else {
+            panic!("expected checkpoint");
+        };
+        assert_eq!(data["agent"], "billing");
+        let routed = ctx
+            .trace_snapshot()
+            .iter()
+            .filter(|e| matches!(&e.event, WorkflowEvent::AgentHandoff { from: None, .. }))
+            .count();
+        assert_eq!(routed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_agent_is_rejected() {
+        let router = LambdaStep::new(|_messages: Vec<Message>| async move { Ok::<Next, Error>(Next::Agent("ghost".to_string())) });
+        let team = SupervisorStep::new(router, 2);
+        let err = team.run(&ExecutionContext::new(), TeamState::default(), vec![]).await.unwrap_err();
+        assert!(matches!(err, Error::Validation(msg) if msg == "unknown agent 'ghost'"));
+    }
+}
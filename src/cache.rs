@@ -0,0 +1,580 @@
+//! Response caching for steps.
+//!
+//! [`CachedStep`] memoizes a step's output keyed on a hash of its serialized
+//! input, the step name and a version string. Entries live in any [`Cache`]
+//! implementation; [`InMemoryCache`] (LRU) and [`JsonFileCache`] (one JSON
+//! file per entry) are provided. Per-run behaviour such as bypassing the cache
+//! or overriding the TTL is controlled by the [`CachePolicy`] carried on the
+//! [`ExecutionContext`].
+
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{Error, ExecutionContext, Result, WorkflowEvent, step::Step};
+
+/// A cached step output along with bookkeeping used for TTLs and metrics.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    /// The serialized step output.
+    pub value: serde_json::Value,
+    /// Unix epoch milliseconds when the entry was stored.
+    pub created_at: u128,
+    /// Tokens the step consumed when the entry was produced.
+    pub tokens: usize,
+}
+
+impl CacheEntry {
+    /// Create an entry stamped with the current time.
+    #[must_use]
+    pub fn new(value: serde_json::Value, tokens: usize) -> Self {
+        Self {
+            value,
+            created_at: now_ms(),
+            tokens,
+        }
+    }
+
+    /// Age of the entry relative to now.
+    #[must_use]
+    pub fn age(&self) -> Duration {
+        let age_ms = now_ms().saturating_sub(self.created_at);
+        Duration::from_millis(u64::try_from(age_ms).unwrap_or(u64::MAX))
+    }
+}
+
+/// A key-value store for cached step outputs.
+#[async_trait]
+pub trait Cache: Send + Sync {
+    /// Look up an entry by key.
+    async fn get(&self, key: &str) -> Result<Option<CacheEntry>>;
+
+    /// Store an entry, replacing any existing entry for the key.
+    async fn put(&self, key: &str, entry: CacheEntry) -> Result<()>;
+
+    /// Remove an entry, if present.
+    async fn remove(&self, key: &str) -> Result<()>;
+}
+
+/// How a run interacts with caches.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CacheMode {
+    /// Read cached entries and store fresh results.
+    #[default]
+    ReadWrite,
+    /// Ignore cached entries but store fresh results.
+    Refresh,
+    /// Neither read nor write the cache.
+    Bypass,
+}
+
+/// Per-run cache settings carried on the [`ExecutionContext`].
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+/// use llm_workflow::ExecutionContext;
+/// use llm_workflow::cache::{CacheMode, CachePolicy};
+///
+/// let ctx = ExecutionContext::new().with_cache_policy(CachePolicy {
+///     mode: CacheMode::Refresh,
+///     ttl: Some(Duration::from_secs(60)),
+/// });
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CachePolicy {
+    /// Whether to read and/or write cached entries.
+    pub mode: CacheMode,
+    /// Overrides each cached step's TTL for this run when set.
+    pub ttl: Option<Duration>,
+}
+
+/// An in-memory cache that evicts the least recently used entry when full.
+#[derive(Debug)]
+pub struct InMemoryCache {
+    capacity: usize,
+    state: Mutex<LruState>,
+}
+
+#[derive(Debug, Default)]
+struct LruState {
+    tick: u64,
+    entries: HashMap<String, (CacheEntry, u64)>,
+    order: BTreeMap<u64, String>,
+}
+
+impl LruState {
+    fn touch(&mut self, key: &str) {
+        self.tick += 1;
+        let tick = self.tick;
+        if let Some((_, last)) = self.entries.get_mut(key) {
+            self.order.remove(last);
+            *last = tick;
+            self.order.insert(tick, key.to_string());
+        }
+    }
+}
+
+impl InMemoryCache {
+    /// Create a cache holding at most `capacity` entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must be greater than zero");
+        Self {
+            capacity,
+            state: Mutex::new(LruState::default()),
+        }
+    }
+
+    /// Number of entries currently cached.
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().entries.len()
+    }
+
+    /// Whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[async_trait]
+impl Cache for InMemoryCache {
+    async fn get(&self, key: &str) -> Result<Option<CacheEntry>> {
+        let mut state = self.state.lock().unwrap();
+        state.touch(key);
+        Ok(state.entries.get(key).map(|(entry, _)| entry.clone()))
+    }
+
+    async fn put(&self, key: &str, entry: CacheEntry) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if let Some((existing, _)) = state.entries.get_mut(key) {
+            *existing = entry;
+            state.touch(key);
+            return Ok(());
+        }
+        while state.entries.len() >= self.capacity {
+            let Some((_, oldest)) = state.order.pop_first() else { break };
+            state.entries.remove(&oldest);
+        }
+        state.tick += 1;
+        let tick = state.tick;
+        state.entries.insert(key.to_string(), (entry, tick));
+        state.order.insert(tick, key.to_string());
+        Ok(())
+    }
+
+    async fn remove(&self, key: &str) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if let Some((_, tick)) = state.entries.remove(key) {
+            state.order.remove(&tick);
+        }
+        Ok(())
+    }
+}
+
+/// An on-disk cache storing each entry as `<key>.json` in a directory.
+///
+/// The directory is created on first write. Entries survive process restarts,
+/// which makes this useful for avoiding repeat model calls during development.
+/// Give the wrapping [`CachedStep`] an explicit [`CachedStep::with_name`]:
+/// the default name comes from [`std::any::type_name`], which is not stable
+/// across compiler versions or refactors, so a rebuild can silently orphan
+/// every stored entry.
+#[derive(Debug, Clone)]
+pub struct JsonFileCache {
+    dir: PathBuf,
+}
+
+impl JsonFileCache {
+    /// Create a cache rooted at `dir`.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.json"))
+    }
+}
+
+#[async_trait]
+impl Cache for JsonFileCache {
+    async fn get(&self, key: &str) -> Result<Option<CacheEntry>> {
+        match tokio::fs::read(self.path(key)).await {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(Error::Execution(format!("cache read failed: {e}"))),
+        }
+    }
+
+    async fn put(&self, key: &str, entry: CacheEntry) -> Result<()> {
+        let io_err = |e: std::io::Error| Error::Execution(format!("cache write failed: {e}"));
+        tokio::fs::create_dir_all(&self.dir).await.map_err(io_err)?;
+        let bytes = serde_json::to_vec_pretty(&entry)?;
+        // Concurrent writers of the same key each need their own temp file, or
+        // one could rename another's half-written bytes into place.
+        static NEXT_TMP: AtomicU64 = AtomicU64::new(0);
+        let unique = NEXT_TMP.fetch_add(1, Ordering::Relaxed);
+        let tmp = self
+            .dir
+            .join(format!("{key}.json.{}.{unique}.tmp", std::process::id()));
+        tokio::fs::write(&tmp, bytes).await.map_err(io_err)?;
+        tokio::fs::rename(&tmp, self.path(key)).await.map_err(io_err)
+    }
+
+    async fn remove(&self, key: &str) -> Result<()> {
+        match tokio::fs::remove_file(self.path(key)).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(Error::Execution(format!("cache remove failed: {e}"))),
+        }
+    }
+}
+
+/// Wraps a step so identical inputs are served from a [`Cache`].
+///
+/// The cache key is a stable hash of the step name, a version string, and the
+/// JSON-serialized input; bump the version to invalidate entries after
+/// changing a prompt. On a miss the inner step runs under an
+/// [`ExecutionContext::child`] scope so the tokens it consumes can be stored
+/// with the entry; a later hit credits those tokens to
+/// [`WorkflowMetrics::tokens_saved`](crate::WorkflowMetrics::tokens_saved).
+/// Every lookup emits a [`WorkflowEvent::CacheLookup`] event.
+///
+/// Cache read and write failures are logged and treated as misses so that a
+/// broken cache never fails the workflow.
+///
+/// # Example
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use llm_workflow::{LambdaStep, Step, ExecutionContext};
+/// use llm_workflow::cache::{CachedStep, InMemoryCache};
+///
+/// # tokio_test::block_on(async {
+/// let step = LambdaStep::new(|q: String| async move { Ok::<usize, llm_workflow::Error>(q.len()) });
+/// let cached = CachedStep::new(step, Arc::new(InMemoryCache::new(128))).with_name("length");
+///
+/// let ctx = ExecutionContext::new();
+/// cached.run(&ctx, "hello".to_string()).await.unwrap();
+/// cached.run(&ctx, "hello".to_string()).await.unwrap();
+///
+/// let metrics = ctx.snapshot();
+/// assert_eq!((metrics.cache_misses, metrics.cache_hits), (1, 1));
+/// # });
+/// ```
+pub struct CachedStep<S> {
+    inner: S,
+    cache: Arc<dyn Cache>,
+    name: String,
+    version: String,
+    ttl: Option<Duration>,
+}
+
+impl<S: Step> CachedStep<S> {
+    /// Wrap `inner` with the given cache. The step name defaults to the inner step's name.
+    ///
+    /// An unnamed inner step reports its [`std::any::type_name`], which can
+    /// change between builds; set [`CachedStep::with_name`] when the cache
+    /// outlives the process, such as a [`JsonFileCache`].
+    pub fn new(inner: S, cache: Arc<dyn Cache>) -> Self {
+        let name = inner.name().to_string();
+        Self {
+            inner,
+            cache,
+            name,
+            version: String::new(),
+            ttl: None,
+        }
+    }
+
+    /// Set the name used in cache keys and events.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Set a version string mixed into cache keys.
+    pub fn with_version(mut self, version: impl Into<String>) -> Self {
+        self.version = version.into();
+        self
+    }
+
+    /// Treat entries older than `ttl` as misses.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Access the inner step.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Compute the cache key for an input.
+    pub fn cache_key<I: Serialize>(&self, input: &I) -> Result<String> {
+        let serialized = serde_json::to_string(input)?;
+        let mut hash = Fnv1a::default();
+        hash.write(self.name.as_bytes());
+        hash.write(&[0]);
+        hash.write(self.version.as_bytes());
+        hash.write(&[0]);
+        hash.write(serialized.as_bytes());
+        Ok(format!("{:016x}", hash.0))
+    }
+
+    async fn lookup<O: DeserializeOwned>(&self, key: &str, ttl: Option<Duration>) -> Option<(O, usize)> {
+        let entry = match self.cache.get(key).await {
+            Ok(Some(entry)) => entry,
+            Ok(None) => return None,
+            Err(e) => {
+                tracing::warn!(step = %self.name, error = %e, "cache lookup failed");
+                return None;
+            }
+        };
+        if ttl.is_some_and(|ttl| entry.age() > ttl) {
+            return None;
+        }
+        let tokens = entry.tokens;
+        serde_json::from_value(entry.value).ok().map(|output| (output, tokens))
+    }
+}
+
+#[async_trait]
+impl<S> Step for CachedStep<S>
+where
+    S: Step,
+    S::Input: Serialize + Sync + 'static,
+    S::Output: Serialize + DeserializeOwned + 'static,
+{
+    type Input = S::Input;
+    type Output = S::Output;
+
+    async fn run(&self, ctx: &ExecutionContext, input: S::Input) -> Result<S::Output> {
+        let policy = ctx.cache_policy;
+        if policy.mode == CacheMode::Bypass {
+            return self.inner.run(ctx, input).await;
+        }
+
+        let key = self.cache_key(&input)?;
+        if policy.mode == CacheMode::ReadWrite {
+            if let Some((output, tokens)) = self.lookup(&key, policy.ttl.or(self.ttl)).await {
+                ctx.record_cache_hit(tokens);
+                ctx.emit(WorkflowEvent::CacheLookup {
+                    step_name: self.name.clone(),
+                    key,
+                    hit: true,
+                });
+                return Ok(output);
+            }
+        }
+        ctx.record_cache_miss();
+        ctx.emit(WorkflowEvent::CacheLookup {
+            step_name: self.name.clone(),
+            key: key.clone(),
+            hit: false,
+        });
+
+        let child = ctx.child();
+        let result = self.inner.run(&child, input).await;
+        let usage = child.snapshot();
+        ctx.merge(&child);
+        let output = result?;
+
+        match serde_json::to_value(&output) {
+            Ok(value) => {
+                let tokens = usage.prompt_token_count + usage.completion_token_count;
+                if let Err(e) = self.cache.put(&key, CacheEntry::new(value, tokens)).await {
+                    tracing::warn!(step = %self.name, error = %e, "cache store failed");
+                }
+            }
+            Err(e) => tracing::warn!(step = %self.name, error = %e, "cache serialization failed"),
+        }
+        Ok(output)
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// 64-bit FNV-1a, used because its output is stable across Rust versions,
+/// which matters for on-disk cache keys and hashed embeddings.
+pub(crate) struct Fnv1a(pub(crate) u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Fnv1a {
+    /// Hash `bytes` in one call.
+    pub(crate) fn hash(bytes: &[u8]) -> u64 {
+        let mut hash = Self::default();
+        hash.write(bytes);
+        hash.0
+    }
+
+    pub(crate) fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+fn now_ms() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_millis()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct Counting {
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Step for Counting {
+        type Input = String;
+        type Output = String;
+
+        async fn run(&self, ctx: &ExecutionContext, input: String) -> Result<String> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            ctx.record_tokens(40, 2);
+            Ok(input.to_uppercase())
+        }
+    }
+
+    fn counting(cache: Arc<dyn Cache>) -> (CachedStep<Counting>, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let step = Counting {
+            calls: Arc::clone(&calls),
+        };
+        (CachedStep::new(step, cache).with_name("upper"), calls)
+    }
+
+    #[tokio::test]
+    async fn test_hit_skips_inner_and_records_savings() {
+        let (cached, calls) = counting(Arc::new(InMemoryCache::new(8)));
+        let ctx = ExecutionContext::new();
+
+        assert_eq!(cached.run(&ctx, "abc".to_string()).await.unwrap(), "ABC");
+        assert_eq!(cached.run(&ctx, "abc".to_string()).await.unwrap(), "ABC");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let metrics = ctx.snapshot();
+        assert_eq!(metrics.cache_hits, 1);
+        assert_eq!(metrics.cache_misses, 1);
+        assert_eq!(metrics.tokens_saved, 42);
+        assert_eq!(metrics.total_tokens(), 42, "only the miss consumed tokens");
+    }
+
+    #[test]
+    fn test_version_changes_key() {
+        let cache: Arc<dyn Cache> = Arc::new(InMemoryCache::new(8));
+        let (v1, _) = counting(Arc::clone(&cache));
+        let (v2, _) = counting(cache);
+        let v2 = v2.with_version("2");
+        assert_ne!(v1.cache_key(&"x").unwrap(), v2.cache_key(&"x").unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_ttl_expires_entries() {
+        let (cached, calls) = counting(Arc::new(InMemoryCache::new(8)));
+        let cached = cached.with_ttl(Duration::from_millis(20));
+        let ctx = ExecutionContext::new();
+
+        cached.run(&ctx, "a".to_string()).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        cached.run(&ctx, "a".to_string()).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_policy_modes() {
+        let (cached, calls) = counting(Arc::new(InMemoryCache::new(8)));
+        let bypass = ExecutionContext::new().with_cache_policy(CachePolicy {
+            mode: CacheMode::Bypass,
+            ttl: None,
+        });
+        cached.run(&bypass, "a".to_string()).await.unwrap();
+        assert_eq!(bypass.snapshot().cache_misses, 0, "bypass does not touch the cache");
+
+        let refresh = ExecutionContext::new().with_cache_policy(CachePolicy {
+            mode: CacheMode::Refresh,
+            ttl: None,
+        });
+        cached.run(&refresh, "a".to_string()).await.unwrap();
+
+        let normal = ExecutionContext::new();
+        cached.run(&normal, "a".to_string()).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2, "refresh stored the entry");
+        assert_eq!(normal.snapshot().cache_hits, 1);
+    }
+
+    #[tokio::test]
+    async fn test_lru_evicts_least_recently_used() {
+        let cache = InMemoryCache::new(2);
+        cache.put("a", CacheEntry::new(serde_json::json!(1), 0)).await.unwrap();
+        cache.put("b", CacheEntry::new(serde_json::json!(2), 0)).await.unwrap();
+        cache.get("a").await.unwrap();
+        cache.put("c", CacheEntry::new(serde_json::json!(3), 0)).await.unwrap();
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get("a").await.unwrap().is_some());
+        assert!(cache.get("b").await.unwrap().is_none());
+        assert!(cache.get("c").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_json_file_cache_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("llm-workflow-cache-{}", now_ms()));
+        let cache = JsonFileCache::new(&dir);
+
+        assert!(cache.get("k").await.unwrap().is_none());
+        let entry = CacheEntry::new(serde_json::json!({"answer": 42}), 7);
+        cache.put("k", entry.clone()).await.unwrap();
+        assert_eq!(cache.get("k").await.unwrap(), Some(entry));
+
+        cache.remove("k").await.unwrap();
+        assert!(cache.get("k").await.unwrap().is_none());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_json_file_cache_concurrent_puts_of_one_key() {
+        let dir = std::env::temp_dir().join(format!("llm-workflow-cache-race-{}", now_ms()));
+        let cache = Arc::new(JsonFileCache::new(&dir));
+
+        let writes = (0..8).map(|i| {
+            let cache = cache.clone();
+            tokio::spawn(async move {
+                let entry = CacheEntry::new(serde_json::json!({ "writer": i }), i);
+                cache.put("k", entry).await
+            })
+        });
+        for write in futures::future::join_all(writes).await {
+            write.unwrap().unwrap();
+        }
+
+        assert!(cache.get("k").await.unwrap().is_some());
+        let leftovers = std::fs::read_dir(&dir).unwrap().count();
+        assert_eq!(leftovers, 1);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
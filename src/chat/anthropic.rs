@@ -0,0 +1,316 @@
+//! Conversions to and from the Anthropic Messages API shape.
+//!
+//! Anthropic takes system prompts as a top-level field and only accepts
+//! `user` and `assistant` messages, so [`to_request`] hoists system messages
+//! and sends tool results as `tool_result` blocks inside user messages.
+//! Consecutive tool results, and any user message that follows them, are
+//! merged into a single user message.
+
+use serde_json::{json, Value};
+
+use super::{ContentPart, Message, Role, ToolCall, ToolResult};
+use crate::{Error, Result};
+
+/// Convert a conversation into the `system` and `messages` fields of a request.
+///
+/// Returns the joined system prompt (if any) and the `messages` array, or an
+/// [`Error::Validation`] for a user or assistant message with neither
+/// non-empty content nor tool calls, which the API rejects, or for a tool
+/// message without a [`ToolResult`] naming the call it answers.
+pub fn to_request(messages: &[Message]) -> Result<(Option<String>, Value)> {
+    let mut system = Vec::new();
+    let mut out: Vec<Value> = Vec::new();
+    let mut merging_tool_results = false;
+
+    for message in messages {
+        match message.role {
+            Role::System => {
+                system.push(message.text());
+                merging_tool_results = false;
+            }
+            Role::Tool => {
+                let block = tool_result_block(message)?;
+                match out.last_mut() {
+                    Some(last) if merging_tool_results => {
+                        last["content"].as_array_mut().expect("content blocks").push(block);
+                    }
+                    _ => out.push(json!({"role": "user", "content": [block]})),
+                }
+                merging_tool_results = true;
+            }
+            Role::User | Role::Assistant => {
+                let value = to_value(message);
+                if value["content"].as_array().is_none_or(Vec::is_empty) {
+                    return Err(invalid(format!("empty {:?} message", message.role)));
+                }
+                match out.last_mut() {
+                    Some(last) if merging_tool_results && message.role == Role::User => {
+                        let blocks = last["content"].as_array_mut().expect("content blocks");
+                        blocks.extend(value["content"].as_array().expect("content blocks").iter().cloned());
+                    }
+                    _ => out.push(value),
+                }
+                merging_tool_results = false;
+            }
+        }
+    }
+
+    let system = (!system.is_empty()).then(|| system.join("\n\n"));
+    Ok((system, Value::Array(out)))
+}
+
+/// Convert a single user or assistant message to an Anthropic message object.
+///
+/// System and tool messages are handled by [`to_request`]. Empty text parts
+/// are left out, since Anthropic rejects empty text blocks.
+pub fn to_value(message: &Message) -> Value {
+    let mut blocks: Vec<Value> = message
+        .content
+        .iter()
+        .filter(|part| !matches!(part, ContentPart::Text { text } if text.is_empty()))
+        .map(part_to_block)
+        .collect();
+    blocks.extend(message.tool_calls.iter().map(|call| {
+        json!({"type": "tool_use", "id": call.id, "name": call.name, "input": call.arguments})
+    }));
+    let role = match message.role {
+        Role::Assistant => "assistant",
+        _ => "user",
+    };
+    json!({"role": role, "content": blocks})
+}
+
+fn tool_result_block(message: &Message) -> Result<Value> {
+    let result = message
+        .tool_result
+        .as_ref()
+        .ok_or_else(|| invalid("tool message without tool_result"))?;
+    Ok(json!({
+        "type": "tool_result",
+        "tool_use_id": result.call_id,
+        "content": message.text(),
+        "is_error": result.is_error,
+    }))
+}
+
+fn part_to_block(part: &ContentPart) -> Value {
+    match part {
+        ContentPart::Text { text } => json!({"type": "text", "text": text}),
+        ContentPart::Image { url } => match parse_data_url(url) {
+            Some((media_type, data)) => json!({
+                "type": "image",
+                "source": {"type": "base64", "media_type": media_type, "data": data},
+            }),
+            None => json!({"type": "image", "source": {"type": "url", "url": url}}),
+        },
+    }
+}
+
+fn parse_data_url(url: &str) -> Option<(&str, &str)> {
+    let rest = url.strip_prefix("data:")?;
+    let (media_type, data) = rest.split_once(";base64,")?;
+    Some((media_type, data))
+}
+
+fn invalid(msg: impl Into<String>) -> Error {
+    Error::Validation(format!("invalid Anthropic message: {}", msg.into()))
+}
+
+/// Parse an Anthropic message object (a request message or a response body).
+///
+/// A user message carrying `tool_result` blocks yields one [`Role::Tool`]
+/// message per result, followed by a user message for any remaining content.
+pub fn from_value(value: &Value) -> Result<Vec<Message>> {
+    let role = match value["role"].as_str() {
+        Some("user") => Role::User,
+        Some("assistant") => Role::Assistant,
+        other => return Err(invalid(format!("unknown role {other:?}"))),
+    };
+    let blocks = match &value["content"] {
+        Value::String(text) => return Ok(vec![Message::new(role, text.clone())]),
+        Value::Array(blocks) => blocks,
+        other => return Err(invalid(format!("unexpected content {other}"))),
+    };
+
+    let mut results = Vec::new();
+    let mut message = Message {
+        content: Vec::new(),
+        ..Message::new(role, "")
+    };
+    for block in blocks {
+        match block["type"].as_str() {
+            Some("text") => message.content.push(ContentPart::text(
+                block["text"].as_str().ok_or_else(|| invalid("text block without text"))?,
+            )),
+            Some("image") => {
+                let source = &block["source"];
+                let url = match source["type"].as_str() {
+                    Some("base64") => format!(
+                        "data:{};base64,{}",
+                        source["media_type"].as_str().unwrap_or_default(),
+                        source["data"].as_str().unwrap_or_default()
+                    ),
+                    _ => source["url"]
+                        .as_str()
+                        .ok_or_else(|| invalid("image block without source"))?
+                        .to_string(),
+                };
+                message.content.push(ContentPart::image(url));
+            }
+            Some("tool_use") => message.tool_calls.push(ToolCall {
+                id: block["id"].as_str().ok_or_else(|| invalid("tool_use without id"))?.to_string(),
+                name: block["name"]
+                    .as_str()
+                    .ok_or_else(|| invalid("tool_use without name"))?
+                    .to_string(),
+                arguments: block["input"].clone(),
+            }),
+            Some("tool_result") => results.push(tool_result_from_block(block)?),
+            other => return Err(invalid(format!("unsupported content block {other:?}"))),
+        }
+    }
+
+    if !message.content.is_empty() || !message.tool_calls.is_empty() || results.is_empty() {
+        results.push(message);
+    }
+    Ok(results)
+}
+
+fn tool_result_from_block(block: &Value) -> Result<Message> {
+    let text = match &block["content"] {
+        Value::String(text) => text.clone(),
+        Value::Array(parts) => parts.iter().filter_map(|p| p["text"].as_str()).collect(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    };
+    Ok(Message {
+        tool_result: Some(ToolResult {
+            call_id: block["tool_use_id"]
+                .as_str()
+                .ok_or_else(|| invalid("tool_result without tool_use_id"))?
+                .to_string(),
+            is_error: block["is_error"].as_bool().unwrap_or(false),
+        }),
+        ..Message::new(Role::Tool, text)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_hoists_system_and_merges_tool_results() {
+        let conversation = vec![
+            Message::system("Be helpful."),
+            Message::user("Add 1+2 and 3+4"),
+            Message::tool_calls(vec![
+                ToolCall::new("t1", "add", json!({"a": 1, "b": 2})),
+                ToolCall::new("t2", "add", json!({"a": 3, "b": 4})),
+            ]),
+            Message::tool_result("t1", "3"),
+            Message::tool_error("t2", "overflow"),
+        ];
+        let (system, messages) = to_request(&conversation).unwrap();
+        assert_eq!(system.as_deref(), Some("Be helpful."));
+        assert_eq!(
+            messages,
+            json!([
+                {"role": "user", "content": [{"type": "text", "text": "Add 1+2 and 3+4"}]},
+                {"role": "assistant", "content": [
+                    {"type": "tool_use", "id": "t1", "name": "add", "input": {"a": 1, "b": 2}},
+                    {"type": "tool_use", "id": "t2", "name": "add", "input": {"a": 3, "b": 4}},
+                ]},
+                {"role": "user", "content": [
+                    {"type": "tool_result", "tool_use_id": "t1", "content": "3", "is_error": false},
+                    {"type": "tool_result", "tool_use_id": "t2", "content": "overflow", "is_error": true},
+                ]},
+            ])
+        );
+    }
+
+    #[test]
+    fn test_round_trip_through_request_shape() {
+        let conversation = vec![
+            Message::user("hi").with_part(ContentPart::image("data:image/png;base64,AAAA")),
+            Message::assistant("Let me check.")
+                .with_tool_call(ToolCall::new("t1", "lookup", json!({"id": 7}))),
+            Message::tool_result("t1", "found"),
+        ];
+        let (_, messages) = to_request(&conversation).unwrap();
+        assert_eq!(
+            messages[0]["content"][1]["source"],
+            json!({"type": "base64", "media_type": "image/png", "data": "AAAA"})
+        );
+        let parsed: Vec<Message> = messages
+            .as_array()
+            .unwrap()
+            .iter()
+            .flat_map(|m| from_value(m).unwrap())
+            .collect();
+        assert_eq!(parsed, conversation);
+    }
+
+    #[test]
+    fn test_user_message_after_tool_results_joins_their_turn() {
+        let conversation = vec![
+            Message::tool_calls(vec![ToolCall::new("t1", "lookup", json!({}))]),
+            Message::tool_result("t1", "found"),
+            Message::user("Thanks, now summarize."),
+        ];
+        let (_, messages) = to_request(&conversation).unwrap();
+        assert_eq!(
+            messages[1],
+            json!({"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": "t1", "content": "found", "is_error": false},
+                {"type": "text", "text": "Thanks, now summarize."},
+            ]})
+        );
+        assert_eq!(messages.as_array().unwrap().len(), 2);
+        let parsed = from_value(&messages[1]).unwrap();
+        assert_eq!(parsed, conversation[1..]);
+    }
+
+    #[test]
+    fn test_empty_message_is_rejected() {
+        let empty = Message {
+            content: Vec::new(),
+            ..Message::assistant("")
+        };
+        let conversation = vec![Message::user("hi"), empty];
+        assert!(matches!(to_request(&conversation), Err(Error::Validation(_))));
+
+        let blank = vec![Message::user("hi"), Message::assistant("")];
+        assert!(matches!(to_request(&blank), Err(Error::Validation(_))));
+    }
+
+    #[test]
+    fn test_empty_text_is_dropped_beside_tool_calls() {
+        let call = Message {
+            tool_calls: vec![ToolCall::new("t1", "add", json!({}))],
+            ..Message::assistant("")
+        };
+        let (_, messages) = to_request(&[Message::user("hi"), call]).unwrap();
+        assert_eq!(messages[1]["content"].as_array().unwrap().len(), 1);
+        assert_eq!(messages[1]["content"][0]["type"], "tool_use");
+    }
+
+    #[test]
+    fn test_tool_message_without_result_is_rejected() {
+        let orphan = Message::new(Role::Tool, "3");
+        let conversation = vec![Message::user("hi"), orphan];
+        assert!(matches!(to_request(&conversation), Err(Error::Validation(_))));
+    }
+
+    #[test]
+    fn test_parse_response_body() {
+        let response = json!({
+            "id": "msg_01",
+            "role": "assistant",
+            "content": [{"type": "text", "text": "Hello!"}],
+            "stop_reason": "end_turn"
+        });
+        let messages = from_value(&response).unwrap();
+        assert_eq!(messages, vec![Message::assistant("Hello!")]);
+    }
+}
@@ -0,0 +1,319 @@
+//! Canonical chat message types.
+//!
+//! [`Message`] is the crate-wide representation of a chat turn: a [`Role`],
+//! multi-part [`ContentPart`] content, optional [`ToolCall`]s or a
+//! [`ToolResult`], an optional participant name and free-form metadata. Model
+//! steps are expected to take `Vec<Message>` and return a `Message` (see
+//! [`ChatModel`]); the [`openai`] and [`anthropic`] modules convert to and
+//! from those providers' JSON shapes. Messages can be counted by a
+//! [`Tokenizer`](crate::Tokenizer) and kept in a
+//! [`ConversationMemory`](crate::ConversationMemory).
+
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::memory::MemoryMessage;
+use crate::step::Step;
+use crate::tokenizer::CountableMessage;
+
+pub mod anthropic;
+pub mod openai;
+
+/// The author of a [`Message`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// Instructions that frame the conversation.
+    System,
+    /// The end user.
+    User,
+    /// The model.
+    Assistant,
+    /// The result of a tool invocation.
+    Tool,
+}
+
+impl Role {
+    /// The lowercase wire name of the role.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::System => "system",
+            Role::User => "user",
+            Role::Assistant => "assistant",
+            Role::Tool => "tool",
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One part of a multi-part message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentPart {
+    /// Plain text.
+    Text {
+        /// The text.
+        text: String,
+    },
+    /// An image, referenced by URL or as a `data:` URL with base64 content.
+    Image {
+        /// The image URL.
+        url: String,
+    },
+}
+
+impl ContentPart {
+    /// A text part.
+    pub fn text(text: impl Into<String>) -> Self {
+        ContentPart::Text { text: text.into() }
+    }
+
+    /// An image part.
+    pub fn image(url: impl Into<String>) -> Self {
+        ContentPart::Image { url: url.into() }
+    }
+}
+
+/// A request from the model to invoke a tool.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolCall {
+    /// Provider-assigned identifier, echoed back in the [`ToolResult`].
+    pub id: String,
+    /// The tool name.
+    pub name: String,
+    /// The tool arguments as JSON.
+    pub arguments: serde_json::Value,
+}
+
+impl ToolCall {
+    /// Create a tool call.
+    pub fn new(id: impl Into<String>, name: impl Into<String>, arguments: serde_json::Value) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            arguments,
+        }
+    }
+}
+
+/// The outcome of a tool invocation, sent back to the model.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolResult {
+    /// The [`ToolCall::id`] this result answers.
+    pub call_id: String,
+    /// Whether the tool failed; the message text then describes the failure.
+    #[serde(default)]
+    pub is_error: bool,
+}
+
+/// A single chat message.
+///
+/// # Example
+///
+/// ```rust
+/// use llm_workflow::chat::{Message, Role};
+///
+/// let msg = Message::user("What is 2 + 2?").with_name("alice");
+/// assert_eq!(msg.role, Role::User);
+/// assert_eq!(msg.text(), "What is 2 + 2?");
+///
+/// let json = serde_json::to_value(&msg).unwrap();
+/// assert_eq!(json["role"], "user");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Message {
+    /// The author of the message.
+    pub role: Role,
+    /// The message content, in order.
+    #[serde(default)]
+    pub content: Vec<ContentPart>,
+    /// Tool invocations requested by an assistant message.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    /// For [`Role::Tool`] messages, the call this message answers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_result: Option<ToolResult>,
+    /// An optional participant name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Application-defined metadata; not sent to providers.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, serde_json::Value>,
+}
+
+impl Message {
+    /// A message with the given role and a single text part.
+    pub fn new(role: Role, text: impl Into<String>) -> Self {
+        Self {
+            role,
+            content: vec![ContentPart::text(text)],
+            tool_calls: Vec::new(),
+            tool_result: None,
+            name: None,
+            metadata: BTreeMap::new(),
+        }
+    }
+
+    /// A system message.
+    pub fn system(text: impl Into<String>) -> Self {
+        Self::new(Role::System, text)
+    }
+
+    /// A user message.
+    pub fn user(text: impl Into<String>) -> Self {
+        Self::new(Role::User, text)
+    }
+
+    /// An assistant message.
+    pub fn assistant(text: impl Into<String>) -> Self {
+        Self::new(Role::Assistant, text)
+    }
+
+    /// An assistant message requesting tool calls, with no text.
+    pub fn tool_calls(calls: Vec<ToolCall>) -> Self {
+        Self {
+            content: Vec::new(),
+            tool_calls: calls,
+            ..Self::new(Role::Assistant, "")
+        }
+    }
+
+    /// A tool message answering the call with id `call_id`.
+    pub fn tool_result(call_id: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            tool_result: Some(ToolResult {
+                call_id: call_id.into(),
+                is_error: false,
+            }),
+            ..Self::new(Role::Tool, content)
+        }
+    }
+
+    /// A tool message reporting that the call with id `call_id` failed.
+    pub fn tool_error(call_id: impl Into<String>, error: impl Into<String>) -> Self {
+        Self {
+            tool_result: Some(ToolResult {
+                call_id: call_id.into(),
+                is_error: true,
+            }),
+            ..Self::new(Role::Tool, error)
+        }
+    }
+
+    /// Set the participant name.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Append a content part.
+    pub fn with_part(mut self, part: ContentPart) -> Self {
+        self.content.push(part);
+        self
+    }
+
+    /// Append a tool call.
+    pub fn with_tool_call(mut self, call: ToolCall) -> Self {
+        self.tool_calls.push(call);
+        self
+    }
+
+    /// Attach a metadata entry.
+    pub fn with_metadata(mut self, key: impl Into<String>, value: serde_json::Value) -> Self {
+        self.metadata.insert(key.into(), value);
+        self
+    }
+
+    /// The concatenated text parts of the message.
+    pub fn text(&self) -> String {
+        self.content
+            .iter()
+            .filter_map(|part| match part {
+                ContentPart::Text { text } => Some(text.as_str()),
+                ContentPart::Image { .. } => None,
+            })
+            .collect()
+    }
+}
+
+/// A step that takes a conversation and returns the model's reply.
+///
+/// Blanket-implemented for every [`Step`] with `Input = Vec<Message>` and
+/// `Output = Message`, so provider clients only need to implement [`Step`].
+pub trait ChatModel: Step<Input = Vec<Message>, Output = Message> {}
+
+impl<S> ChatModel for S where S: Step<Input = Vec<Message>, Output = Message> {}
+
+/// Counts the role, text content, name and tool call names and arguments.
+/// Image parts are not counted.
+impl CountableMessage for Message {
+    fn role(&self) -> &str {
+        self.role.as_str()
+    }
+
+    fn segments(&self) -> Vec<Cow<'_, str>> {
+        let mut segments = vec![Cow::Owned(self.text())];
+        segments.extend(self.name.as_deref().map(Cow::Borrowed));
+        for call in &self.tool_calls {
+            segments.push(Cow::Borrowed(call.name.as_str()));
+            segments.push(Cow::Owned(call.arguments.to_string()));
+        }
+        segments
+    }
+}
+
+impl MemoryMessage for Message {
+    fn system(text: String) -> Self {
+        Message::system(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_message_serde_round_trip() {
+        let msg = Message::assistant("Looking it up.")
+            .with_tool_call(ToolCall::new("call_1", "search", json!({"q": "rust"})))
+            .with_metadata("latency_ms", json!(120));
+        let value = serde_json::to_value(&msg).unwrap();
+        assert_eq!(value["content"][0], json!({"type": "text", "text": "Looking it up."}));
+        assert_eq!(value["tool_calls"][0]["name"], "search");
+        assert!(value.get("tool_result").is_none());
+        assert_eq!(serde_json::from_value::<Message>(value).unwrap(), msg);
+    }
+
+    #[test]
+    fn test_text_skips_images() {
+        let msg = Message::user("Describe ")
+            .with_part(ContentPart::image("https://example.com/cat.png"))
+            .with_part(ContentPart::text("this."));
+        assert_eq!(msg.text(), "Describe this.");
+    }
+
+    #[test]
+    fn test_tool_error_message() {
+        let msg = Message::tool_error("call_9", "timeout");
+        assert_eq!(msg.role, Role::Tool);
+        assert!(msg.tool_result.as_ref().unwrap().is_error);
+        assert_eq!(msg.text(), "timeout");
+    }
+
+    #[test]
+    fn test_count_message_includes_name_and_tool_calls() {
+        use crate::tokenizer::{FnTokenizer, Tokenizer};
+
+        let tokenizer = FnTokenizer::new(|text: &str| text.len());
+        let msg = Message::tool_calls(vec![ToolCall::new("id", "add", json!({"a": 1}))]).with_name("bot");
+        assert_eq!(tokenizer.count_message(&msg), 3 + 9 + 3 + (3 + 7));
+    }
+}
@@ -0,0 +1,223 @@
+//! Conversions to and from the OpenAI Chat Completions message shape.
+//!
+//! Text-only content is written as a plain string; multi-part content uses
+//! the array form. Tool call arguments are carried as a JSON-encoded string,
+//! as the API requires. Arguments the model emitted as malformed JSON are kept
+//! as the raw string so tool validation can report the problem back to it.
+
+use serde_json::{json, Map, Value};
+
+use super::{ContentPart, Message, Role, ToolCall, ToolResult};
+use crate::{Error, Result};
+
+/// Convert a message to an OpenAI chat message object.
+pub fn to_value(message: &Message) -> Value {
+    let mut obj = Map::new();
+    obj.insert("role".to_string(), json!(message.role.as_str()));
+
+    let text_only = message
+        .content
+        .iter()
+        .all(|part| matches!(part, ContentPart::Text { .. }));
+    let content = if message.content.is_empty() && !message.tool_calls.is_empty() {
+        Value::Null
+    } else if text_only {
+        json!(message.text())
+    } else {
+        Value::Array(message.content.iter().map(part_to_value).collect())
+    };
+    obj.insert("content".to_string(), content);
+
+    if let Some(name) = &message.name {
+        obj.insert("name".to_string(), json!(name));
+    }
+    if !message.tool_calls.is_empty() {
+        let calls = message
+            .tool_calls
+            .iter()
+            .map(|call| {
+                json!({
+                    "id": call.id,
+                    "type": "function",
+                    "function": {"name": call.name, "arguments": encode_arguments(&call.arguments)},
+                })
+            })
+            .collect();
+        obj.insert("tool_calls".to_string(), Value::Array(calls));
+    }
+    if let Some(result) = &message.tool_result {
+        obj.insert("tool_call_id".to_string(), json!(result.call_id));
+    }
+    Value::Object(obj)
+}
+
+/// Convert a conversation to an OpenAI `messages` array.
+pub fn to_messages(messages: &[Message]) -> Value {
+    Value::Array(messages.iter().map(to_value).collect())
+}
+
+fn part_to_value(part: &ContentPart) -> Value {
+    match part {
+        ContentPart::Text { text } => json!({"type": "text", "text": text}),
+        ContentPart::Image { url } => json!({"type": "image_url", "image_url": {"url": url}}),
+    }
+}
+
+fn invalid(msg: impl Into<String>) -> Error {
+    Error::Validation(format!("invalid OpenAI message: {}", msg.into()))
+}
+
+/// Parse an OpenAI chat message object, e.g. `choices[0].message` of a response.
+pub fn from_value(value: &Value) -> Result<Message> {
+    let role = match value["role"].as_str() {
+        Some("system") | Some("developer") => Role::System,
+        Some("user") => Role::User,
+        Some("assistant") => Role::Assistant,
+        Some("tool") => Role::Tool,
+        other => return Err(invalid(format!("unknown role {other:?}"))),
+    };
+
+    let content = match &value["content"] {
+        Value::Null => Vec::new(),
+        Value::String(text) => vec![ContentPart::text(text.clone())],
+        Value::Array(parts) => parts.iter().map(part_from_value).collect::<Result<_>>()?,
+        other => return Err(invalid(format!("unexpected content {other}"))),
+    };
+
+    let tool_calls = match value["tool_calls"].as_array() {
+        Some(calls) => calls.iter().map(tool_call_from_value).collect::<Result<_>>()?,
+        None => Vec::new(),
+    };
+
+    let tool_result = match role {
+        Role::Tool => Some(ToolResult {
+            call_id: value["tool_call_id"]
+                .as_str()
+                .ok_or_else(|| invalid("tool message without tool_call_id"))?
+                .to_string(),
+            is_error: false,
+        }),
+        _ => None,
+    };
+
+    Ok(Message {
+        role,
+        content,
+        tool_calls,
+        tool_result,
+        name: value["name"].as_str().map(str::to_string),
+        metadata: Default::default(),
+    })
+}
+
+fn part_from_value(value: &Value) -> Result<ContentPart> {
+    match value["type"].as_str() {
+        Some("text") => Ok(ContentPart::text(
+            value["text"].as_str().ok_or_else(|| invalid("text part without text"))?,
+        )),
+        Some("image_url") => Ok(ContentPart::image(
+            value["image_url"]["url"]
+                .as_str()
+                .ok_or_else(|| invalid("image part without url"))?,
+        )),
+        other => Err(invalid(format!("unsupported content part {other:?}"))),
+    }
+}
+
+fn encode_arguments(arguments: &Value) -> String {
+    match arguments {
+        Value::String(raw) => raw.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn tool_call_from_value(value: &Value) -> Result<ToolCall> {
+    let function = &value["function"];
+    let arguments = match &function["arguments"] {
+        Value::String(raw) if raw.is_empty() => Value::Object(Map::new()),
+        Value::String(raw) => serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.clone())),
+        other => other.clone(),
+    };
+    Ok(ToolCall {
+        id: value["id"].as_str().ok_or_else(|| invalid("tool call without id"))?.to_string(),
+        name: function["name"]
+            .as_str()
+            .ok_or_else(|| invalid("tool call without name"))?
+            .to_string(),
+        arguments,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_message_uses_string_content() {
+        let value = to_value(&Message::user("hi").with_name("bob"));
+        assert_eq!(value, json!({"role": "user", "content": "hi", "name": "bob"}));
+    }
+
+    #[test]
+    fn test_multipart_content() {
+        let msg = Message::user("What is this?").with_part(ContentPart::image("https://x/y.png"));
+        let value = to_value(&msg);
+        assert_eq!(
+            value["content"],
+            json!([
+                {"type": "text", "text": "What is this?"},
+                {"type": "image_url", "image_url": {"url": "https://x/y.png"}},
+            ])
+        );
+        assert_eq!(from_value(&value).unwrap(), msg);
+    }
+
+    #[test]
+    fn test_tool_call_round_trip() {
+        let msg = Message::tool_calls(vec![ToolCall::new("call_1", "add", json!({"a": 1, "b": 2}))]);
+        let value = to_value(&msg);
+        assert_eq!(value["content"], Value::Null);
+        assert_eq!(value["tool_calls"][0]["function"]["arguments"], r#"{"a":1,"b":2}"#);
+        assert_eq!(from_value(&value).unwrap(), msg);
+    }
+
+    #[test]
+    fn test_tool_result_round_trip() {
+        let msg = Message::tool_result("call_1", "3");
+        let value = to_value(&msg);
+        assert_eq!(value, json!({"role": "tool", "content": "3", "tool_call_id": "call_1"}));
+        assert_eq!(from_value(&value).unwrap(), msg);
+    }
+
+    #[test]
+    fn test_parse_response_message() {
+        let response = json!({
+            "role": "assistant",
+            "content": null,
+            "tool_calls": [{
+                "id": "call_abc",
+                "type": "function",
+                "function": {"name": "get_weather", "arguments": "{\"city\":\"Paris\"}"}
+            }]
+        });
+        let msg = from_value(&response).unwrap();
+        assert_eq!(msg.tool_calls[0].arguments, json!({"city": "Paris"}));
+        assert!(from_value(&json!({"role": "robot"})).is_err());
+    }
+
+    #[test]
+    fn test_malformed_arguments_are_kept_raw() {
+        let response = json!({
+            "role": "assistant",
+            "tool_calls": [{
+                "id": "call_bad",
+                "type": "function",
+                "function": {"name": "get_weather", "arguments": "{\"city\": \"Par"}
+            }]
+        });
+        let msg = from_value(&response).unwrap();
+        assert_eq!(msg.tool_calls[0].arguments, json!("{\"city\": \"Par"));
+        // The raw text is sent back unchanged alongside the validation error.
+        assert_eq!(to_value(&msg)["tool_calls"][0]["function"]["arguments"], "{\"city\": \"Par");
+    }
+}
@@ -3,10 +3,23 @@
 //! Checkpoints emit a [`Error::Checkpoint`](crate::Error::Checkpoint) error,
 //! which callers can catch to pause execution, review the current state,
 //! and decide whether to continue or abort.
+//!
+//! For actions that need sign-off each time they happen (e.g. a tool call that
+//! sends an email), record the reviewer's [`Decision`] in an [`ApprovalStore`]
+//! keyed by the checkpointed item; on resume the gated step consults the store
+//! and proceeds or skips accordingly. Tool-call decisions are bound to the
+//! exact call reviewed and consumed when it runs (see
+//! [`ToolExecutorStep::require_approval`](crate::ToolExecutorStep::require_approval)).
 
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
 
 use crate::{Error, ExecutionContext, Result, step::Step};
+use crate::chat::ToolCall;
+use crate::step::predicate::{AsyncPredicate, Predicate, StepPredicate};
 
 /// A step that always pauses execution by emitting a checkpoint error.
 ///
@@ -71,6 +84,12 @@ where
 /// When the predicate returns `true`, a checkpoint error is emitted.
 /// When it returns `false`, the input passes through unchanged.
 ///
+/// The predicate can be any [`Predicate`]; besides the synchronous
+/// [`new`](ConditionalCheckpointStep::new) constructor, see
+/// [`with_predicate`](ConditionalCheckpointStep::with_predicate),
+/// [`from_step`](ConditionalCheckpointStep::from_step) and
+/// [`from_async`](ConditionalCheckpointStep::from_async).
+///
 /// # Example
 ///
 /// ```rust
@@ -109,17 +128,76 @@ where
     }
 }
 
+impl<I, P> ConditionalCheckpointStep<I, P>
+where
+    P: Predicate<I>,
+{
+    /// Create a conditional checkpoint step from any [`Predicate`].
+    pub fn with_predicate(name: impl Into<String>, predicate: P) -> Self {
+        Self {
+            step_name: name.into(),
+            predicate,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<I, S> ConditionalCheckpointStep<I, StepPredicate<S>>
+where
+    S: Step<Input = I, Output = bool>,
+    I: Clone + Send + 'static,
+{
+    /// Create a conditional checkpoint whose predicate is a step run over a clone of the input.
+    pub fn from_step(name: impl Into<String>, predicate: S) -> Self {
+        Self::with_predicate(name, StepPredicate::new(predicate))
+    }
+}
+
+impl<I, F> ConditionalCheckpointStep<I, AsyncPredicate<I, F>> {
+    /// Create a conditional checkpoint whose predicate is an async closure over
+    /// a clone of the execution context and input.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use llm_workflow::{ConditionalCheckpointStep, Step, ExecutionContext, Error};
+    ///
+    /// # tokio_test::block_on(async {
+    /// let cp = ConditionalCheckpointStep::from_async("flagged", |_ctx: ExecutionContext, text: String| async move {
+    ///     // e.g. consult a moderation API
+    ///     Ok::<bool, Error>(text.contains("refund"))
+    /// });
+    ///
+    /// let ctx = ExecutionContext::new();
+    /// assert!(cp.run(&ctx, "hello".to_string()).await.is_ok());
+    /// assert!(matches!(
+    ///     cp.run(&ctx, "issue a refund".to_string()).await,
+    ///     Err(Error::Checkpoint { .. })
+    /// ));
+    /// # });
+    /// ```
+    pub fn from_async<Fut>(name: impl Into<String>, predicate: F) -> Self
+    where
+        F: Fn(ExecutionContext, I) -> Fut + Send + Sync,
+        Fut: Future<Output = Result<bool>> + Send + 'static,
+        I: Clone + Send,
+    {
+        Self::with_predicate(name, AsyncPredicate::new(predicate))
+    }
+}
+
 #[async_trait]
 impl<I, F> Step for ConditionalCheckpointStep<I, F>
 where
     I: Send + serde::Serialize + 'static,
-    F: Fn(&I) -> bool + Send + Sync + 'static,
+    F: Predicate<I> + 'static,
 {
     type Input = I;
     type Output = I;
 
-    async fn run(&self, _ctx: &ExecutionContext, input: I) -> Result<I> {
-        if (self.predicate)(&input) {
+    async fn run(&self, ctx: &ExecutionContext, input: I) -> Result<I> {
+        let decision = self.predicate.evaluate(ctx, &input);
+        if decision.await? {
             let data = serde_json::to_value(&input)
                 .unwrap_or_else(|_| serde_json::json!("<serialization_error>"));
             Err(Error::Checkpoint {
@@ -135,3 +213,141 @@ where
         &self.step_name
     }
 }
+
+/// A reviewer's decision on a checkpointed action.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Decision {
+    /// Carry out the action.
+    Approve,
+    /// Skip the action, optionally explaining why.
+    Reject {
+        /// The reviewer's reason, reported back in place of the action's result.
+        reason: Option<String>,
+    },
+}
+
+/// A decision as recorded, with the tool call it was made for, if any.
+#[derive(Debug, Clone)]
+struct Recorded {
+    decision: Decision,
+    call: Option<ToolCall>,
+}
+
+/// A shared store of reviewer decisions, keyed by the checkpointed item.
+///
+/// Cheap to clone; clones share the same decisions. Gated steps look up a
+/// decision when they run: with none recorded they raise
+/// [`Error::Checkpoint`], otherwise they act on it.
+///
+/// Decisions on tool calls are recorded with [`ApprovalStore::decide_call`],
+/// which binds them to the call's id, tool name and arguments: a call that
+/// reuses the id with different arguments finds no decision and pauses again.
+///
+/// # Example
+///
+/// ```rust
+/// use serde_json::json;
+/// use llm_workflow::chat::ToolCall;
+/// use llm_workflow::checkpoint::{ApprovalStore, Decision};
+///
+/// let approvals = ApprovalStore::new();
+/// approvals.approve("call_1");
+/// approvals.reject("call_2", "amount too large");
+/// assert_eq!(approvals.decision("call_1"), Some(Decision::Approve));
+/// assert_eq!(approvals.decision("call_3"), None);
+///
+/// let refund = ToolCall::new("call_4", "refund", json!({"amount": 10}));
+/// approvals.approve_call(&refund);
+/// let altered = ToolCall::new("call_4", "refund", json!({"amount": 10_000}));
+/// assert_eq!(approvals.call_decision(&altered), None);
+/// assert_eq!(approvals.take_call(&refund), Some(Decision::Approve));
+/// assert_eq!(approvals.call_decision(&refund), None);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ApprovalStore {
+    decisions: Arc<Mutex<HashMap<String, Recorded>>>,
+}
+
+impl ApprovalStore {
+    /// Create an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a decision for `key`, replacing any earlier one.
+    pub fn decide(&self, key: impl Into<String>, decision: Decision) {
+        let recorded = Recorded { decision, call: None };
+        self.decisions.lock().unwrap().insert(key.into(), recorded);
+    }
+
+    /// Approve the action identified by `key`.
+    pub fn approve(&self, key: impl Into<String>) {
+        self.decide(key, Decision::Approve);
+    }
+
+    /// Reject the action identified by `key`.
+    pub fn reject(&self, key: impl Into<String>, reason: impl Into<String>) {
+        self.decide(
+            key,
+            Decision::Reject {
+                reason: Some(reason.into()),
+            },
+        );
+    }
+
+    /// The decision recorded for `key`, if any.
+    pub fn decision(&self, key: &str) -> Option<Decision> {
+        self.decisions.lock().unwrap().get(key).map(|recorded| recorded.decision.clone())
+    }
+
+    /// Remove and return the decision recorded for `key`.
+    pub fn take(&self, key: &str) -> Option<Decision> {
+        self.decisions.lock().unwrap().remove(key).map(|recorded| recorded.decision)
+    }
+
+    /// Record a decision for exactly `call`, keyed by its id.
+    pub fn decide_call(&self, call: &ToolCall, decision: Decision) {
+        let recorded = Recorded {
+            decision,
+            call: Some(call.clone()),
+        };
+        self.decisions.lock().unwrap().insert(call.id.clone(), recorded);
+    }
+
+    /// Approve exactly `call`.
+    pub fn approve_call(&self, call: &ToolCall) {
+        self.decide_call(call, Decision::Approve);
+    }
+
+    /// Reject exactly `call`.
+    pub fn reject_call(&self, call: &ToolCall, reason: impl Into<String>) {
+        self.decide_call(
+            call,
+            Decision::Reject {
+                reason: Some(reason.into()),
+            },
+        );
+    }
+
+    /// The decision recorded for `call`, if one was made for the same tool
+    /// and arguments.
+    pub fn call_decision(&self, call: &ToolCall) -> Option<Decision> {
+        let decisions = self.decisions.lock().unwrap();
+        decisions
+            .get(&call.id)
+            .filter(|recorded| recorded.call.as_ref() == Some(call))
+            .map(|recorded| recorded.decision.clone())
+    }
+
+    /// Remove and return the decision recorded for `call`, if one was made for
+    /// the same tool and arguments.
+    pub fn take_call(&self, call: &ToolCall) -> Option<Decision> {
+        let mut decisions = self.decisions.lock().unwrap();
+        match decisions.get(&call.id) {
+            Some(recorded) if recorded.call.as_ref() == Some(call) => {
+                decisions.remove(&call.id).map(|recorded| recorded.decision)
+            }
+            _ => None,
+        }
+    }
+}
@@ -5,6 +5,7 @@
 
 use std::sync::{Arc, Mutex};
 
+use crate::cache::CachePolicy;
 use crate::metrics::WorkflowMetrics;
 use crate::events::{TraceEntry, WorkflowEvent};
 
@@ -41,6 +42,11 @@ pub struct ExecutionContext {
     pub metrics: Arc<Mutex<WorkflowMetrics>>,
     /// Shared trace log for structured workflow events.
     pub traces: Arc<Mutex<Vec<TraceEntry>>>,
+    /// Cache settings for this run, honoured by [`CachedStep`](crate::cache::CachedStep).
+    pub cache_policy: CachePolicy,
+    /// The agent on whose behalf work under this context runs; recorded on
+    /// every emitted [`TraceEntry`].
+    pub agent: Option<String>,
 }
 
 impl Default for ExecutionContext {
@@ -56,6 +62,55 @@ impl ExecutionContext {
         Self {
             metrics: Arc::new(Mutex::new(WorkflowMetrics::default())),
             traces: Arc::new(Mutex::new(Vec::new())),
+            cache_policy: CachePolicy::default(),
+            agent: None,
+        }
+    }
+
+    /// Set the cache policy for runs using this context.
+    #[must_use]
+    pub fn with_cache_policy(mut self, policy: CachePolicy) -> Self {
+        self.cache_policy = policy;
+        self
+    }
+
+    /// Attribute events emitted under this context to the named agent.
+    ///
+    /// The returned context shares this context's metrics and trace log.
+    #[must_use]
+    pub fn with_agent(&self, agent: impl Into<String>) -> Self {
+        Self {
+            agent: Some(agent.into()),
+            ..self.clone()
+        }
+    }
+
+    /// Create a child context with its own empty metrics and trace log.
+    ///
+    /// Work run under the child is isolated from this context until it is
+    /// folded back in, e.g. with [`merge`](Self::merge). Run settings such as
+    /// the cache policy and agent attribution are inherited.
+    #[must_use]
+    pub fn child(&self) -> Self {
+        Self {
+            cache_policy: self.cache_policy,
+            agent: self.agent.clone(),
+            ..Self::new()
+        }
+    }
+
+    /// Fold a child context's metrics and trace entries into this context.
+    ///
+    /// Trace entries are appended in their original order. Merging a context
+    /// into itself (or into a clone sharing the same state) is a no-op.
+    pub fn merge(&self, child: &ExecutionContext) {
+        if !Arc::ptr_eq(&self.metrics, &child.metrics) {
+            let child_metrics = child.snapshot();
+            self.metrics.lock().unwrap().merge(&child_metrics);
+        }
+        if !Arc::ptr_eq(&self.traces, &child.traces) {
+            let entries = child.trace_snapshot();
+            self.traces.lock().unwrap().extend(entries);
         }
     }
 
@@ -79,6 +134,18 @@ impl ExecutionContext {
         m.total_token_count += prompt + completion;
     }
 
+    /// Record a cache hit that avoided spending `tokens_saved` tokens.
+    pub fn record_cache_hit(&self, tokens_saved: usize) {
+        let mut m = self.metrics.lock().unwrap();
+        m.record_cache_hit(tokens_saved);
+    }
+
+    /// Record a cache miss.
+    pub fn record_cache_miss(&self) {
+        let mut m = self.metrics.lock().unwrap();
+        m.record_cache_miss();
+    }
+
     /// Increment the steps completed counter.
     pub fn record_step(&self) {
         let mut m = self.metrics.lock().unwrap();
@@ -114,7 +181,8 @@ impl ExecutionContext {
     /// });
     /// ```
     pub fn emit(&self, event: WorkflowEvent) {
-        let entry = TraceEntry::new(event);
+        let mut entry = TraceEntry::new(event);
+        entry.agent = self.agent.clone();
         self.traces.lock().unwrap().push(entry);
     }
 
@@ -146,6 +214,15 @@ impl ExecutionContext {
         });
     }
 
+    /// Record a conversation as an [`WorkflowEvent::Artifact`] in the canonical
+    /// [`Message`](crate::chat::Message) JSON shape.
+    ///
+    /// Use this for prompts and replies so traces from different model steps
+    /// can be inspected and replayed uniformly.
+    pub fn emit_messages(&self, step_name: &str, key: &str, messages: &[crate::chat::Message]) {
+        self.emit_artifact(step_name, key, &messages);
+    }
+
     /// Get a snapshot of the current trace log.
     ///
     /// Returns all trace entries recorded so far. Useful for debugging
@@ -249,6 +326,23 @@ mod tests {
         assert_eq!(traces.len(), 1);
     }
 
+    #[test]
+    fn test_emit_messages_uses_canonical_shape() {
+        use crate::chat::Message;
+
+        let ctx = ExecutionContext::new();
+        let prompt = vec![Message::system("Be brief."), Message::user("Hi")];
+        ctx.emit_messages("Chat", "prompt", &prompt);
+        match &ctx.trace_snapshot()[0].event {
+            WorkflowEvent::Artifact { key, data, .. } => {
+                assert_eq!(key, "prompt");
+                let parsed: Vec<Message> = serde_json::from_value(data.clone()).unwrap();
+                assert_eq!(parsed, prompt);
+            }
+            other => panic!("unexpected event {other:?}"),
+        }
+    }
+
     #[test]
     fn test_clear_traces() {
         let ctx = ExecutionContext::new();
@@ -273,6 +367,58 @@ mod tests {
         assert_eq!(snap.prompt_token_count, 50);
     }
 
+    #[test]
+    fn test_child_is_isolated_until_merged() {
+        let parent = ExecutionContext::new();
+        let child = parent.child();
+
+        child.record_tokens(10, 20);
+        child.record_step();
+        child.emit(WorkflowEvent::StepStart {
+            step_name: "Inner".to_string(),
+            input_type: "i32".to_string(),
+        });
+        assert_eq!(parent.snapshot().total_token_count, 0);
+        assert!(parent.trace_snapshot().is_empty());
+
+        parent.merge(&child);
+        let snap = parent.snapshot();
+        assert_eq!(snap.total_token_count, 30);
+        assert_eq!(snap.steps_completed, 1);
+        assert_eq!(parent.trace_snapshot().len(), 1);
+    }
+
+    #[test]
+    fn test_agent_attribution_is_inherited() {
+        let ctx = ExecutionContext::new();
+        let researcher = ctx.with_agent("researcher");
+        let child = researcher.child();
+        child.emit(WorkflowEvent::StepStart {
+            step_name: "Model".to_string(),
+            input_type: "Vec<Message>".to_string(),
+        });
+        researcher.merge(&child);
+        ctx.emit(WorkflowEvent::StepStart {
+            step_name: "Supervisor".to_string(),
+            input_type: "Vec<Message>".to_string(),
+        });
+
+        let traces = ctx.trace_snapshot();
+        assert_eq!(traces[0].agent.as_deref(), Some("researcher"));
+        assert_eq!(traces[1].agent, None);
+        let json = serde_json::to_value(&traces[0]).unwrap();
+        assert_eq!(json["agent"], "researcher");
+        assert!(serde_json::to_value(&traces[1]).unwrap().get("agent").is_none());
+    }
+
+    #[test]
+    fn test_merge_with_clone_is_noop() {
+        let ctx = ExecutionContext::new();
+        ctx.record_step();
+        ctx.merge(&ctx.clone());
+        assert_eq!(ctx.snapshot().steps_completed, 1);
+    }
+
     #[test]
     fn test_default_is_same_as_new() {
         let ctx = ExecutionContext::default();
@@ -22,6 +22,15 @@ pub enum Error {
     #[error("Execution error: {0}")]
     Execution(String),
 
+    /// A circuit breaker is open and the call was rejected without running.
+    #[error("Circuit open for step '{step_name}' (retry after {retry_after_ms}ms)")]
+    CircuitOpen {
+        /// The name of the circuit breaker step.
+        step_name: String,
+        /// Milliseconds until the circuit will admit a trial call.
+        retry_after_ms: u128,
+    },
+
     /// A JSON serialization/deserialization error.
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
@@ -31,6 +40,32 @@ pub enum Error {
     Message(String),
 }
 
+impl Error {
+    /// Produce an equivalent error for delivery to an additional caller.
+    ///
+    /// `Error` is not `Clone` because [`serde_json::Error`] is not; JSON errors
+    /// are replicated as [`Error::Message`] with the same text.
+    pub(crate) fn replicate(&self) -> Error {
+        match self {
+            Error::Checkpoint { step_name, data } => Error::Checkpoint {
+                step_name: step_name.clone(),
+                data: data.clone(),
+            },
+            Error::Validation(msg) => Error::Validation(msg.clone()),
+            Error::Execution(msg) => Error::Execution(msg.clone()),
+            Error::CircuitOpen {
+                step_name,
+                retry_after_ms,
+            } => Error::CircuitOpen {
+                step_name: step_name.clone(),
+                retry_after_ms: *retry_after_ms,
+            },
+            Error::Json(e) => Error::Message(e.to_string()),
+            Error::Message(msg) => Error::Message(msg.clone()),
+        }
+    }
+}
+
 impl From<String> for Error {
     fn from(msg: String) -> Self {
         Error::Message(msg)
@@ -77,6 +112,18 @@ mod tests {
         assert_eq!(err.to_string(), "Checkpoint reached at step 'review'");
     }
 
+    #[test]
+    fn test_error_display_circuit_open() {
+        let err = Error::CircuitOpen {
+            step_name: "provider".to_string(),
+            retry_after_ms: 1500,
+        };
+        assert_eq!(
+            err.to_string(),
+            "Circuit open for step 'provider' (retry after 1500ms)"
+        );
+    }
+
     #[test]
     fn test_from_string() {
         let err: Error = "from string".to_string().into();
@@ -98,6 +145,20 @@ mod tests {
         assert!(matches!(err, Error::Json(_)));
     }
 
+    #[test]
+    fn test_replicate_preserves_variant() {
+        let err = Error::Checkpoint {
+            step_name: "review".to_string(),
+            data: serde_json::json!([1, 2]),
+        };
+        assert!(matches!(err.replicate(), Error::Checkpoint { step_name, .. } if step_name == "review"));
+
+        let json_err: Error = serde_json::from_str::<serde_json::Value>("{").unwrap_err().into();
+        let copy = json_err.replicate();
+        assert!(matches!(copy, Error::Message(_)));
+        assert_eq!(copy.to_string(), json_err.to_string().trim_start_matches("JSON error: "));
+    }
+
     #[test]
     fn test_error_debug() {
         let err = Error::Validation("test".to_string());
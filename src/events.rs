@@ -6,6 +6,8 @@
 use serde::{Serialize, Deserialize};
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use crate::step::circuit_breaker::CircuitState;
+
 /// Events that can be emitted during workflow execution.
 ///
 /// These events provide structured observability into workflow behavior,
@@ -46,6 +48,179 @@ pub enum WorkflowEvent {
         /// Error message describing what went wrong.
         message: String,
     },
+    /// A nested workflow finished, successfully or not.
+    ///
+    /// The child's own trace entries are carried inside this event so they
+    /// remain attributed to the sub-workflow that produced them.
+    SubWorkflow {
+        /// Name of the nested workflow.
+        workflow_name: String,
+        /// Duration of the nested run in milliseconds.
+        duration_ms: u128,
+        /// Whether the nested run completed successfully.
+        success: bool,
+        /// Trace entries recorded by the nested run.
+        entries: Vec<TraceEntry>,
+    },
+    /// A router chose the route an input will take.
+    RouteSelected {
+        /// Name of the router step.
+        step_name: String,
+        /// The route key returned by the classifier, as JSON.
+        route: serde_json::Value,
+        /// Whether no route matched and the default route was taken.
+        is_default: bool,
+    },
+    /// A race or hedged request settled.
+    RaceFinished {
+        /// Name of the race step.
+        step_name: String,
+        /// Index of the winning candidate, or `None` if every candidate failed.
+        winner: Option<usize>,
+        /// Number of candidates that were actually started.
+        launched: usize,
+        /// Prompt tokens recorded by the losing candidates.
+        wasted_prompt_tokens: usize,
+        /// Completion tokens recorded by the losing candidates.
+        wasted_completion_tokens: usize,
+    },
+    /// A step in a fallback chain failed and the next step is being tried.
+    Fallback {
+        /// Name of the fallback step.
+        step_name: String,
+        /// Index of the step that failed.
+        failed_index: usize,
+        /// Index of the step about to be tried.
+        next_index: usize,
+        /// Error message from the failed step.
+        error: String,
+    },
+    /// A circuit breaker changed state.
+    CircuitStateChanged {
+        /// Name of the circuit breaker step.
+        step_name: String,
+        /// The state before the transition.
+        from: CircuitState,
+        /// The state after the transition.
+        to: CircuitState,
+    },
+    /// A step waited for rate limit capacity before running.
+    RateLimited {
+        /// Name of the rate-limited step.
+        step_name: String,
+        /// How long the step waited, in milliseconds.
+        waited_ms: u128,
+    },
+    /// A cached step looked up its input in the cache.
+    CacheLookup {
+        /// Name of the cached step.
+        step_name: String,
+        /// The cache key derived from the input.
+        key: String,
+        /// Whether the output was served from the cache.
+        hit: bool,
+    },
+    /// A loop step completed one iteration of its body.
+    LoopIteration {
+        /// Name of the loop step.
+        step_name: String,
+        /// The 1-based iteration number.
+        iteration: usize,
+        /// Whether the stop predicate held after this iteration.
+        converged: bool,
+    },
+    /// A batch step divided its input into batches.
+    BatchPlanned {
+        /// Name of the batch step.
+        step_name: String,
+        /// Number of items in each batch, in order.
+        batch_sizes: Vec<usize>,
+        /// Summed item size of each batch, when the batch policy measures items.
+        batch_tokens: Option<Vec<usize>>,
+    },
+    /// A conversation memory was trimmed to fit the context window.
+    MemoryTrimmed {
+        /// Name of the memory-managing step.
+        step_name: String,
+        /// The trim strategy that was applied.
+        strategy: String,
+        /// Number of turns removed.
+        removed_turns: usize,
+        /// Token count before trimming, if measured.
+        tokens_before: Option<usize>,
+        /// Token count after trimming, if measured.
+        tokens_after: Option<usize>,
+        /// Whether the removed turns were folded into a summary.
+        summarized: bool,
+    },
+    /// A tool was invoked on behalf of a model.
+    ToolInvocation {
+        /// Name of the executing step.
+        step_name: String,
+        /// Name of the tool that was called.
+        tool_name: String,
+        /// The model-assigned identifier of the call.
+        call_id: String,
+        /// The arguments supplied by the model.
+        arguments: serde_json::Value,
+        /// The tool's result, if it succeeded.
+        result: Option<serde_json::Value>,
+        /// The failure message, if it failed.
+        error: Option<String>,
+        /// Wall-clock duration of the call in milliseconds.
+        duration_ms: u128,
+    },
+    /// A model was called.
+    ModelCall {
+        /// Name of the calling step.
+        step_name: String,
+        /// Number of messages sent to the model.
+        messages: usize,
+        /// Prompt tokens reported by the call.
+        prompt_tokens: usize,
+        /// Completion tokens reported by the call.
+        completion_tokens: usize,
+        /// Wall-clock duration of the call in milliseconds.
+        duration_ms: u128,
+    },
+    /// An agent completed one model call.
+    AgentIteration {
+        /// Name of the agent step.
+        step_name: String,
+        /// The 1-based iteration number.
+        iteration: usize,
+        /// Names of the tools the model requested, if any.
+        tool_calls: Vec<String>,
+        /// Tokens used by the agent so far.
+        tokens_used: usize,
+    },
+    /// Control of a multi-agent conversation passed to an agent.
+    AgentHandoff {
+        /// Name of the supervisor step.
+        step_name: String,
+        /// The agent that handed off, or `None` when the supervisor chose.
+        from: Option<String>,
+        /// The agent taking over.
+        to: String,
+    },
+    /// A retrieval step returned documents for a query.
+    DocumentsRetrieved {
+        /// Name of the retrieval step.
+        step_name: String,
+        /// Ids of the returned documents, best first.
+        document_ids: Vec<String>,
+        /// Similarity scores, aligned with `document_ids`.
+        scores: Vec<f32>,
+    },
+    /// A streaming step yielded a chunk.
+    StreamChunk {
+        /// Name of the streaming step.
+        step_name: String,
+        /// The 0-based index of the chunk within the stream.
+        index: usize,
+        /// The chunk as a JSON value.
+        data: serde_json::Value,
+    },
 }
 
 /// A timestamped trace entry containing a workflow event.
@@ -56,6 +231,10 @@ pub enum WorkflowEvent {
 pub struct TraceEntry {
     /// Unix epoch timestamp in milliseconds when this event occurred.
     pub timestamp: u128,
+    /// The agent the event is attributed to, if it was emitted on behalf of one
+    /// (see [`ExecutionContext::with_agent`](crate::ExecutionContext::with_agent)).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
     /// The workflow event that was recorded.
     #[serde(flatten)]
     pub event: WorkflowEvent,
@@ -70,7 +249,11 @@ impl TraceEntry {
             .duration_since(UNIX_EPOCH)
             .expect("Time went backwards")
             .as_millis();
-        Self { timestamp, event }
+        Self {
+            timestamp,
+            agent: None,
+            event,
+        }
     }
 }
 
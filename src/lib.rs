@@ -14,9 +14,30 @@
 //! - **ChainStep**: Sequential composition of steps
 //! - **MapStep**: Inline transformations between steps
 //! - **ParallelMapStep**: Apply a step to multiple inputs concurrently
-//! - **BranchStep**: Conditional routing based on predicates
+//! - **StreamParallelMapStep / StreamBatchStep / StreamFoldStep**: Lazy pipelines over unbounded input streams
+//! - **ReduceStep / FoldStep / TreeReduceStep**: Aggregate results, including via model calls
+//! - **JoinStep**: Run heterogeneous steps concurrently, returning a tuple
+//! - **RaceStep / HedgeStep**: First-answer-wins over redundant providers
+//! - **FallbackStep**: Provider failover through an ordered chain of steps
+//! - **CircuitBreakerStep**: Fail fast while a step is unhealthy
+//! - **RateLimiter**: Shared request/token budgets enforced by `RateLimitedStep`
+//! - **CachedStep**: Memoize step outputs in a pluggable cache
+//! - **SingleFlightStep**: Coalesce concurrent runs with identical inputs
+//! - **BranchStep**: Conditional routing based on sync or async predicates
+//! - **RouterStep**: Multi-way routing keyed by a (sync or async) classifier
+//! - **LoopStep**: Iterate a step until a predicate holds or a round limit is hit
 //! - **TapStep**: Side-effect inspection without modifying output
+//! - **StreamingStep**: Token-by-token output with a final aggregated value
+//! - **Tokenizer**: Estimate token counts before a call is made
+//! - **Message**: Canonical chat messages with OpenAI and Anthropic conversions
+//! - **AgentStep**: ReAct-style model/tool loop with budgets and checkpoints
+//! - **SupervisorStep**: Named agents with handoffs under a supervisor loop
+//! - **EmbedStep / VectorIndex / RetrieveStep**: Batched embeddings and in-memory similarity search
+//! - **SplitStep**: Document chunking (fixed, recursive, sentence, token, markdown) with source offsets
+//! - **Tool / ToolExecutorStep**: Schema-validated tool calling with traced invocations
+//! - **ConversationMemory**: Conversation state kept within a context window
 //! - **CheckpointStep**: Human-in-the-loop pausing
+//! - **ApprovalStore**: Reviewer decisions gating individual tool calls
 //! - **Workflow**: High-level container with automatic metrics collection
 //!
 //! ## Example: Fluent Pipeline with Metrics
@@ -50,22 +71,52 @@ pub mod step;
 pub mod checkpoint;
 pub mod instrumented;
 pub mod state;
+pub mod agent;
+pub mod chat;
+pub mod memory;
+pub mod retrieval;
+pub mod splitter;
+pub mod streaming;
+pub mod tokenizer;
+pub mod tool;
+pub mod rate_limit;
+pub mod cache;
 
 pub use error::{Error, Result};
 pub use context::ExecutionContext;
 pub use metrics::WorkflowMetrics;
 pub use events::{TraceEntry, WorkflowEvent};
 pub use workflow::Workflow;
-pub use checkpoint::{CheckpointStep, ConditionalCheckpointStep};
+pub use checkpoint::{ApprovalStore, CheckpointStep, ConditionalCheckpointStep, Decision};
 pub use instrumented::InstrumentedStep;
 pub use state::{StateStep, StateWorkflow, LambdaStateStep, StepAdapter};
+pub use streaming::{StreamEvent, StreamingAdapter, StreamingStep, StreamingStepExt};
+pub use rate_limit::{RateLimitedStep, RateLimiter};
+pub use agent::team::{Next, SupervisorStep, TeamOutcome, TeamState, TeamStop};
+pub use agent::{AgentOutcome, AgentStep, AgentStop, Handoff, Scratchpad};
+pub use chat::{ChatModel, ContentPart, Message, Role, ToolCall, ToolResult};
+pub use memory::{ConversationMemory, ManagedMemoryStep, TrimStrategy};
+pub use retrieval::{Document, EmbedStep, EmbeddingModel, Filter, HashEmbedding, RetrieveStep, VectorIndex};
+pub use splitter::{Chunk, SplitStep, TextSplitter};
+pub use tokenizer::{HeuristicTokenizer, Tokenizer};
+pub use tool::{FnTool, Tool, ToolDefinition, ToolExecutorStep, ToolRegistry};
+pub use cache::{Cache, CachedStep, CachePolicy, InMemoryCache, JsonFileCache};
 
 // Re-export step types
 pub use step::{Step, LambdaStep, MapStep, BoxedStepExt};
 pub use step::chain::{ChainStep, ChainTupleStep};
+pub use step::circuit_breaker::{CircuitBreakerStep, CircuitState};
+pub use step::fallback::FallbackStep;
+pub use step::iterate::{CollectLoopStep, LoopStep};
+pub use step::join::{Join3Step, Join4Step, Join5Step, JoinMode, JoinStep};
 pub use step::map::MapStep as MapStepType;
 pub use step::tap::TapStep;
 pub use step::parallel::{ParallelMapStep, ParallelMapBuilder};
-pub use step::reduce::ReduceStep;
-pub use step::batch::{BatchStep, SingleItemAdapter};
+pub use step::pipeline::{ResultStream, StreamBatchStep, StreamFoldStep, StreamParallelMapStep};
+pub use step::reduce::{FoldStep, Reducer, ReduceStep, TreeReduceStep};
+pub use step::batch::{BatchPolicy, BatchStep, FixedSize, SingleItemAdapter, TokenBudget};
 pub use step::branch::BranchStep;
+pub use step::race::{HedgeStep, RaceStep};
+pub use step::predicate::{AsyncPredicate, Predicate, ProjectedStepPredicate, StepPredicate};
+pub use step::single_flight::SingleFlightStep;
+pub use step::router::{RouteClassifier, RouterStep, StepClassifier};
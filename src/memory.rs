@@ -0,0 +1,533 @@
+//! Conversation memory and context-window management.
+//!
+//! [`ConversationMemory`] is a serializable conversation history intended to
+//! be used as a [`StateStep::State`]. A [`TrimStrategy`] keeps it within a
+//! model's context window, and [`ManagedMemoryStep`] applies a strategy before
+//! every call to a conversational [`StateStep`], emitting a
+//! [`WorkflowEvent::MemoryTrimmed`] event whenever turns are dropped.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+
+use crate::tokenizer::{count_messages, CountableMessage, Tokenizer};
+use crate::{ExecutionContext, Result, StateStep, WorkflowEvent, step::Step};
+
+/// A message type a [`ConversationMemory`] can hold.
+///
+/// Implemented for [`Message`](crate::chat::Message) and for `(role, content)`
+/// string pairs.
+pub trait MemoryMessage: CountableMessage + Clone + Send + Sync + 'static {
+    /// A system message carrying `text`, used to render the system prompt and
+    /// the summary of trimmed turns.
+    fn system(text: String) -> Self;
+}
+
+impl MemoryMessage for (String, String) {
+    fn system(text: String) -> Self {
+        ("system".to_string(), text)
+    }
+}
+
+/// A conversation history with an optional system prompt and running summary.
+///
+/// The system prompt is never trimmed. When older turns are summarized, the
+/// summary is kept separately and rendered as a system message ahead of the
+/// remaining turns by [`ConversationMemory::messages`].
+///
+/// # Example
+///
+/// ```rust
+/// use llm_workflow::chat::{Message, Role};
+/// use llm_workflow::memory::ConversationMemory;
+///
+/// let mut memory = ConversationMemory::new().with_system("You are terse.");
+/// memory.push(Message::user("Hi"));
+/// memory.push(Message::assistant("Hello."));
+///
+/// let roles: Vec<Role> = memory.messages().iter().map(|m| m.role).collect();
+/// assert_eq!(roles, vec![Role::System, Role::User, Role::Assistant]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConversationMemory<M> {
+    system: Option<String>,
+    #[serde(default)]
+    summary: Option<String>,
+    #[serde(default)]
+    turns: Vec<M>,
+}
+
+impl<M> Default for ConversationMemory<M> {
+    fn default() -> Self {
+        Self {
+            system: None,
+            summary: None,
+            turns: Vec::new(),
+        }
+    }
+}
+
+impl<M: MemoryMessage> ConversationMemory<M> {
+    /// Create an empty memory.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the system prompt.
+    pub fn with_system(mut self, system: impl Into<String>) -> Self {
+        self.system = Some(system.into());
+        self
+    }
+
+    /// Append a turn.
+    pub fn push(&mut self, message: M) {
+        self.turns.push(message);
+    }
+
+    /// The system prompt, if any.
+    pub fn system(&self) -> Option<&str> {
+        self.system.as_deref()
+    }
+
+    /// The summary of trimmed turns, if any.
+    pub fn summary(&self) -> Option<&str> {
+        self.summary.as_deref()
+    }
+
+    /// The retained turns, oldest first.
+    pub fn turns(&self) -> &[M] {
+        &self.turns
+    }
+
+    /// The conversation as messages ready to send to a model.
+    pub fn messages(&self) -> Vec<M> {
+        let mut messages = Vec::with_capacity(self.turns.len() + 2);
+        if let Some(system) = &self.system {
+            messages.push(M::system(system.clone()));
+        }
+        if let Some(summary) = &self.summary {
+            messages.push(M::system(format!(
+                "Summary of the earlier conversation: {summary}"
+            )));
+        }
+        messages.extend(self.turns.iter().cloned());
+        messages
+    }
+
+    /// Token count of [`ConversationMemory::messages`] under `tokenizer`.
+    pub fn token_count<T: Tokenizer + ?Sized>(&self, tokenizer: &T) -> usize {
+        count_messages(tokenizer, &self.messages())
+    }
+
+    /// Move a cut before `index` past any tool results it would orphan.
+    ///
+    /// A tool result always follows the call it answers, so a `tool` turn at
+    /// the start of what remains answers a call that is being removed.
+    fn cut_after_tool_results(&self, mut index: usize) -> usize {
+        while self.turns.get(index).is_some_and(|turn| turn.role() == "tool") {
+            index += 1;
+        }
+        index
+    }
+}
+
+/// The outcome of a [`TrimStrategy`] that removed turns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrimReport {
+    /// Number of turns removed from the memory.
+    pub removed_turns: usize,
+    /// Token count before trimming, if the strategy measures tokens.
+    pub tokens_before: Option<usize>,
+    /// Token count after trimming, if the strategy measures tokens.
+    pub tokens_after: Option<usize>,
+    /// Whether the removed turns were folded into the summary.
+    pub summarized: bool,
+}
+
+/// Decides which turns of a [`ConversationMemory`] to keep.
+#[async_trait]
+pub trait TrimStrategy<M: MemoryMessage>: Send + Sync {
+    /// A short identifier for the strategy, recorded in trim events.
+    fn name(&self) -> &str;
+
+    /// Trim `memory` in place, returning a report if any turns were removed.
+    async fn trim(&self, ctx: &ExecutionContext, memory: &mut ConversationMemory<M>) -> Result<Option<TrimReport>>;
+}
+
+/// Keep the system prompt plus the most recent `n` turns.
+///
+/// Tool results whose call falls outside the window are dropped as well, so
+/// fewer than `n` turns may remain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeepLastN(pub usize);
+
+#[async_trait]
+impl<M: MemoryMessage> TrimStrategy<M> for KeepLastN {
+    fn name(&self) -> &str {
+        "keep_last_n"
+    }
+
+    async fn trim(&self, _ctx: &ExecutionContext, memory: &mut ConversationMemory<M>) -> Result<Option<TrimReport>> {
+        let excess = memory.cut_after_tool_results(memory.turns.len().saturating_sub(self.0));
+        if excess == 0 {
+            return Ok(None);
+        }
+        memory.turns.drain(..excess);
+        Ok(Some(TrimReport {
+            removed_turns: excess,
+            tokens_before: None,
+            tokens_after: None,
+            summarized: false,
+        }))
+    }
+}
+
+/// Drop the oldest turns until the conversation fits within `max_tokens`.
+///
+/// The system prompt, the summary and the most recent turn are always kept,
+/// so a single oversized turn can leave the memory above budget. Tool results
+/// are dropped together with the call they answer.
+pub struct SlidingWindow<T> {
+    tokenizer: T,
+    max_tokens: usize,
+}
+
+impl<T: Tokenizer> SlidingWindow<T> {
+    /// Keep the conversation within `max_tokens` as counted by `tokenizer`.
+    pub fn new(tokenizer: T, max_tokens: usize) -> Self {
+        Self { tokenizer, max_tokens }
+    }
+}
+
+#[async_trait]
+impl<T: Tokenizer, M: MemoryMessage> TrimStrategy<M> for SlidingWindow<T> {
+    fn name(&self) -> &str {
+        "sliding_window"
+    }
+
+    async fn trim(&self, _ctx: &ExecutionContext, memory: &mut ConversationMemory<M>) -> Result<Option<TrimReport>> {
+        let before = memory.token_count(&self.tokenizer);
+        let mut tokens = before;
+        let mut removed = 0;
+        while tokens > self.max_tokens && memory.turns.len() > 1 {
+            let cut = memory.cut_after_tool_results(1);
+            for turn in memory.turns.drain(..cut) {
+                tokens -= self.tokenizer.count_message(&turn);
+                removed += 1;
+            }
+        }
+        Ok((removed > 0).then_some(TrimReport {
+            removed_turns: removed,
+            tokens_before: Some(before),
+            tokens_after: Some(tokens),
+            summarized: false,
+        }))
+    }
+}
+
+/// Fold older turns into a running summary once the conversation exceeds `max_tokens`.
+///
+/// The summarizer is any [`Step`] taking the previous summary (if any) and the
+/// turns being retired, and returning the new summary — typically a model call.
+/// The most recent `keep_last` turns are kept verbatim, except for tool
+/// results whose call is summarized; those are summarized along with it.
+///
+/// # Example
+///
+/// ```rust
+/// use llm_workflow::{ExecutionContext, LambdaStep, HeuristicTokenizer};
+/// use llm_workflow::chat::Message;
+/// use llm_workflow::memory::{ConversationMemory, SummarizeOlder, TrimStrategy};
+///
+/// # tokio_test::block_on(async {
+/// let summarizer = LambdaStep::new(|(_previous, turns): (Option<String>, Vec<Message>)| async move {
+///     Ok::<String, llm_workflow::Error>(format!("{} earlier turns", turns.len()))
+/// });
+/// let strategy = SummarizeOlder::new(summarizer, HeuristicTokenizer::default(), 20, 1);
+///
+/// let mut memory = ConversationMemory::new();
+/// for i in 0..4 {
+///     memory.push(Message::user(format!("message number {i}")));
+/// }
+/// strategy.trim(&ExecutionContext::new(), &mut memory).await.unwrap();
+/// assert_eq!(memory.summary(), Some("3 earlier turns"));
+/// assert_eq!(memory.turns().len(), 1);
+/// # });
+/// ```
+pub struct SummarizeOlder<S, T> {
+    summarizer: S,
+    tokenizer: T,
+    max_tokens: usize,
+    keep_last: usize,
+}
+
+impl<S, T: Tokenizer> SummarizeOlder<S, T> {
+    /// Summarize all but the last `keep_last` turns when over `max_tokens`.
+    pub fn new(summarizer: S, tokenizer: T, max_tokens: usize, keep_last: usize) -> Self {
+        Self {
+            summarizer,
+            tokenizer,
+            max_tokens,
+            keep_last,
+        }
+    }
+}
+
+#[async_trait]
+impl<S, T, M> TrimStrategy<M> for SummarizeOlder<S, T>
+where
+    S: Step<Input = (Option<String>, Vec<M>), Output = String>,
+    T: Tokenizer,
+    M: MemoryMessage,
+{
+    fn name(&self) -> &str {
+        "summarize_older"
+    }
+
+    async fn trim(&self, ctx: &ExecutionContext, memory: &mut ConversationMemory<M>) -> Result<Option<TrimReport>> {
+        let before = memory.token_count(&self.tokenizer);
+        let excess = memory.cut_after_tool_results(memory.turns.len().saturating_sub(self.keep_last));
+        if before <= self.max_tokens || excess == 0 {
+            return Ok(None);
+        }
+
+        let retired: Vec<M> = memory.turns[..excess].to_vec();
+        let summary = self.summarizer.run(ctx, (memory.summary.clone(), retired)).await?;
+        memory.turns.drain(..excess);
+        memory.summary = Some(summary);
+
+        Ok(Some(TrimReport {
+            removed_turns: excess,
+            tokens_before: Some(before),
+            tokens_after: Some(memory.token_count(&self.tokenizer)),
+            summarized: true,
+        }))
+    }
+}
+
+/// Wraps a conversational [`StateStep`] so its memory is trimmed before every call.
+///
+/// Trimming happens on the incoming state; the inner step then appends to the
+/// trimmed memory as usual. Each trim that removes turns emits a
+/// [`WorkflowEvent::MemoryTrimmed`] event.
+pub struct ManagedMemoryStep<SS, St> {
+    inner: SS,
+    strategy: St,
+    name: String,
+}
+
+impl<SS, St> ManagedMemoryStep<SS, St> {
+    /// Trim state with `strategy` before delegating to `inner`.
+    pub fn new(inner: SS, strategy: St) -> Self {
+        Self {
+            inner,
+            strategy,
+            name: "memory".to_string(),
+        }
+    }
+
+    /// Set a human-readable name, used in emitted events.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+}
+
+impl<SS, St, M> StateStep for ManagedMemoryStep<SS, St>
+where
+    SS: StateStep<State = ConversationMemory<M>>,
+    St: TrimStrategy<M>,
+    M: MemoryMessage,
+{
+    type Input = SS::Input;
+    type Output = SS::Output;
+    type State = ConversationMemory<M>;
+
+    #[allow(clippy::type_complexity)]
+    fn run<'life0, 'async_trait>(
+        &'life0 self,
+        ctx: &'life0 ExecutionContext,
+        mut state: ConversationMemory<M>,
+        input: SS::Input,
+    ) -> std::pin::Pin<
+        Box<dyn Future<Output = Result<(SS::Output, ConversationMemory<M>)>> + Send + 'async_trait>,
+    >
+    where
+        'life0: 'async_trait,
+        Self: 'async_trait,
+    {
+        Box::pin(async move {
+            if let Some(report) = self.strategy.trim(ctx, &mut state).await? {
+                ctx.emit(WorkflowEvent::MemoryTrimmed {
+                    step_name: self.name.clone(),
+                    strategy: self.strategy.name().to_string(),
+                    removed_turns: report.removed_turns,
+                    tokens_before: report.tokens_before,
+                    tokens_after: report.tokens_after,
+                    summarized: report.summarized,
+                });
+            }
+            self.inner.run(ctx, state, input).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenizer::FnTokenizer;
+    use crate::chat::{Message, Role, ToolCall};
+    use crate::{LambdaStateStep, LambdaStep, StepAdapter};
+
+    fn ctx() -> ExecutionContext {
+        ExecutionContext::default()
+    }
+
+    /// One token per byte, with no per-message overhead beyond the trait default.
+    fn bytes() -> FnTokenizer<fn(&str) -> usize> {
+        FnTokenizer::new(str::len)
+    }
+
+    type Turn = (String, String);
+
+    fn user(text: impl Into<String>) -> Turn {
+        ("user".to_string(), text.into())
+    }
+
+    fn memory(turns: usize) -> ConversationMemory<Turn> {
+        let mut memory = ConversationMemory::new().with_system("sys");
+        for i in 0..turns {
+            memory.push(user(format!("turn{i}")));
+        }
+        memory
+    }
+
+    fn contents(memory: &ConversationMemory<Turn>) -> Vec<String> {
+        memory.turns().iter().map(|(_, content)| content.clone()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_keep_last_n_keeps_system() {
+        let mut m = memory(5);
+        let report = KeepLastN(2).trim(&ctx(), &mut m).await.unwrap().unwrap();
+        assert_eq!(report.removed_turns, 3);
+        assert_eq!(contents(&m), vec!["turn3", "turn4"]);
+        assert_eq!(m.system(), Some("sys"));
+        assert!(KeepLastN(2).trim(&ctx(), &mut m).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_sliding_window_fits_budget() {
+        let mut m = memory(4);
+        // Each turn costs 3 + 4 + 5 = 12; system costs 3 + 6 + 3 = 12; priming 3.
+        assert_eq!(m.token_count(&bytes()), 12 + 4 * 12 + 3);
+        let report = SlidingWindow::new(bytes(), 40).trim(&ctx(), &mut m).await.unwrap().unwrap();
+        assert_eq!(contents(&m), vec!["turn2", "turn3"]);
+        assert_eq!(report.tokens_before, Some(63));
+        assert_eq!(report.tokens_after, Some(39));
+        assert_eq!(m.token_count(&bytes()), 39);
+    }
+
+    #[tokio::test]
+    async fn test_sliding_window_keeps_latest_turn() {
+        let mut m = memory(3);
+        SlidingWindow::new(bytes(), 1).trim(&ctx(), &mut m).await.unwrap();
+        assert_eq!(contents(&m), vec!["turn2"]);
+    }
+
+    #[tokio::test]
+    async fn test_summarize_older_chains_previous_summary() {
+        let summarizer = LambdaStep::new(|(previous, turns): (Option<String>, Vec<Turn>)| async move {
+            let mut parts: Vec<String> = previous.into_iter().collect();
+            parts.extend(turns.into_iter().map(|(_, content)| content));
+            Ok(parts.join("|"))
+        });
+        let strategy = SummarizeOlder::new(summarizer, bytes(), 40, 1);
+
+        let mut m = memory(3);
+        let report = strategy.trim(&ctx(), &mut m).await.unwrap().unwrap();
+        assert!(report.summarized);
+        assert_eq!(m.summary(), Some("turn0|turn1"));
+
+        m.push(user("turn3"));
+        m.push(user("turn4"));
+        strategy.trim(&ctx(), &mut m).await.unwrap();
+        assert_eq!(m.summary(), Some("turn0|turn1|turn2|turn3"));
+        assert_eq!(contents(&m), vec!["turn4"]);
+        assert_eq!(m.messages()[1].1, "Summary of the earlier conversation: turn0|turn1|turn2|turn3");
+    }
+
+    fn tool_exchange() -> ConversationMemory<Message> {
+        let mut memory = ConversationMemory::new();
+        memory.push(Message::user("What's the weather?"));
+        memory.push(Message::tool_calls(vec![ToolCall::new("c1", "weather", serde_json::json!({}))]));
+        memory.push(Message::tool_result("c1", "sunny"));
+        memory.push(Message::assistant("It's sunny."));
+        memory
+    }
+
+    fn roles(memory: &ConversationMemory<Message>) -> Vec<Role> {
+        memory.turns().iter().map(|m| m.role).collect()
+    }
+
+    #[tokio::test]
+    async fn test_trimming_never_orphans_tool_results() {
+        let mut m = tool_exchange();
+        let report = KeepLastN(2).trim(&ctx(), &mut m).await.unwrap().unwrap();
+        assert_eq!(report.removed_turns, 3);
+        assert_eq!(roles(&m), vec![Role::Assistant]);
+
+        let mut m = tool_exchange();
+        let budget = m.token_count(&bytes()) - 1;
+        SlidingWindow::new(bytes(), budget).trim(&ctx(), &mut m).await.unwrap();
+        assert_eq!(roles(&m), vec![Role::Assistant, Role::Tool, Role::Assistant]);
+        SlidingWindow::new(bytes(), 1).trim(&ctx(), &mut m).await.unwrap();
+        assert_eq!(roles(&m), vec![Role::Assistant]);
+
+        let summarizer = LambdaStep::new(|(_, turns): (Option<String>, Vec<Message>)| async move {
+            Ok(format!("{} turns", turns.len()))
+        });
+        let mut m = tool_exchange();
+        SummarizeOlder::new(summarizer, bytes(), 1, 2).trim(&ctx(), &mut m).await.unwrap();
+        assert_eq!(m.summary(), Some("3 turns"));
+        assert_eq!(roles(&m), vec![Role::Assistant]);
+    }
+
+    #[tokio::test]
+    async fn test_managed_memory_trims_and_emits() {
+        let chat = LambdaStateStep::new(|mut memory: ConversationMemory<Turn>, input: String| async move {
+            memory.push(user(input));
+            let reply = format!("{} turns seen", memory.turns().len());
+            memory.push(("assistant".to_string(), reply.clone()));
+            Ok((reply, memory))
+        });
+        let managed = ManagedMemoryStep::new(chat, KeepLastN(2)).with_name("chat_memory");
+        let adapter = StepAdapter::new(managed);
+        let ctx = ctx();
+
+        for i in 0..4 {
+            adapter.run(&ctx, format!("q{i}")).await.unwrap();
+        }
+        let trims: Vec<(String, usize)> = ctx
+            .trace_snapshot()
+            .into_iter()
+            .filter_map(|entry| match entry.event {
+                WorkflowEvent::MemoryTrimmed {
+                    step_name,
+                    removed_turns,
+                    ..
+                } => Some((step_name, removed_turns)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(trims, vec![("chat_memory".to_string(), 2), ("chat_memory".to_string(), 2)]);
+    }
+
+    #[test]
+    fn test_memory_round_trips_through_json() {
+        let mut m = memory(1);
+        m.summary = Some("earlier".to_string());
+        let json = serde_json::to_string(&m).unwrap();
+        assert_eq!(serde_json::from_str::<ConversationMemory<Turn>>(&json).unwrap(), m);
+    }
+}
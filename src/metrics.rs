@@ -4,6 +4,7 @@
 //! execution statistics, and failures.
 
 use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
 
 /// Aggregated metrics for a workflow execution.
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
@@ -18,6 +19,20 @@ pub struct WorkflowMetrics {
     pub steps_completed: usize,
     /// Collected failure messages from the workflow.
     pub failures: Vec<String>,
+    /// Number of cached step lookups served from a cache.
+    #[serde(default)]
+    pub cache_hits: usize,
+    /// Number of cached step lookups that had to run the underlying step.
+    #[serde(default)]
+    pub cache_misses: usize,
+    /// Tokens that cache hits avoided spending.
+    #[serde(default)]
+    pub tokens_saved: usize,
+    /// Per-child breakdown of metrics for nested sub-workflows, keyed by name.
+    ///
+    /// Child metrics are also rolled up into the totals above.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub children: BTreeMap<String, WorkflowMetrics>,
 }
 
 impl WorkflowMetrics {
@@ -50,6 +65,17 @@ impl WorkflowMetrics {
         self.steps_completed += 1;
     }
 
+    /// Record a cache hit that avoided spending `tokens_saved` tokens.
+    pub fn record_cache_hit(&mut self, tokens_saved: usize) {
+        self.cache_hits += 1;
+        self.tokens_saved += tokens_saved;
+    }
+
+    /// Record a cache miss.
+    pub fn record_cache_miss(&mut self) {
+        self.cache_misses += 1;
+    }
+
     /// Check if there were any failures.
     pub fn has_failures(&self) -> bool {
         !self.failures.is_empty()
@@ -59,6 +85,31 @@ impl WorkflowMetrics {
     pub fn total_tokens(&self) -> usize {
         self.total_token_count
     }
+
+    /// Add another set of metrics into this one.
+    ///
+    /// Counters are summed, failures appended, and child breakdowns merged by name.
+    pub fn merge(&mut self, other: &WorkflowMetrics) {
+        self.prompt_token_count += other.prompt_token_count;
+        self.completion_token_count += other.completion_token_count;
+        self.total_token_count += other.total_token_count;
+        self.steps_completed += other.steps_completed;
+        self.failures.extend(other.failures.iter().cloned());
+        self.cache_hits += other.cache_hits;
+        self.cache_misses += other.cache_misses;
+        self.tokens_saved += other.tokens_saved;
+        for (name, child) in &other.children {
+            self.children.entry(name.clone()).or_default().merge(child);
+        }
+    }
+
+    /// Roll a named child's metrics into the totals and record them in the breakdown.
+    ///
+    /// Repeated calls with the same name accumulate into a single entry.
+    pub fn record_child(&mut self, name: impl Into<String>, child: &WorkflowMetrics) {
+        self.merge(child);
+        self.children.entry(name.into()).or_default().merge(child);
+    }
 }
 
 #[cfg(test)]
@@ -165,4 +216,65 @@ mod tests {
         assert_eq!(deserialized.steps_completed, 1);
         assert_eq!(deserialized.failures.len(), 1);
     }
+
+    #[test]
+    fn test_merge_sums_counters() {
+        let mut parent = WorkflowMetrics::default();
+        parent.add_tokens(10, 5);
+        parent.record_step();
+
+        let mut other = WorkflowMetrics::default();
+        other.add_tokens(100, 50);
+        other.record_step();
+        other.record_failure("child error".to_string());
+
+        parent.merge(&other);
+        assert_eq!(parent.prompt_token_count, 110);
+        assert_eq!(parent.completion_token_count, 55);
+        assert_eq!(parent.total_tokens(), 165);
+        assert_eq!(parent.steps_completed, 2);
+        assert_eq!(parent.failures, vec!["child error".to_string()]);
+    }
+
+    #[test]
+    fn test_record_child_keeps_breakdown() {
+        let mut parent = WorkflowMetrics::default();
+        let mut child = WorkflowMetrics::default();
+        child.add_tokens(30, 20);
+        child.record_step();
+
+        parent.record_child("summarize", &child);
+        parent.record_child("summarize", &child);
+
+        assert_eq!(parent.total_tokens(), 100);
+        assert_eq!(parent.children.len(), 1);
+        let breakdown = &parent.children["summarize"];
+        assert_eq!(breakdown.total_tokens(), 100);
+        assert_eq!(breakdown.steps_completed, 2);
+    }
+
+    #[test]
+    fn test_cache_counters() {
+        let mut metrics = WorkflowMetrics::default();
+        metrics.record_cache_miss();
+        metrics.record_cache_hit(120);
+        metrics.record_cache_hit(30);
+        assert_eq!(metrics.cache_hits, 2);
+        assert_eq!(metrics.cache_misses, 1);
+        assert_eq!(metrics.tokens_saved, 150);
+
+        let mut total = WorkflowMetrics::default();
+        total.merge(&metrics);
+        assert_eq!(total.tokens_saved, 150);
+    }
+
+    #[test]
+    fn test_empty_children_not_serialized() {
+        let metrics = WorkflowMetrics::default();
+        let json = serde_json::to_string(&metrics).unwrap();
+        assert!(!json.contains("children"));
+
+        let deserialized: WorkflowMetrics = serde_json::from_str(&json).unwrap();
+        assert!(deserialized.children.is_empty());
+    }
 }
@@ -0,0 +1,395 @@
+//! Shared rate limiting for provider requests and tokens.
+//!
+//! A [`RateLimiter`] is a cheaply cloneable handle over token buckets. Attach
+//! the same limiter to many steps (across workflows) with [`RateLimitedStep`]
+//! to enforce a provider's requests-per-minute and tokens-per-minute limits.
+//! Callers that find the bucket empty wait in FIFO order rather than failing,
+//! so fan-outs such as [`ParallelMapStep`](crate::ParallelMapStep) simply queue.
+
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::{ExecutionContext, Result, WorkflowEvent, step::Step};
+
+#[derive(Debug)]
+struct Bucket {
+    capacity: f64,
+    available: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl Bucket {
+    fn new(amount: usize, per: Duration) -> Self {
+        assert!(amount > 0, "rate limit amount must be greater than zero");
+        assert!(!per.is_zero(), "rate limit period must be non-zero");
+        let capacity = amount as f64;
+        Self {
+            capacity,
+            available: capacity,
+            refill_per_sec: capacity / per.as_secs_f64(),
+            last_refill: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.available = (self.available + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+    }
+
+    /// Time until at least `amount` is available (capped at capacity).
+    fn wait_for(&mut self, amount: f64) -> Duration {
+        self.refill();
+        let needed = amount.min(self.capacity) - self.available;
+        if needed <= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(needed / self.refill_per_sec)
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Limits {
+    requests: Option<Bucket>,
+    tokens: Option<Bucket>,
+}
+
+/// A shareable token-bucket limiter for request counts and token usage.
+///
+/// Buckets start full, so up to the configured amount can be used in a burst
+/// before callers start waiting. An estimate of a request's tokens can be
+/// taken up front by [`RateLimiter::acquire`]; actual usage is settled after
+/// the fact (see [`RateLimiter::record_tokens`] and
+/// [`RateLimiter::refund_tokens`]). A bucket driven into debt blocks new
+/// requests until it refills.
+///
+/// # Example
+///
+/// ```rust
+/// use llm_workflow::{LambdaStep, ParallelMapStep, Step, ExecutionContext};
+/// use llm_workflow::rate_limit::{RateLimiter, RateLimitedStep};
+///
+/// # tokio_test::block_on(async {
+/// let limiter = RateLimiter::new()
+///     .requests_per_minute(500)
+///     .tokens_per_minute(90_000);
+///
+/// let call = LambdaStep::new(|x: i32| async move { Ok::<i32, llm_workflow::Error>(x * 2) });
+/// let limited = RateLimitedStep::new(call, limiter.clone());
+///
+/// let out = ParallelMapStep::new(limited)
+///     .run(&ExecutionContext::new(), vec![1, 2, 3])
+///     .await
+///     .unwrap();
+/// assert_eq!(out, vec![2, 4, 6]);
+/// # });
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RateLimiter {
+    limits: Arc<Mutex<Limits>>,
+    queue: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl RateLimiter {
+    /// Create a limiter with no limits configured.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow at most `amount` requests per `per`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `amount` is zero or `per` is zero.
+    #[must_use]
+    pub fn requests(self, amount: usize, per: Duration) -> Self {
+        self.limits.lock().unwrap().requests = Some(Bucket::new(amount, per));
+        self
+    }
+
+    /// Allow at most `amount` tokens per `per`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `amount` is zero or `per` is zero.
+    #[must_use]
+    pub fn tokens(self, amount: usize, per: Duration) -> Self {
+        self.limits.lock().unwrap().tokens = Some(Bucket::new(amount, per));
+        self
+    }
+
+    /// Allow at most `amount` requests per minute.
+    #[must_use]
+    pub fn requests_per_minute(self, amount: usize) -> Self {
+        self.requests(amount, Duration::from_secs(60))
+    }
+
+    /// Allow at most `amount` tokens per minute.
+    #[must_use]
+    pub fn tokens_per_minute(self, amount: usize) -> Self {
+        self.tokens(amount, Duration::from_secs(60))
+    }
+
+    /// Wait until one request and `estimated_tokens` tokens are available, then take them.
+    ///
+    /// Waiters are served in FIFO order. Returns how long the caller waited,
+    /// or [`Duration::ZERO`] if capacity was available immediately.
+    pub async fn acquire(&self, estimated_tokens: usize) -> Duration {
+        let start = Instant::now();
+        let mut waited = false;
+        let _turn = self.queue.lock().await;
+        loop {
+            let wait = {
+                let mut limits = self.limits.lock().unwrap();
+                let request_wait = limits.requests.as_mut().map_or(Duration::ZERO, |b| b.wait_for(1.0));
+                let token_wait = limits
+                    .tokens
+                    .as_mut()
+                    .map_or(Duration::ZERO, |b| b.wait_for(estimated_tokens as f64));
+                let wait = request_wait.max(token_wait);
+                if wait.is_zero() {
+                    if let Some(bucket) = limits.requests.as_mut() {
+                        bucket.available -= 1.0;
+                    }
+                    if let Some(bucket) = limits.tokens.as_mut() {
+                        bucket.available -= estimated_tokens as f64;
+                    }
+                }
+                wait
+            };
+            if wait.is_zero() {
+                return if waited { start.elapsed() } else { Duration::ZERO };
+            }
+            waited = true;
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Debit tokens that were consumed after a request was admitted.
+    ///
+    /// The token bucket may go negative, delaying subsequent requests.
+    pub fn record_tokens(&self, count: usize) {
+        let mut limits = self.limits.lock().unwrap();
+        if let Some(bucket) = limits.tokens.as_mut() {
+            bucket.refill();
+            bucket.available -= count as f64;
+        }
+    }
+
+    /// Return tokens taken by [`RateLimiter::acquire`] that a request did not use.
+    ///
+    /// The bucket never refills beyond its capacity.
+    pub fn refund_tokens(&self, count: usize) {
+        let mut limits = self.limits.lock().unwrap();
+        if let Some(bucket) = limits.tokens.as_mut() {
+            bucket.refill();
+            bucket.available = (bucket.available + count as f64).min(bucket.capacity);
+        }
+    }
+}
+
+type EstimateFn<I> = Box<dyn Fn(&I) -> usize + Send + Sync>;
+
+/// Wraps a step so each run first acquires capacity from a shared [`RateLimiter`].
+///
+/// The inner step runs under an [`ExecutionContext::child`] scope; the tokens it
+/// reports via [`ExecutionContext::record_tokens`] are debited from the
+/// limiter's token bucket before the scope is merged back. When a run had to
+/// wait, a [`WorkflowEvent::RateLimited`] event is emitted.
+///
+/// Without an estimate, token usage is only known once a call returns, so a
+/// concurrent fan-out is admitted at once and can overshoot a tokens-per-minute
+/// limit. Set [`RateLimitedStep::with_estimate`] to reserve each call's
+/// expected tokens before it starts; the difference from the reported usage is
+/// settled afterwards.
+pub struct RateLimitedStep<S: Step> {
+    inner: S,
+    limiter: RateLimiter,
+    estimate: Option<EstimateFn<S::Input>>,
+    name: String,
+}
+
+impl<S: Step> RateLimitedStep<S> {
+    /// Wrap `inner` with the given limiter handle.
+    pub fn new(inner: S, limiter: RateLimiter) -> Self {
+        Self {
+            inner,
+            limiter,
+            estimate: None,
+            name: "rate_limited".to_string(),
+        }
+    }
+
+    /// Reserve `estimate(input)` tokens before each call, e.g. a
+    /// [`Tokenizer`](crate::Tokenizer) count of the prompt plus the expected
+    /// completion length.
+    pub fn with_estimate<F>(mut self, estimate: F) -> Self
+    where
+        F: Fn(&S::Input) -> usize + Send + Sync + 'static,
+    {
+        self.estimate = Some(Box::new(estimate));
+        self
+    }
+
+    /// Set a human-readable name, used in emitted events.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// The shared limiter handle.
+    pub fn limiter(&self) -> &RateLimiter {
+        &self.limiter
+    }
+}
+
+#[async_trait]
+impl<S> Step for RateLimitedStep<S>
+where
+    S: Step,
+    S::Input: 'static,
+    S::Output: 'static,
+{
+    type Input = S::Input;
+    type Output = S::Output;
+
+    async fn run(&self, ctx: &ExecutionContext, input: S::Input) -> Result<S::Output> {
+        let estimated = self.estimate.as_ref().map_or(0, |estimate| estimate(&input));
+        let waited = self.limiter.acquire(estimated).await;
+        if !waited.is_zero() {
+            ctx.emit(WorkflowEvent::RateLimited {
+                step_name: self.name.clone(),
+                waited_ms: waited.as_millis(),
+            });
+        }
+
+        let child = ctx.child();
+        let result = self.inner.run(&child, input).await;
+        let usage = child.snapshot();
+        let used = usage.prompt_token_count + usage.completion_token_count;
+        if used >= estimated {
+            self.limiter.record_tokens(used - estimated);
+        } else {
+            self.limiter.refund_tokens(estimated - used);
+        }
+        ctx.merge(&child);
+
+        result
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LambdaStep, ParallelMapStep};
+
+    struct Spend(usize);
+
+    #[async_trait]
+    impl Step for Spend {
+        type Input = u32;
+        type Output = u32;
+
+        async fn run(&self, ctx: &ExecutionContext, input: u32) -> Result<u32> {
+            ctx.record_tokens(self.0, 0);
+            Ok(input)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unlimited_limiter_never_waits() {
+        let limiter = RateLimiter::new();
+        for _ in 0..100 {
+            assert!(limiter.acquire(1_000).await.is_zero());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_limit_queues_fan_out() {
+        let limiter = RateLimiter::new().requests(2, Duration::from_millis(200));
+        let step = RateLimitedStep::new(
+            LambdaStep::new(|x: u32| async move { Ok(x + 1) }),
+            limiter,
+        );
+
+        let ctx = ExecutionContext::new();
+        let start = Instant::now();
+        let out = ParallelMapStep::new(step).run(&ctx, vec![1, 2, 3, 4]).await.unwrap();
+        assert_eq!(out, vec![2, 3, 4, 5]);
+        // Two burst slots, then two more at 100ms each.
+        assert!(start.elapsed() >= Duration::from_millis(180));
+
+        let waits = ctx
+            .trace_snapshot()
+            .into_iter()
+            .filter(|e| matches!(e.event, WorkflowEvent::RateLimited { .. }))
+            .count();
+        assert_eq!(waits, 2);
+    }
+
+    #[tokio::test]
+    async fn test_token_usage_is_debited_and_merged() {
+        let limiter = RateLimiter::new().tokens(100, Duration::from_millis(200));
+        let step = RateLimitedStep::new(Spend(150), limiter.clone());
+        let ctx = ExecutionContext::new();
+
+        step.run(&ctx, 1).await.unwrap();
+        assert_eq!(ctx.snapshot().prompt_token_count, 150);
+
+        // The bucket is 50 tokens in debt, so the next request waits ~100ms.
+        let start = Instant::now();
+        step.run(&ctx, 2).await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(80));
+    }
+
+    #[tokio::test]
+    async fn test_limiter_is_shared_across_steps() {
+        let limiter = RateLimiter::new().requests(1, Duration::from_millis(100));
+        let a = RateLimitedStep::new(Spend(0), limiter.clone());
+        let b = RateLimitedStep::new(Spend(0), limiter);
+        let ctx = ExecutionContext::new();
+
+        let start = Instant::now();
+        a.run(&ctx, 1).await.unwrap();
+        b.run(&ctx, 2).await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(80));
+    }
+
+    #[tokio::test]
+    async fn test_estimate_reserves_tokens_before_fan_out() {
+        let limiter = RateLimiter::new().tokens(100, Duration::from_millis(200));
+        let step = RateLimitedStep::new(Spend(60), limiter.clone()).with_estimate(|_input: &u32| 60);
+        let ctx = ExecutionContext::new();
+
+        // Only one 60-token call fits at first; the second waits ~40ms for a refill.
+        let start = Instant::now();
+        ParallelMapStep::new(step).run(&ctx, vec![1, 2]).await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(30));
+        let waits = ctx
+            .trace_snapshot()
+            .into_iter()
+            .filter(|e| matches!(e.event, WorkflowEvent::RateLimited { .. }))
+            .count();
+        assert_eq!(waits, 1);
+    }
+
+    #[tokio::test]
+    async fn test_unused_estimate_is_refunded() {
+        let limiter = RateLimiter::new().tokens(100, Duration::from_secs(60));
+        let step = RateLimitedStep::new(Spend(10), limiter.clone()).with_estimate(|_input: &u32| 90);
+        let ctx = ExecutionContext::new();
+
+        step.run(&ctx, 1).await.unwrap();
+        // 80 of the 90 reserved tokens came back, so another 90 fit without waiting.
+        assert!(limiter.acquire(90).await.is_zero());
+    }
+}
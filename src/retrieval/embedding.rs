@@ -0,0 +1,204 @@
+//! Embedding models and batched embedding.
+
+use async_trait::async_trait;
+
+use crate::cache::Fnv1a;
+use crate::step::batch::{BatchPolicy, BatchStep, FixedSize};
+use crate::{ExecutionContext, Result, step::Step};
+
+/// A dense vector representation of a text.
+pub type Embedding = Vec<f32>;
+
+/// A step that embeds a batch of texts, returning one vector per text in order.
+///
+/// Blanket-implemented for every [`Step`] with `Input = Vec<String>` and
+/// `Output = Vec<Embedding>`, so provider clients only need to implement
+/// [`Step`].
+pub trait EmbeddingModel: Step<Input = Vec<String>, Output = Vec<Embedding>> {}
+
+impl<S> EmbeddingModel for S where S: Step<Input = Vec<String>, Output = Vec<Embedding>> {}
+
+/// Embeds texts in batches sized for the model's request limits.
+///
+/// A thin wrapper over [`BatchStep`]: batches are planned by a
+/// [`BatchPolicy`] — a fixed number of texts by default, or a
+/// [`TokenBudget`](crate::TokenBudget) — and the model must return one vector
+/// per text.
+///
+/// # Example
+///
+/// ```rust
+/// use llm_workflow::{ExecutionContext, Step};
+/// use llm_workflow::retrieval::{EmbedStep, HashEmbedding};
+///
+/// # tokio_test::block_on(async {
+/// let embed = EmbedStep::new(HashEmbedding::new(64), 2).with_concurrency(2);
+/// let texts = vec!["red apple".to_string(), "green pear".to_string(), "red apple".to_string()];
+/// let vectors = embed.run(&ExecutionContext::new(), texts).await.unwrap();
+/// assert_eq!(vectors.len(), 3);
+/// assert_eq!(vectors[0], vectors[2]);
+/// # });
+/// ```
+pub struct EmbedStep<M, P = FixedSize> {
+    batch: BatchStep<M, P>,
+}
+
+impl<M> EmbedStep<M> {
+    /// Embed at most `batch_size` texts per model call.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `batch_size` is zero.
+    pub fn new(model: M, batch_size: usize) -> Self {
+        Self::with_policy(model, FixedSize::new(batch_size))
+    }
+}
+
+impl<M, P> EmbedStep<M, P> {
+    /// Embed in batches planned by `policy`.
+    pub fn with_policy(model: M, policy: P) -> Self {
+        Self {
+            batch: BatchStep::with_policy(model, policy).with_name("embed"),
+        }
+    }
+
+    /// Run up to `limit` batches at once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is zero.
+    pub fn with_concurrency(mut self, limit: usize) -> Self {
+        self.batch = self.batch.with_concurrency(limit);
+        self
+    }
+
+    /// Set a human-readable name, used in emitted events.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.batch = self.batch.with_name(name);
+        self
+    }
+}
+
+#[async_trait]
+impl<M, P> Step for EmbedStep<M, P>
+where
+    M: EmbeddingModel,
+    P: BatchPolicy<String>,
+{
+    type Input = Vec<String>;
+    type Output = Vec<Embedding>;
+
+    async fn run(&self, ctx: &ExecutionContext, input: Vec<String>) -> Result<Vec<Embedding>> {
+        self.batch.run(ctx, input).await
+    }
+
+    fn name(&self) -> &str {
+        self.batch.name()
+    }
+}
+
+/// A deterministic embedding built by hashing words into buckets.
+///
+/// Each lowercase alphanumeric word adds ±1 to one of `dimensions` buckets,
+/// chosen by a stable FNV-1a hash, and the result is L2-normalized. Texts
+/// sharing words get similar vectors, which is enough to exercise retrieval
+/// pipelines without a model or network access. Text with no words embeds to
+/// the zero vector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HashEmbedding {
+    dimensions: usize,
+}
+
+impl HashEmbedding {
+    /// Create an embedding with `dimensions` buckets.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dimensions` is zero.
+    pub fn new(dimensions: usize) -> Self {
+        assert!(dimensions > 0, "dimensions must be greater than zero");
+        Self { dimensions }
+    }
+
+    /// The length of the produced vectors.
+    pub fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    /// Embed a single text.
+    pub fn embed(&self, text: &str) -> Embedding {
+        let mut vector = vec![0.0f32; self.dimensions];
+        for word in text.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()) {
+            let hash = Fnv1a::hash(word.to_lowercase().as_bytes());
+            let bucket = (hash % self.dimensions as u64) as usize;
+            vector[bucket] += if hash >> 63 == 0 { 1.0 } else { -1.0 };
+        }
+        let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            vector.iter_mut().for_each(|x| *x /= norm);
+        }
+        vector
+    }
+}
+
+#[async_trait]
+impl Step for HashEmbedding {
+    type Input = Vec<String>;
+    type Output = Vec<Embedding>;
+
+    async fn run(&self, _ctx: &ExecutionContext, input: Vec<String>) -> Result<Vec<Embedding>> {
+        Ok(input.iter().map(|text| self.embed(text)).collect())
+    }
+
+    fn name(&self) -> &str {
+        "hash_embedding"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LambdaStep, TokenBudget, WorkflowEvent};
+
+    fn dot(a: &[f32], b: &[f32]) -> f32 {
+        a.iter().zip(b).map(|(x, y)| x * y).sum()
+    }
+
+    #[test]
+    fn test_hash_embedding_is_deterministic_and_normalized() {
+        let model = HashEmbedding::new(32);
+        let a = model.embed("The quick brown fox");
+        assert_eq!(a, model.embed("the QUICK, brown fox!"));
+        assert!((dot(&a, &a) - 1.0).abs() < 1e-6);
+        assert!(model.embed("  ...  ").iter().all(|x| *x == 0.0));
+    }
+
+    #[test]
+    fn test_hash_embedding_similarity_tracks_shared_words() {
+        let model = HashEmbedding::new(256);
+        let query = model.embed("rust async runtime");
+        let close = model.embed("an async runtime for rust");
+        let far = model.embed("banana bread recipe");
+        assert!(dot(&query, &close) > dot(&query, &far));
+    }
+
+    #[tokio::test]
+    async fn test_embed_step_batches_by_token_budget() {
+        let model = LambdaStep::new(|texts: Vec<String>| async move {
+            Ok::<Vec<Embedding>, crate::Error>(texts.iter().map(|t| vec![t.len() as f32]).collect())
+        });
+        let embed = EmbedStep::with_policy(model, TokenBudget::new(6, |text: &String| text.len()));
+        let ctx = ExecutionContext::new();
+        let texts = ["abc", "abc", "abcdef"].map(String::from).to_vec();
+        let out = embed.run(&ctx, texts).await.unwrap();
+        assert_eq!(out, vec![vec![3.0], vec![3.0], vec![6.0]]);
+
+        match &ctx.trace_snapshot()[0].event {
+            WorkflowEvent::BatchPlanned { step_name, batch_sizes, .. } => {
+                assert_eq!(step_name, "embed");
+                assert_eq!(batch_sizes, &[2, 1]);
+            }
+            other => panic!("unexpected event {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,522 @@
+//! An in-memory vector index and the retrieval step built on it.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+use super::embedding::{Embedding, EmbeddingModel};
+use crate::{Error, ExecutionContext, Result, WorkflowEvent, step::Step};
+
+/// A text stored in a [`VectorIndex`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Document {
+    /// Unique identifier; inserting a document with an existing id replaces it.
+    pub id: String,
+    /// The text that was embedded.
+    pub text: String,
+    /// Application-defined metadata, matched by [`Filter`]s.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, Value>,
+}
+
+impl Document {
+    /// Create a document without metadata.
+    pub fn new(id: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            text: text.into(),
+            metadata: BTreeMap::new(),
+        }
+    }
+
+    /// Attach a metadata entry.
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+}
+
+/// A search hit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoredDocument {
+    /// The matching document.
+    pub document: Document,
+    /// Similarity to the query; higher is closer.
+    pub score: f32,
+}
+
+/// How query and document vectors are compared.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Similarity {
+    /// Cosine of the angle between the vectors, in `[-1, 1]`; zero vectors score 0.
+    #[default]
+    Cosine,
+    /// Raw dot product, for models that return normalized vectors.
+    Dot,
+}
+
+impl Similarity {
+    /// Score `a` against `b`. Both must have the same length.
+    pub fn score(&self, a: &[f32], b: &[f32]) -> f32 {
+        let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+        match self {
+            Similarity::Dot => dot,
+            Similarity::Cosine => {
+                let norms = a.iter().map(|x| x * x).sum::<f32>().sqrt() * b.iter().map(|x| x * x).sum::<f32>().sqrt();
+                if norms == 0.0 { 0.0 } else { dot / norms }
+            }
+        }
+    }
+}
+
+/// A predicate over [`Document::metadata`].
+///
+/// # Example
+///
+/// ```rust
+/// use llm_workflow::retrieval::{Document, Filter};
+///
+/// let doc = Document::new("1", "...").with_metadata("lang", "en").with_metadata("year", 2023);
+/// let filter = Filter::eq("lang", "en").and(Filter::range("year", Some(2020.0), None));
+/// assert!(filter.matches(&doc.metadata));
+/// assert!(!Filter::one_of("lang", ["de", "fr"]).matches(&doc.metadata));
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Filter {
+    /// The key is present with exactly this value.
+    Eq(String, Value),
+    /// The key is present with one of these values.
+    In(String, Vec<Value>),
+    /// The key is present with a number within the inclusive bounds.
+    Range {
+        /// The metadata key.
+        key: String,
+        /// Lower bound, if any.
+        min: Option<f64>,
+        /// Upper bound, if any.
+        max: Option<f64>,
+    },
+    /// The key is present.
+    Exists(String),
+    /// Every filter matches.
+    All(Vec<Filter>),
+    /// At least one filter matches.
+    Any(Vec<Filter>),
+    /// The filter does not match.
+    Not(Box<Filter>),
+}
+
+impl Filter {
+    /// Match documents whose `key` equals `value`.
+    pub fn eq(key: impl Into<String>, value: impl Into<Value>) -> Self {
+        Filter::Eq(key.into(), value.into())
+    }
+
+    /// Match documents whose `key` is one of `values`.
+    pub fn one_of<V: Into<Value>>(key: impl Into<String>, values: impl IntoIterator<Item = V>) -> Self {
+        Filter::In(key.into(), values.into_iter().map(Into::into).collect())
+    }
+
+    /// Match documents whose `key` is a number within `min..=max`.
+    pub fn range(key: impl Into<String>, min: Option<f64>, max: Option<f64>) -> Self {
+        Filter::Range { key: key.into(), min, max }
+    }
+
+    /// Match documents that have `key`.
+    pub fn exists(key: impl Into<String>) -> Self {
+        Filter::Exists(key.into())
+    }
+
+    /// Match documents matching both this filter and `other`.
+    pub fn and(self, other: Filter) -> Self {
+        match self {
+            Filter::All(mut filters) => {
+                filters.push(other);
+                Filter::All(filters)
+            }
+            filter => Filter::All(vec![filter, other]),
+        }
+    }
+
+    /// Match documents matching this filter or `other`.
+    pub fn or(self, other: Filter) -> Self {
+        match self {
+            Filter::Any(mut filters) => {
+                filters.push(other);
+                Filter::Any(filters)
+            }
+            filter => Filter::Any(vec![filter, other]),
+        }
+    }
+
+    /// Whether `metadata` satisfies the filter.
+    pub fn matches(&self, metadata: &BTreeMap<String, Value>) -> bool {
+        match self {
+            Filter::Eq(key, value) => metadata.get(key) == Some(value),
+            Filter::In(key, values) => metadata.get(key).is_some_and(|v| values.contains(v)),
+            Filter::Range { key, min, max } => metadata.get(key).and_then(Value::as_f64).is_some_and(|n| {
+                min.is_none_or(|min| n >= min) && max.is_none_or(|max| n <= max)
+            }),
+            Filter::Exists(key) => metadata.contains_key(key),
+            Filter::All(filters) => filters.iter().all(|f| f.matches(metadata)),
+            Filter::Any(filters) => filters.iter().any(|f| f.matches(metadata)),
+            Filter::Not(filter) => !filter.matches(metadata),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct IndexState {
+    dimensions: Option<usize>,
+    entries: Vec<(Document, Embedding)>,
+}
+
+/// A thread-safe, in-memory vector index with exhaustive search.
+///
+/// All vectors must have the same length, fixed by the first insert. Search
+/// scores every stored document, so it suits corpora of up to tens of
+/// thousands of documents; share one index between ingestion and
+/// [`RetrieveStep`]s through an [`Arc`].
+///
+/// # Example
+///
+/// ```rust
+/// use llm_workflow::ExecutionContext;
+/// use llm_workflow::retrieval::{Document, Filter, HashEmbedding, VectorIndex};
+///
+/// # tokio_test::block_on(async {
+/// let model = HashEmbedding::new(128);
+/// let index = VectorIndex::default();
+/// index.add(&ExecutionContext::new(), &model, vec![
+///     Document::new("a", "Tokio is an async runtime for Rust").with_metadata("topic", "rust"),
+///     Document::new("b", "Sourdough bread needs a starter").with_metadata("topic", "baking"),
+/// ]).await.unwrap();
+///
+/// let hits = index.search(&model.embed("rust async runtime"), 1, None).unwrap();
+/// assert_eq!(hits[0].document.id, "a");
+/// let hits = index.search(&model.embed("rust async runtime"), 5, Some(&Filter::eq("topic", "baking"))).unwrap();
+/// assert_eq!(hits.len(), 1);
+/// # });
+/// ```
+#[derive(Debug, Default)]
+pub struct VectorIndex {
+    similarity: Similarity,
+    state: RwLock<IndexState>,
+}
+
+impl VectorIndex {
+    /// Create an empty index comparing vectors with `similarity`.
+    pub fn new(similarity: Similarity) -> Self {
+        Self {
+            similarity,
+            state: RwLock::default(),
+        }
+    }
+
+    /// The similarity used for search.
+    pub fn similarity(&self) -> Similarity {
+        self.similarity
+    }
+
+    /// Store a document with its embedding, replacing any document with the same id.
+    ///
+    /// Returns [`Error::Validation`] if the embedding's length differs from
+    /// the index's.
+    pub fn insert(&self, document: Document, embedding: Embedding) -> Result<()> {
+        self.insert_all(vec![(document, embedding)])
+    }
+
+    /// Store a batch, checking every embedding's length before storing any.
+    fn insert_all(&self, batch: Vec<(Document, Embedding)>) -> Result<()> {
+        let mut state = self.state.write().unwrap();
+        let Some(first) = batch.first() else {
+            return Ok(());
+        };
+        let dimensions = state.dimensions.unwrap_or(first.1.len());
+        batch
+            .iter()
+            .try_for_each(|(_, embedding)| check_dimensions(dimensions, embedding))?;
+        state.dimensions = Some(dimensions);
+        for (document, embedding) in batch {
+            match state.entries.iter_mut().find(|(doc, _)| doc.id == document.id) {
+                Some(entry) => *entry = (document, embedding),
+                None => state.entries.push((document, embedding)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Embed `documents` with `model` and store them.
+    ///
+    /// Nothing is stored unless every embedding has the index's length.
+    pub async fn add<M: EmbeddingModel>(&self, ctx: &ExecutionContext, model: &M, documents: Vec<Document>) -> Result<()> {
+        let texts = documents.iter().map(|doc| doc.text.clone()).collect();
+        let embeddings = model.run(ctx, texts).await?;
+        if embeddings.len() != documents.len() {
+            return Err(Error::Validation(format!(
+                "model returned {} embeddings for {} documents",
+                embeddings.len(),
+                documents.len()
+            )));
+        }
+        self.insert_all(documents.into_iter().zip(embeddings).collect())
+    }
+
+    /// Remove the document with `id`, returning whether it was present.
+    pub fn remove(&self, id: &str) -> bool {
+        let mut state = self.state.write().unwrap();
+        let before = state.entries.len();
+        state.entries.retain(|(doc, _)| doc.id != id);
+        state.entries.len() != before
+    }
+
+    /// Look up a document by id.
+    pub fn get(&self, id: &str) -> Option<Document> {
+        let state = self.state.read().unwrap();
+        state.entries.iter().find(|(doc, _)| doc.id == id).map(|(doc, _)| doc.clone())
+    }
+
+    /// Number of stored documents.
+    pub fn len(&self) -> usize {
+        self.state.read().unwrap().entries.len()
+    }
+
+    /// Whether the index has no documents.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The `k` documents most similar to `query` that match `filter`, best first.
+    ///
+    /// Ties keep insertion order. Returns [`Error::Validation`] if the query's
+    /// length differs from the index's.
+    pub fn search(&self, query: &[f32], k: usize, filter: Option<&Filter>) -> Result<Vec<ScoredDocument>> {
+        let state = self.state.read().unwrap();
+        if let Some(dimensions) = state.dimensions {
+            check_dimensions(dimensions, query)?;
+        }
+        let mut hits: Vec<ScoredDocument> = state
+            .entries
+            .iter()
+            .filter(|(doc, _)| filter.is_none_or(|f| f.matches(&doc.metadata)))
+            .map(|(doc, embedding)| ScoredDocument {
+                document: doc.clone(),
+                score: self.similarity.score(query, embedding),
+            })
+            .collect();
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(k);
+        Ok(hits)
+    }
+}
+
+fn check_dimensions(expected: usize, vector: &[f32]) -> Result<()> {
+    match vector.len() == expected {
+        true => Ok(()),
+        false => Err(Error::Validation(format!(
+            "expected a {expected}-dimensional vector, got {}",
+            vector.len()
+        ))),
+    }
+}
+
+/// Turns a query into the most similar documents in a [`VectorIndex`].
+///
+/// The query is embedded with the given model, then the top `k` documents
+/// matching the optional filter and minimum score are returned, best first.
+/// Each run emits a [`WorkflowEvent::DocumentsRetrieved`] event.
+///
+/// # Example
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use llm_workflow::{ExecutionContext, Step};
+/// use llm_workflow::retrieval::{Document, HashEmbedding, RetrieveStep, VectorIndex};
+///
+/// # tokio_test::block_on(async {
+/// let ctx = ExecutionContext::new();
+/// let index = Arc::new(VectorIndex::default());
+/// index.add(&ctx, &HashEmbedding::new(128), vec![
+///     Document::new("rust", "Rust has zero-cost abstractions"),
+///     Document::new("go", "Go has goroutines"),
+/// ]).await.unwrap();
+///
+/// let retrieve = RetrieveStep::new(HashEmbedding::new(128), index, 1);
+/// let hits = retrieve.run(&ctx, "abstractions in Rust".to_string()).await.unwrap();
+/// assert_eq!(hits[0].document.id, "rust");
+/// # });
+/// ```
+pub struct RetrieveStep<M> {
+    model: M,
+    index: Arc<VectorIndex>,
+    k: usize,
+    filter: Option<Filter>,
+    min_score: Option<f32>,
+    name: String,
+}
+
+impl<M: EmbeddingModel> RetrieveStep<M> {
+    /// Retrieve the top `k` documents from `index`, embedding queries with `model`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is zero.
+    pub fn new(model: M, index: Arc<VectorIndex>, k: usize) -> Self {
+        assert!(k > 0, "k must be greater than zero");
+        Self {
+            model,
+            index,
+            k,
+            filter: None,
+            min_score: None,
+            name: "retrieve".to_string(),
+        }
+    }
+
+    /// Only return documents whose metadata matches `filter`.
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Drop documents scoring below `score`.
+    pub fn with_min_score(mut self, score: f32) -> Self {
+        self.min_score = Some(score);
+        self
+    }
+
+    /// Set a human-readable name, used in emitted events.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// The index searched by this step.
+    pub fn index(&self) -> &Arc<VectorIndex> {
+        &self.index
+    }
+}
+
+#[async_trait]
+impl<M: EmbeddingModel> Step for RetrieveStep<M> {
+    type Input = String;
+    type Output = Vec<ScoredDocument>;
+
+    async fn run(&self, ctx: &ExecutionContext, input: String) -> Result<Vec<ScoredDocument>> {
+        let query = self
+            .model
+            .run(ctx, vec![input])
+            .await?
+            .pop()
+            .ok_or_else(|| Error::Validation("model returned no embedding for the query".to_string()))?;
+        let mut hits = self.index.search(&query, self.k, self.filter.as_ref())?;
+        if let Some(min) = self.min_score {
+            hits.retain(|hit| hit.score >= min);
+        }
+        ctx.emit(WorkflowEvent::DocumentsRetrieved {
+            step_name: self.name.clone(),
+            document_ids: hits.iter().map(|hit| hit.document.id.clone()).collect(),
+            scores: hits.iter().map(|hit| hit.score).collect(),
+        });
+        Ok(hits)
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn index() -> VectorIndex {
+        let index = VectorIndex::new(Similarity::Dot);
+        index.insert(Document::new("x", "x").with_metadata("lang", "en"), vec![1.0, 0.0]).unwrap();
+        index.insert(Document::new("y", "y").with_metadata("lang", "de"), vec![0.0, 2.0]).unwrap();
+        index.insert(Document::new("xy", "xy").with_metadata("year", 2021), vec![1.0, 1.0]).unwrap();
+        index
+    }
+
+    fn ids(hits: &[ScoredDocument]) -> Vec<&str> {
+        hits.iter().map(|hit| hit.document.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_dot_and_cosine_rank_differently() {
+        let index = index();
+        assert_eq!(ids(&index.search(&[1.0, 1.0], 3, None).unwrap()), ["y", "xy", "x"]);
+
+        let cosine = VectorIndex::new(Similarity::Cosine);
+        cosine.insert(Document::new("y", "y"), vec![0.0, 2.0]).unwrap();
+        cosine.insert(Document::new("xy", "xy"), vec![1.0, 1.0]).unwrap();
+        let hits = cosine.search(&[1.0, 1.0], 1, None).unwrap();
+        assert_eq!(ids(&hits), ["xy"]);
+        assert!((hits[0].score - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_filters() {
+        let index = index();
+        let search = |filter: Filter| ids(&index.search(&[1.0, 0.0], 3, Some(&filter)).unwrap()).join(",");
+        assert_eq!(search(Filter::eq("lang", "de")), "y");
+        assert_eq!(search(Filter::one_of("lang", ["en", "de"])), "x,y");
+        assert_eq!(search(Filter::exists("year")), "xy");
+        assert_eq!(search(Filter::range("year", None, Some(2020.0))), "");
+        assert_eq!(search(Filter::Not(Box::new(Filter::exists("lang")))), "xy");
+        assert_eq!(search(Filter::eq("lang", "en").or(Filter::eq("year", json!(2021)))), "x,xy");
+        assert_eq!(search(Filter::exists("lang").and(Filter::eq("lang", "fr"))), "");
+    }
+
+    #[test]
+    fn test_insert_replaces_and_checks_dimensions() {
+        let index = index();
+        index.insert(Document::new("x", "new x"), vec![0.0, 5.0]).unwrap();
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.get("x").unwrap().text, "new x");
+        assert!(index.remove("x"));
+        assert!(!index.remove("x"));
+
+        let err = index.insert(Document::new("z", "z"), vec![1.0]).unwrap_err();
+        assert!(matches!(err, Error::Validation(msg) if msg == "expected a 2-dimensional vector, got 1"));
+        assert!(index.search(&[1.0, 2.0, 3.0], 1, None).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_add_checks_the_whole_batch_before_storing() {
+        let model = crate::LambdaStep::new(|texts: Vec<String>| async move {
+            Ok::<Vec<Embedding>, Error>(texts.iter().map(|text| vec![1.0; text.len()]).collect())
+        });
+        let index = VectorIndex::default();
+        let documents = vec![Document::new("a", "ab"), Document::new("b", "abc")];
+        let err = index.add(&ExecutionContext::new(), &model, documents).await.unwrap_err();
+        assert!(matches!(err, Error::Validation(msg) if msg == "expected a 2-dimensional vector, got 3"));
+        assert_eq!(index.len(), 0);
+
+        // The failed batch did not fix the index's dimensions either.
+        index.insert(Document::new("c", "c"), vec![1.0, 0.0, 0.0]).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_retrieve_step_applies_min_score_and_emits() {
+        let model = crate::LambdaStep::new(|texts: Vec<String>| async move {
+            Ok::<Vec<Embedding>, Error>(texts.iter().map(|_| vec![1.0, 0.0]).collect())
+        });
+        let retrieve = RetrieveStep::new(model, Arc::new(index()), 3).with_min_score(0.5);
+        let ctx = ExecutionContext::new();
+        let hits = retrieve.run(&ctx, "q".to_string()).await.unwrap();
+        assert_eq!(ids(&hits), ["x", "xy"]);
+
+        match &ctx.trace_snapshot()[0].event {
+            WorkflowEvent::DocumentsRetrieved { step_name, document_ids, scores } => {
+                assert_eq!(step_name, "retrieve");
+                assert_eq!(document_ids, &["x", "xy"]);
+                assert_eq!(scores, &[1.0, 1.0]);
+            }
+            other => panic!("unexpected event {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,13 @@
+//! Embeddings and vector retrieval.
+//!
+//! An [`EmbeddingModel`] turns texts into vectors; [`EmbedStep`] runs one in
+//! batches. Embedded [`Document`]s are stored in a [`VectorIndex`] and
+//! searched by similarity with optional metadata [`Filter`]s, and
+//! [`RetrieveStep`] turns a query into ranked documents. [`HashEmbedding`] is
+//! a deterministic, vocabulary-free model for tests and offline runs.
+
+pub mod embedding;
+pub mod index;
+
+pub use embedding::{EmbedStep, Embedding, EmbeddingModel, HashEmbedding};
+pub use index::{Document, Filter, RetrieveStep, ScoredDocument, Similarity, VectorIndex};
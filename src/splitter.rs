@@ -0,0 +1,768 @@
+//! Splitting documents into chunks.
+//!
+//! A [`TextSplitter`] cuts text into [`Chunk`]s that record their byte offsets
+//! in the source, so every chunk's text is exactly `&source[start..end]`.
+//! [`SplitStep`] runs a splitter over a [`Document`], tagging chunks with the
+//! document id and metadata; its `Vec<Chunk>` output feeds directly into
+//! [`ParallelMapStep`](crate::ParallelMapStep), [`BatchStep`](crate::BatchStep)
+//! or, via [`Chunk::into_document`], a [`VectorIndex`](crate::VectorIndex).
+//!
+//! Splitters measure size in characters unless noted; [`TokenSplitter`] and
+//! [`RecursiveSplitter::with_tokenizer`] measure with a [`Tokenizer`].
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, VecDeque};
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::retrieval::Document;
+use crate::tokenizer::Tokenizer;
+use crate::{ExecutionContext, Result, step::Step};
+
+/// A piece of a source text.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Chunk {
+    /// Id of the source document; empty when split directly with a [`TextSplitter`].
+    #[serde(default)]
+    pub source: String,
+    /// Position of the chunk within its source, from zero.
+    pub index: usize,
+    /// The chunk text.
+    pub text: String,
+    /// Byte offset of the chunk's start in the source.
+    pub start: usize,
+    /// Byte offset just past the chunk's end in the source.
+    pub end: usize,
+    /// Metadata from the source document and the splitter, e.g. markdown headings.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, Value>,
+}
+
+impl Chunk {
+    /// Convert into a [`Document`] with id `<source>#<index>`, keeping the
+    /// metadata and adding `source`, `start` and `end` entries.
+    pub fn into_document(self) -> Document {
+        let mut document = Document::new(format!("{}#{}", self.source, self.index), self.text);
+        document.metadata = self.metadata;
+        document
+            .with_metadata("source", self.source)
+            .with_metadata("start", self.start)
+            .with_metadata("end", self.end)
+    }
+}
+
+/// Cuts text into chunks.
+pub trait TextSplitter: Send + Sync {
+    /// Split `text`, returning chunks in source order.
+    fn split(&self, text: &str) -> Vec<Chunk>;
+}
+
+fn chunks(text: &str, spans: Vec<(Range<usize>, BTreeMap<String, Value>)>) -> Vec<Chunk> {
+    spans
+        .into_iter()
+        .enumerate()
+        .map(|(index, (range, metadata))| Chunk {
+            source: String::new(),
+            index,
+            text: text[range.clone()].to_string(),
+            start: range.start,
+            end: range.end,
+            metadata,
+        })
+        .collect()
+}
+
+fn plain(ranges: Vec<Range<usize>>) -> Vec<(Range<usize>, BTreeMap<String, Value>)> {
+    ranges.into_iter().map(|range| (range, BTreeMap::new())).collect()
+}
+
+/// Narrow `range` to exclude surrounding whitespace, or `None` if nothing remains.
+fn trim(text: &str, range: Range<usize>) -> Option<Range<usize>> {
+    let slice = &text[range.clone()];
+    let start = range.start + (slice.len() - slice.trim_start().len());
+    let end = range.end - (slice.len() - slice.trim_end().len());
+    (start < end).then_some(start..end)
+}
+
+/// How chunk sizes are measured.
+#[derive(Clone)]
+enum Measure {
+    Chars,
+    Tokens(Arc<dyn Tokenizer>),
+}
+
+impl Measure {
+    fn len(&self, text: &str) -> usize {
+        match self {
+            Measure::Chars => text.chars().count(),
+            Measure::Tokens(tokenizer) => tokenizer.count(text),
+        }
+    }
+
+    /// Contiguous windows over `range` that each measure at most `size`; a
+    /// single character that measures more than `size` gets its own window.
+    fn windows(&self, text: &str, range: Range<usize>, size: usize) -> Vec<Range<usize>> {
+        if let Measure::Chars = self {
+            return char_windows(text, range, size, 0);
+        }
+        let mut windows = Vec::new();
+        let mut start = range.start;
+        let mut end = start;
+        for (offset, c) in text[range.clone()].char_indices() {
+            let next = range.start + offset + c.len_utf8();
+            if end > start && self.len(&text[start..next]) > size {
+                windows.push(start..end);
+                start = end;
+            }
+            end = next;
+        }
+        if start < end {
+            windows.push(start..end);
+        }
+        windows
+    }
+}
+
+/// Windows of `size` characters over `range`, each starting `size - overlap` after the last.
+fn char_windows(text: &str, range: Range<usize>, size: usize, overlap: usize) -> Vec<Range<usize>> {
+    let bounds: Vec<usize> = text[range.clone()]
+        .char_indices()
+        .map(|(i, _)| range.start + i)
+        .chain(std::iter::once(range.end))
+        .collect();
+    let chars = bounds.len() - 1;
+    let mut windows = Vec::new();
+    let mut start = 0;
+    while start < chars {
+        let end = (start + size).min(chars);
+        windows.push(bounds[start]..bounds[end]);
+        if end == chars {
+            break;
+        }
+        start += size - overlap;
+    }
+    windows
+}
+
+/// Greedily join contiguous pieces into chunks of at most `size`, starting
+/// each new chunk with trailing pieces of the last that fit in `overlap`.
+/// Sizes are measured without surrounding whitespace.
+fn merge(text: &str, pieces: Vec<Range<usize>>, size: usize, overlap: usize, measure: &Measure) -> Vec<Range<usize>> {
+    let span = |window: &VecDeque<Range<usize>>, end: usize| window.front().map_or(end..end, |first| first.start..end);
+    let len = |range: Range<usize>| measure.len(text[range].trim());
+    let mut merged = Vec::new();
+    let mut window: VecDeque<Range<usize>> = VecDeque::new();
+    for piece in pieces {
+        if !window.is_empty() && len(span(&window, piece.end)) > size {
+            let last = window.back().map_or(0, |last| last.end);
+            merged.push(span(&window, last));
+            while !window.is_empty()
+                && (len(span(&window, last)) > overlap || len(span(&window, piece.end)) > size)
+            {
+                window.pop_front();
+            }
+        }
+        window.push_back(piece);
+    }
+    if let Some(last) = window.back() {
+        merged.push(span(&window, last.end));
+    }
+    merged
+}
+
+/// Fixed-size character windows with overlap.
+///
+/// # Example
+///
+/// ```rust
+/// use llm_workflow::splitter::{FixedSizeSplitter, TextSplitter};
+///
+/// let chunks = FixedSizeSplitter::new(4, 1).split("abcdefghij");
+/// let texts: Vec<_> = chunks.iter().map(|c| c.text.as_str()).collect();
+/// assert_eq!(texts, ["abcd", "defg", "ghij"]);
+/// assert_eq!((chunks[1].start, chunks[1].end), (3, 7));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedSizeSplitter {
+    size: usize,
+    overlap: usize,
+}
+
+impl FixedSizeSplitter {
+    /// Chunks of `size` characters, consecutive chunks sharing `overlap` characters.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero or `overlap` is not less than `size`.
+    pub fn new(size: usize, overlap: usize) -> Self {
+        assert!(size > 0, "size must be greater than zero");
+        assert!(overlap < size, "overlap must be less than size");
+        Self { size, overlap }
+    }
+}
+
+impl TextSplitter for FixedSizeSplitter {
+    fn split(&self, text: &str) -> Vec<Chunk> {
+        chunks(text, plain(char_windows(text, 0..text.len(), self.size, self.overlap)))
+    }
+}
+
+/// Splits on the coarsest separator that yields small enough pieces, then
+/// packs the pieces into chunks.
+///
+/// Separators are tried in order — by default paragraphs, lines, sentences,
+/// words, and finally individual characters. Pieces keep their trailing
+/// separator, and chunks are trimmed of surrounding whitespace.
+///
+/// # Example
+///
+/// ```rust
+/// use llm_workflow::splitter::{RecursiveSplitter, TextSplitter};
+///
+/// let text = "First paragraph here.\n\nSecond one, which is rather longer.";
+/// let chunks = RecursiveSplitter::new(40, 0).split(text);
+/// assert_eq!(chunks.len(), 2);
+/// assert_eq!(chunks[1].text, "Second one, which is rather longer.");
+/// assert_eq!(&text[chunks[1].start..chunks[1].end], chunks[1].text);
+/// ```
+#[derive(Clone)]
+pub struct RecursiveSplitter {
+    size: usize,
+    overlap: usize,
+    separators: Vec<String>,
+    measure: Measure,
+}
+
+impl RecursiveSplitter {
+    /// Chunks of at most `size` characters, overlapping by up to `overlap`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero or `overlap` is not less than `size`.
+    pub fn new(size: usize, overlap: usize) -> Self {
+        assert!(size > 0, "size must be greater than zero");
+        assert!(overlap < size, "overlap must be less than size");
+        Self {
+            size,
+            overlap,
+            separators: ["\n\n", "\n", ". ", " ", ""].map(String::from).to_vec(),
+            measure: Measure::Chars,
+        }
+    }
+
+    /// Use these separators, coarsest first. An empty separator splits
+    /// between characters; without one, oversized pieces are cut into
+    /// the longest character runs that fit.
+    pub fn with_separators<I: IntoIterator<Item = S>, S: Into<String>>(mut self, separators: I) -> Self {
+        self.separators = separators.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Measure `size` and `overlap` in tokens counted by `tokenizer`.
+    pub fn with_tokenizer(mut self, tokenizer: impl Tokenizer + 'static) -> Self {
+        self.measure = Measure::Tokens(Arc::new(tokenizer));
+        self
+    }
+
+    fn fits(&self, text: &str, range: &Range<usize>) -> bool {
+        self.measure.len(&text[range.clone()]) <= self.size
+    }
+
+    /// Cut `range` into contiguous pieces that each fit.
+    fn pieces(&self, text: &str, range: Range<usize>, separators: &[String]) -> Vec<Range<usize>> {
+        if self.fits(text, &range) {
+            return vec![range];
+        }
+        let slice = &text[range.clone()];
+        let Some(position) = separators.iter().position(|sep| sep.is_empty() || slice.contains(sep.as_str())) else {
+            return self.measure.windows(text, range, self.size);
+        };
+        let separator = &separators[position];
+        if separator.is_empty() {
+            return self.measure.windows(text, range, self.size);
+        }
+
+        let mut pieces = Vec::new();
+        let mut start = range.start;
+        for (offset, _) in slice.match_indices(separator.as_str()) {
+            let end = range.start + offset + separator.len();
+            pieces.push(start..end);
+            start = end;
+        }
+        if start < range.end {
+            pieces.push(start..range.end);
+        }
+        pieces
+            .into_iter()
+            .flat_map(|piece| match self.fits(text, &piece) {
+                true => vec![piece],
+                false => self.pieces(text, piece, &separators[position + 1..]),
+            })
+            .collect()
+    }
+
+    fn split_range(&self, text: &str, range: Range<usize>) -> Vec<Range<usize>> {
+        let pieces = self.pieces(text, range, &self.separators);
+        merge(text, pieces, self.size, self.overlap, &self.measure)
+            .into_iter()
+            .filter_map(|range| trim(text, range))
+            .collect()
+    }
+}
+
+impl TextSplitter for RecursiveSplitter {
+    fn split(&self, text: &str) -> Vec<Chunk> {
+        chunks(text, plain(self.split_range(text, 0..text.len())))
+    }
+}
+
+/// Chunks of whole words within a token budget.
+///
+/// Packs words into chunks of at most `max_tokens` as counted by the
+/// tokenizer, overlapping by up to `overlap_tokens`; a single word over the
+/// budget is cut into character windows.
+///
+/// # Example
+///
+/// ```rust
+/// use llm_workflow::splitter::{TextSplitter, TokenSplitter};
+/// use llm_workflow::tokenizer::HeuristicTokenizer;
+///
+/// let splitter = TokenSplitter::new(HeuristicTokenizer::words(1.0), 3, 1);
+/// let texts: Vec<_> = splitter.split("one two three four five").into_iter().map(|c| c.text).collect();
+/// assert_eq!(texts, ["one two three", "three four five"]);
+/// ```
+#[derive(Clone)]
+pub struct TokenSplitter {
+    inner: RecursiveSplitter,
+}
+
+impl TokenSplitter {
+    /// Chunks of at most `max_tokens`, overlapping by up to `overlap_tokens`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_tokens` is zero or `overlap_tokens` is not less than `max_tokens`.
+    pub fn new(tokenizer: impl Tokenizer + 'static, max_tokens: usize, overlap_tokens: usize) -> Self {
+        Self {
+            inner: RecursiveSplitter::new(max_tokens, overlap_tokens)
+                .with_separators([" ", ""])
+                .with_tokenizer(tokenizer),
+        }
+    }
+}
+
+impl TextSplitter for TokenSplitter {
+    fn split(&self, text: &str) -> Vec<Chunk> {
+        self.inner.split(text)
+    }
+}
+
+/// Byte ranges of the paragraphs in `range`, separated by blank lines.
+fn paragraphs(text: &str, range: Range<usize>) -> Vec<Range<usize>> {
+    let slice = &text[range.clone()];
+    let mut units = Vec::new();
+    let mut start = 0;
+    let mut offset = 0;
+    for line in slice.split_inclusive('\n') {
+        if line.trim().is_empty() {
+            units.extend(trim(text, range.start + start..range.start + offset));
+            start = offset + line.len();
+        }
+        offset += line.len();
+    }
+    units.extend(trim(text, range.start + start..range.end));
+    units
+}
+
+/// Byte ranges of the sentences in `range`, ending after `.`, `!` or `?`
+/// followed by whitespace, or at a paragraph break.
+fn sentences(text: &str, range: Range<usize>) -> Vec<Range<usize>> {
+    let mut units = Vec::new();
+    for paragraph in paragraphs(text, range) {
+        let slice = &text[paragraph.clone()];
+        let mut start = 0;
+        let mut chars = slice.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            let at_boundary = chars.peek().is_some_and(|(_, next)| next.is_whitespace());
+            if matches!(c, '.' | '!' | '?') && at_boundary {
+                let end = i + c.len_utf8();
+                units.extend(trim(text, paragraph.start + start..paragraph.start + end));
+                start = end;
+            }
+        }
+        units.extend(trim(text, paragraph.start + start..paragraph.end));
+    }
+    units
+}
+
+/// Whether sentence-aware splitting packs sentences or paragraphs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unit {
+    Sentence,
+    Paragraph,
+}
+
+/// Packs whole sentences or paragraphs into chunks.
+///
+/// Chunks never break inside a unit unless the unit alone exceeds the size; an
+/// oversized paragraph is split into sentences, and an oversized sentence into
+/// words. Chunk offsets span from the first unit's start to the last unit's end.
+///
+/// # Example
+///
+/// ```rust
+/// use llm_workflow::splitter::{SentenceSplitter, TextSplitter};
+///
+/// let text = "It rained. The river rose! Was the bridge safe?";
+/// let texts: Vec<_> = SentenceSplitter::sentences(30).split(text).into_iter().map(|c| c.text).collect();
+/// assert_eq!(texts, ["It rained. The river rose!", "Was the bridge safe?"]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SentenceSplitter {
+    size: usize,
+    unit: Unit,
+}
+
+impl SentenceSplitter {
+    /// Pack sentences into chunks of at most `size` characters.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    pub fn sentences(size: usize) -> Self {
+        assert!(size > 0, "size must be greater than zero");
+        Self {
+            size,
+            unit: Unit::Sentence,
+        }
+    }
+
+    /// Pack paragraphs (separated by blank lines) into chunks of at most `size` characters.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    pub fn paragraphs(size: usize) -> Self {
+        Self {
+            unit: Unit::Paragraph,
+            ..Self::sentences(size)
+        }
+    }
+
+    fn split_range(&self, text: &str, range: Range<usize>) -> Vec<Range<usize>> {
+        let fits = |unit: &Range<usize>| text[unit.clone()].chars().count() <= self.size;
+        let units = match self.unit {
+            Unit::Paragraph => paragraphs(text, range)
+                .into_iter()
+                .flat_map(|p| match fits(&p) {
+                    true => vec![p],
+                    false => sentences(text, p),
+                })
+                .collect(),
+            Unit::Sentence => sentences(text, range),
+        };
+        let words = RecursiveSplitter::new(self.size, 0).with_separators([" ", ""]);
+        let units = units
+            .into_iter()
+            .flat_map(|unit| match fits(&unit) {
+                true => vec![unit],
+                false => words.split_range(text, unit),
+            })
+            .collect();
+        merge(text, units, self.size, 0, &Measure::Chars)
+    }
+}
+
+impl TextSplitter for SentenceSplitter {
+    fn split(&self, text: &str) -> Vec<Chunk> {
+        chunks(text, plain(self.split_range(text, 0..text.len())))
+    }
+}
+
+/// Splits markdown at headings, recording the heading path of each chunk.
+///
+/// Each section runs from an ATX heading (`#` to `######`) to the next; text
+/// before the first heading forms its own section, and `#` lines inside
+/// fenced code blocks are ignored. A chunk's `headings` metadata lists the
+/// titles of its section and the enclosing sections. Sections longer than
+/// `size` characters are split further with a [`RecursiveSplitter`].
+///
+/// # Example
+///
+/// ```rust
+/// use serde_json::json;
+/// use llm_workflow::splitter::{MarkdownSplitter, TextSplitter};
+///
+/// let text = "# Guide\nIntro.\n## Install\nRun it.\n## Usage\nCall it.\n";
+/// let chunks = MarkdownSplitter::new(100).split(text);
+/// assert_eq!(chunks.len(), 3);
+/// assert_eq!(chunks[1].text, "## Install\nRun it.");
+/// assert_eq!(chunks[1].metadata["headings"], json!(["Guide", "Install"]));
+/// ```
+#[derive(Clone)]
+pub struct MarkdownSplitter {
+    size: usize,
+    sections: RecursiveSplitter,
+}
+
+impl MarkdownSplitter {
+    /// Split at headings, keeping chunks within `size` characters.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    pub fn new(size: usize) -> Self {
+        Self {
+            size,
+            sections: RecursiveSplitter::new(size, 0),
+        }
+    }
+
+    /// Split oversized sections with `splitter` instead of the default
+    /// [`RecursiveSplitter`] without overlap.
+    pub fn with_section_splitter(mut self, splitter: RecursiveSplitter) -> Self {
+        self.sections = splitter;
+        self
+    }
+}
+
+/// The level and title of an ATX heading line.
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    let rest = &line[indent..];
+    let level = rest.len() - rest.trim_start_matches('#').len();
+    let after = &rest[level..];
+    let valid = indent <= 3 && (1..=6).contains(&level) && (after.is_empty() || after.starts_with([' ', '\t']));
+    valid.then(|| (level, after.trim().trim_end_matches('#').trim_end()))
+}
+
+impl TextSplitter for MarkdownSplitter {
+    fn split(&self, text: &str) -> Vec<Chunk> {
+        let mut sections: Vec<(Range<usize>, Vec<String>)> = Vec::new();
+        let mut path: Vec<(usize, String)> = Vec::new();
+        let mut start = 0;
+        let mut offset = 0;
+        let mut fence: Option<&str> = None;
+        for line in text.split_inclusive('\n') {
+            let trimmed = line.trim_start();
+            match fence {
+                Some(marker) if trimmed.starts_with(marker) => fence = None,
+                Some(_) => {}
+                None if trimmed.starts_with("```") || trimmed.starts_with("~~~") => fence = Some(&trimmed[..3]),
+                None => {
+                    if let Some((level, title)) = heading(line.trim_end_matches(['\r', '\n'])) {
+                        let titles = path.iter().map(|(_, title)| title.clone()).collect();
+                        sections.push((start..offset, titles));
+                        path.retain(|(l, _)| *l < level);
+                        path.push((level, title.to_string()));
+                        start = offset;
+                    }
+                }
+            }
+            offset += line.len();
+        }
+        sections.push((start..text.len(), path.into_iter().map(|(_, title)| title).collect()));
+
+        let spans = sections
+            .into_iter()
+            .flat_map(|(range, headings)| {
+                let ranges = match text[range.clone()].chars().count() <= self.size {
+                    true => trim(text, range).into_iter().collect(),
+                    false => self.sections.split_range(text, range),
+                };
+                let metadata = BTreeMap::from([("headings".to_string(), Value::from(headings))]);
+                ranges.into_iter().map(move |range| (range, metadata.clone()))
+            })
+            .collect();
+        chunks(text, spans)
+    }
+}
+
+/// Splits a [`Document`] into [`Chunk`]s with a [`TextSplitter`].
+///
+/// Chunks carry the document id as `source` and a copy of the document's
+/// metadata, with the splitter's own metadata (such as markdown headings)
+/// taking precedence.
+///
+/// # Example
+///
+/// ```rust
+/// use llm_workflow::{ExecutionContext, Step};
+/// use llm_workflow::retrieval::Document;
+/// use llm_workflow::splitter::{RecursiveSplitter, SplitStep};
+///
+/// # tokio_test::block_on(async {
+/// let split = SplitStep::new(RecursiveSplitter::new(20, 5));
+/// let doc = Document::new("notes", "Short lines.\nAnother short line.\nAnd a third.").with_metadata("lang", "en");
+/// let chunks = split.run(&ExecutionContext::new(), doc).await.unwrap();
+/// assert_eq!(chunks.len(), 3);
+/// assert_eq!(chunks[2].source, "notes");
+/// assert_eq!(chunks[2].metadata["lang"], "en");
+/// assert_eq!(chunks[2].clone().into_document().id, "notes#2");
+/// # });
+/// ```
+pub struct SplitStep<T> {
+    splitter: T,
+    name: String,
+}
+
+impl<T: TextSplitter> SplitStep<T> {
+    /// Split documents with `splitter`.
+    pub fn new(splitter: T) -> Self {
+        Self {
+            splitter,
+            name: "split".to_string(),
+        }
+    }
+
+    /// Set a human-readable name, used in emitted events.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+}
+
+#[async_trait]
+impl<T: TextSplitter> Step for SplitStep<T> {
+    type Input = Document;
+    type Output = Vec<Chunk>;
+
+    async fn run(&self, _ctx: &ExecutionContext, input: Document) -> Result<Vec<Chunk>> {
+        Ok(self
+            .splitter
+            .split(&input.text)
+            .into_iter()
+            .map(|mut chunk| {
+                let mut metadata = input.metadata.clone();
+                metadata.append(&mut chunk.metadata);
+                Chunk {
+                    source: input.id.clone(),
+                    metadata,
+                    ..chunk
+                }
+            })
+            .collect())
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenizer::FnTokenizer;
+    use serde_json::json;
+
+    fn texts(chunks: &[Chunk]) -> Vec<&str> {
+        chunks.iter().map(|chunk| chunk.text.as_str()).collect()
+    }
+
+    fn assert_offsets(text: &str, chunks: &[Chunk]) {
+        for (index, chunk) in chunks.iter().enumerate() {
+            assert_eq!(chunk.index, index);
+            assert_eq!(&text[chunk.start..chunk.end], chunk.text);
+        }
+    }
+
+    #[test]
+    fn test_fixed_size_respects_char_boundaries() {
+        let text = "héllo wörld";
+        let chunks = FixedSizeSplitter::new(5, 2).split(text);
+        assert_eq!(texts(&chunks), ["héllo", "lo wö", "wörld"]);
+        assert_offsets(text, &chunks);
+        assert!(FixedSizeSplitter::new(3, 0).split("").is_empty());
+    }
+
+    #[test]
+    fn test_recursive_prefers_coarse_separators_and_overlaps() {
+        let text = "alpha beta gamma delta epsilon zeta";
+        let chunks = RecursiveSplitter::new(16, 7).split(text);
+        assert_eq!(texts(&chunks), ["alpha beta gamma", "gamma delta", "delta epsilon", "epsilon zeta"]);
+        assert_offsets(text, &chunks);
+
+        let text = "one\ntwo\n\nthree four five six";
+        let chunks = RecursiveSplitter::new(12, 0).split(text);
+        assert_eq!(texts(&chunks), ["one\ntwo", "three four", "five six"]);
+        assert_offsets(text, &chunks);
+    }
+
+    #[test]
+    fn test_recursive_falls_back_to_characters() {
+        let chunks = RecursiveSplitter::new(4, 0).split("abcdefghij");
+        assert_eq!(texts(&chunks), ["abcd", "efgh", "ij"]);
+    }
+
+    #[test]
+    fn test_recursive_character_fallback_counts_tokens() {
+        let text = "自然言語処理は人工知能の一分野です";
+        let two_per_char = FnTokenizer::new(|t: &str| 2 * t.chars().count());
+        let chunks = RecursiveSplitter::new(10, 0).with_tokenizer(two_per_char).split(text);
+        assert_eq!(texts(&chunks), ["自然言語処", "理は人工知", "能の一分野", "です"]);
+        assert_offsets(text, &chunks);
+    }
+
+    #[test]
+    fn test_token_splitter_counts_tokens() {
+        let text = "aa bb cc dd ee";
+        let splitter = TokenSplitter::new(FnTokenizer::new(|t: &str| t.split_whitespace().count()), 2, 0);
+        let chunks = splitter.split(text);
+        assert_eq!(texts(&chunks), ["aa bb", "cc dd", "ee"]);
+        assert_offsets(text, &chunks);
+    }
+
+    #[test]
+    fn test_sentence_and_paragraph_splitting() {
+        let text = "First point. Second point.\n\nNew paragraph here. It goes on for quite a while.";
+        let chunks = SentenceSplitter::sentences(30).split(text);
+        assert_eq!(
+            texts(&chunks),
+            ["First point. Second point.", "New paragraph here.", "It goes on for quite a while."]
+        );
+        assert_offsets(text, &chunks);
+
+        let chunks = SentenceSplitter::paragraphs(60).split(text);
+        assert_eq!(texts(&chunks), ["First point. Second point.", "New paragraph here. It goes on for quite a while."]);
+        assert_offsets(text, &chunks);
+
+        // An oversized sentence falls back to words.
+        let chunks = SentenceSplitter::sentences(10).split("A very long sentence indeed.");
+        assert_eq!(texts(&chunks), ["A very", "long", "sentence", "indeed."]);
+    }
+
+    #[test]
+    fn test_markdown_headings_and_fences() {
+        let text = "Preamble.\n# Title\n```sh\n# not a heading\n```\n## Part A\nAlpha.\n# Other\nOmega.\n";
+        let chunks = MarkdownSplitter::new(200).split(text);
+        assert_eq!(
+            texts(&chunks),
+            ["Preamble.", "# Title\n```sh\n# not a heading\n```", "## Part A\nAlpha.", "# Other\nOmega."]
+        );
+        let headings: Vec<_> = chunks.iter().map(|c| c.metadata["headings"].clone()).collect();
+        assert_eq!(headings, [json!([]), json!(["Title"]), json!(["Title", "Part A"]), json!(["Other"])]);
+        assert_offsets(text, &chunks);
+    }
+
+    #[test]
+    fn test_markdown_splits_long_sections() {
+        let text = "# Notes\nfirst line\nsecond line\nthird line\n";
+        let chunks = MarkdownSplitter::new(20).split(text);
+        assert_eq!(texts(&chunks), ["# Notes\nfirst line", "second line", "third line"]);
+        assert!(chunks.iter().all(|c| c.metadata["headings"] == json!(["Notes"])));
+        assert_offsets(text, &chunks);
+    }
+
+    #[tokio::test]
+    async fn test_split_step_composes_with_parallel_map() {
+        let docs = vec![Document::new("a", "one two"), Document::new("b", "three")];
+        let split = crate::ParallelMapStep::new(SplitStep::new(FixedSizeSplitter::new(3, 0)));
+        let chunks = split.run(&ExecutionContext::new(), docs).await.unwrap();
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].iter().map(|c| c.source.as_str()).collect::<Vec<_>>(), ["a", "a", "a"]);
+        assert_eq!(chunks[1][1].clone().into_document().metadata["start"], 3);
+    }
+}
@@ -1,9 +1,12 @@
 //! Batch processing steps.
 
 use async_trait::async_trait;
+use std::marker::PhantomData;
 
-use crate::{ExecutionContext, Result};
+use crate::{Error, ExecutionContext, Result, WorkflowEvent};
 use super::Step;
+use super::join::JoinMode;
+use super::parallel::run_ordered;
 
 /// A step that adapts a single-item step into a batch step processing `Vec<Input>`.
 ///
@@ -39,13 +42,190 @@ where
     }
 }
 
-/// A step that processes a `Vec<I>` in fixed-size batches using an inner batch step.
+/// Decides how a `Vec<I>` is divided into batches for a [`BatchStep`].
 ///
-/// The inner step must accept `Vec<I>` and return `Vec<O>`. Large inputs are split
-/// into chunks of `batch_size` and each chunk is processed in sequence.
-pub struct BatchStep<S> {
+/// Implemented by [`FixedSize`] (batch by item count) and [`TokenBudget`]
+/// (batch by summed item size).
+pub trait BatchPolicy<I>: Send + Sync {
+    /// Split the items into batches, preserving order.
+    fn plan(&self, items: Vec<I>) -> Result<Vec<Vec<I>>>;
+
+    /// The size this policy assigns to an item, if it measures items.
+    ///
+    /// Used to record per-batch totals in [`WorkflowEvent::BatchPlanned`].
+    fn measure(&self, _item: &I) -> Option<usize> {
+        None
+    }
+}
+
+/// Batches of at most a fixed number of items.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedSize(usize);
+
+impl FixedSize {
+    /// Batches of at most `batch_size` items.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `batch_size` is zero.
+    pub fn new(batch_size: usize) -> Self {
+        assert!(batch_size > 0, "batch_size must be greater than zero");
+        Self(batch_size)
+    }
+}
+
+impl<I: Send> BatchPolicy<I> for FixedSize {
+    fn plan(&self, items: Vec<I>) -> Result<Vec<Vec<I>>> {
+        let mut batches = Vec::with_capacity(items.len().div_ceil(self.0));
+        let mut remaining = items.into_iter();
+        loop {
+            let batch: Vec<I> = remaining.by_ref().take(self.0).collect();
+            if batch.is_empty() {
+                return Ok(batches);
+            }
+            batches.push(batch);
+        }
+    }
+}
+
+/// How a [`TokenBudget`] handles a single item larger than the budget.
+///
+/// Items are never split, so each input still maps to exactly one output;
+/// chunk oversized documents beforehand with a
+/// [`SplitStep`](crate::SplitStep) instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Oversize {
+    /// Fail the run with an [`Error::Validation`].
+    Error,
+    /// Send the item alone in its own batch.
+    Isolate,
+}
+
+/// Batches whose summed item sizes stay within a budget.
+///
+/// Item sizes come from a pluggable function, typically a tokenizer estimate.
+/// Items are packed greedily in order; a new batch starts when adding the next
+/// item would exceed `max_tokens`.
+///
+/// # Example
+///
+/// ```rust
+/// use llm_workflow::{LambdaStep, Step, ExecutionContext};
+/// use llm_workflow::step::batch::{BatchStep, TokenBudget};
+///
+/// # tokio_test::block_on(async {
+/// let embed = LambdaStep::new(|texts: Vec<String>| async move {
+///     Ok::<Vec<usize>, llm_workflow::Error>(texts.iter().map(|t| t.len()).collect())
+/// });
+/// let budget = TokenBudget::new(8, |text: &String| text.len());
+/// let batched = BatchStep::with_policy(embed, budget);
+///
+/// let texts = vec!["four", "four", "eight!!!"].into_iter().map(String::from).collect();
+/// let out = batched.run(&ExecutionContext::new(), texts).await.unwrap();
+/// assert_eq!(out, vec![4, 4, 8]);
+/// # });
+/// ```
+pub struct TokenBudget<I, F> {
+    max_tokens: usize,
+    size: F,
+    oversize: Oversize,
+    _item: PhantomData<fn(&I)>,
+}
+
+impl<I, F> TokenBudget<I, F>
+where
+    F: Fn(&I) -> usize + Send + Sync,
+{
+    /// Batches of at most `max_tokens` as measured by `size`.
+    ///
+    /// Oversized items fail the run by default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_tokens` is zero.
+    pub fn new(max_tokens: usize, size: F) -> Self {
+        assert!(max_tokens > 0, "max_tokens must be greater than zero");
+        Self {
+            max_tokens,
+            size,
+            oversize: Oversize::Error,
+            _item: PhantomData,
+        }
+    }
+
+    /// Send oversized items alone in their own batch.
+    pub fn isolate_oversize(mut self) -> Self {
+        self.oversize = Oversize::Isolate;
+        self
+    }
+}
+
+impl<I, F> BatchPolicy<I> for TokenBudget<I, F>
+where
+    I: Send,
+    F: Fn(&I) -> usize + Send + Sync,
+{
+    fn plan(&self, items: Vec<I>) -> Result<Vec<Vec<I>>> {
+        let too_large = |size: usize| {
+            Error::Validation(format!(
+                "item of {size} tokens exceeds batch budget of {}",
+                self.max_tokens
+            ))
+        };
+
+        let mut batches = Vec::new();
+        let mut current = Vec::new();
+        let mut current_tokens = 0;
+        for item in items {
+            let size = (self.size)(&item);
+            if size > self.max_tokens {
+                match self.oversize {
+                    Oversize::Error => return Err(too_large(size)),
+                    Oversize::Isolate => {
+                        if !current.is_empty() {
+                            batches.push(std::mem::take(&mut current));
+                            current_tokens = 0;
+                        }
+                        batches.push(vec![item]);
+                        continue;
+                    }
+                }
+            }
+            if current_tokens + size > self.max_tokens && !current.is_empty() {
+                batches.push(std::mem::take(&mut current));
+                current_tokens = 0;
+            }
+            current_tokens += size;
+            current.push(item);
+        }
+        if !current.is_empty() {
+            batches.push(current);
+        }
+        Ok(batches)
+    }
+
+    fn measure(&self, item: &I) -> Option<usize> {
+        Some((self.size)(item))
+    }
+}
+
+/// A step that processes a `Vec<I>` in batches using an inner batch step.
+///
+/// The inner step must accept `Vec<I>` and return `Vec<O>`. Input is divided
+/// by a [`BatchPolicy`] — fixed-size chunks by default, or a [`TokenBudget`] —
+/// and the planned batch composition is recorded as a
+/// [`WorkflowEvent::BatchPlanned`] event.
+///
+/// Batches run one at a time unless [`BatchStep::with_concurrency`] is set;
+/// outputs are returned in input order either way. The inner step must
+/// return exactly one output per input, otherwise the run fails with an
+/// [`Error::Validation`].
+pub struct BatchStep<S, P = FixedSize> {
     step: S,
-    batch_size: usize,
+    policy: P,
+    name: String,
+    concurrency: usize,
+    mode: JoinMode,
 }
 
 impl<S> BatchStep<S> {
@@ -55,15 +235,51 @@ impl<S> BatchStep<S> {
     ///
     /// Panics if `batch_size` is zero.
     pub fn new(step: S, batch_size: usize) -> Self {
-        assert!(batch_size > 0, "batch_size must be greater than zero");
-        Self { step, batch_size }
+        Self::with_policy(step, FixedSize::new(batch_size))
+    }
+}
+
+impl<S, P> BatchStep<S, P> {
+    /// Create a batch step dividing input with the given policy.
+    pub fn with_policy(step: S, policy: P) -> Self {
+        Self {
+            step,
+            policy,
+            name: "batch".to_string(),
+            concurrency: 1,
+            mode: JoinMode::FailFast,
+        }
+    }
+
+    /// Run up to `limit` batches at once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is zero.
+    pub fn with_concurrency(mut self, limit: usize) -> Self {
+        assert!(limit > 0, "concurrency must be greater than zero");
+        self.concurrency = limit;
+        self
+    }
+
+    /// Set how failures are handled when batches run concurrently.
+    pub fn with_mode(mut self, mode: JoinMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Set a human-readable name, used in emitted events.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
     }
 }
 
 #[async_trait]
-impl<S, I, O> Step for BatchStep<S>
+impl<S, P, I, O> Step for BatchStep<S, P>
 where
     S: Step<Input = Vec<I>, Output = Vec<O>>,
+    P: BatchPolicy<I>,
     I: Send + 'static,
     O: Send + 'static,
 {
@@ -71,24 +287,41 @@ where
     type Output = Vec<O>;
 
     async fn run(&self, ctx: &ExecutionContext, input: Vec<I>) -> Result<Vec<O>> {
-        let mut all_outputs = Vec::new();
-        let mut remaining = input;
+        let batches = self.policy.plan(input)?;
+        ctx.emit(WorkflowEvent::BatchPlanned {
+            step_name: self.name.clone(),
+            batch_sizes: batches.iter().map(Vec::len).collect(),
+            batch_tokens: batches
+                .iter()
+                .map(|batch| batch.iter().map(|item| self.policy.measure(item)).sum())
+                .collect(),
+        });
 
-        while !remaining.is_empty() {
-            let batch_size = self.batch_size.min(remaining.len());
-            let batch: Vec<I> = remaining.drain(..batch_size).collect();
+        let tasks = batches.into_iter().enumerate().map(|(index, batch)| async move {
+            let expected = batch.len();
             let outputs = self.step.run(ctx, batch).await?;
-            all_outputs.extend(outputs);
-        }
+            if outputs.len() != expected {
+                return Err(Error::Validation(format!(
+                    "batch {index} returned {} outputs for {expected} inputs",
+                    outputs.len()
+                )));
+            }
+            Ok(outputs)
+        });
+
+        let outputs = run_ordered(tasks, Some(self.concurrency), self.mode).await?;
+        Ok(outputs.into_iter().flatten().collect())
+    }
 
-        Ok(all_outputs)
+    fn name(&self) -> &str {
+        &self.name
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{LambdaStep, ExecutionContext, Error};
+    use crate::{LambdaStep, ExecutionContext};
 
     fn ctx() -> ExecutionContext {
         ExecutionContext::default()
@@ -146,4 +379,106 @@ mod tests {
         let batch = BatchStep::new(inner, 2);
         assert!(batch.run(&ctx(), vec![1, 2, 3]).await.is_err());
     }
+
+    fn echo_lengths() -> impl Step<Input = Vec<String>, Output = Vec<usize>> {
+        LambdaStep::new(|v: Vec<String>| async move {
+            Ok(v.iter().map(|s| s.len()).collect::<Vec<_>>())
+        })
+    }
+
+    fn words(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn planned(ctx: &ExecutionContext) -> Vec<(Vec<usize>, Option<Vec<usize>>)> {
+        ctx.trace_snapshot()
+            .into_iter()
+            .filter_map(|entry| match entry.event {
+                WorkflowEvent::BatchPlanned {
+                    batch_sizes,
+                    batch_tokens,
+                    ..
+                } => Some((batch_sizes, batch_tokens)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_token_budget_packs_greedily() {
+        let budget = TokenBudget::new(5, |s: &String| s.len());
+        let batches = budget.plan(words(&["aa", "bb", "c", "dddd", "e"])).unwrap();
+        assert_eq!(batches, vec![words(&["aa", "bb", "c"]), words(&["dddd", "e"])]);
+    }
+
+    #[tokio::test]
+    async fn test_token_budget_records_composition() {
+        let ctx = ctx();
+        let step = BatchStep::with_policy(echo_lengths(), TokenBudget::new(4, |s: &String| s.len()));
+        let out = step.run(&ctx, words(&["abc", "d", "efg"])).await.unwrap();
+        assert_eq!(out, vec![3, 1, 3]);
+        assert_eq!(planned(&ctx), vec![(vec![2, 1], Some(vec![4, 3]))]);
+    }
+
+    #[tokio::test]
+    async fn test_fixed_size_records_counts_only() {
+        let ctx = ctx();
+        BatchStep::new(echo_lengths(), 2).run(&ctx, words(&["a", "b", "c"])).await.unwrap();
+        assert_eq!(planned(&ctx), vec![(vec![2, 1], None)]);
+    }
+
+    #[tokio::test]
+    async fn test_oversize_item_errors_by_default() {
+        let step = BatchStep::with_policy(echo_lengths(), TokenBudget::new(3, |s: &String| s.len()));
+        let result = step.run(&ctx(), words(&["ab", "toolong"])).await;
+        assert!(matches!(result, Err(Error::Validation(_))));
+    }
+
+    #[test]
+    fn test_oversize_item_isolated() {
+        let budget = TokenBudget::new(3, |s: &String| s.len()).isolate_oversize();
+        let batches = budget.plan(words(&["a", "toolong", "b", "c"])).unwrap();
+        assert_eq!(batches, vec![words(&["a"]), words(&["toolong"]), words(&["b", "c"])]);
+    }
+
+    #[tokio::test]
+    async fn test_output_length_mismatch_is_rejected() {
+        let drop_last = LambdaStep::new(|mut v: Vec<i32>| async move {
+            v.pop();
+            Ok(v)
+        });
+        let result = BatchStep::new(drop_last, 2).run(&ctx(), vec![1, 2, 3]).await;
+        match result {
+            Err(Error::Validation(msg)) => assert_eq!(msg, "batch 0 returned 1 outputs for 2 inputs"),
+            other => panic!("expected validation error, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_batches_preserve_order() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let (a, p) = (Arc::clone(&active), Arc::clone(&peak));
+        let inner = LambdaStep::new(move |v: Vec<u64>| {
+            let (a, p) = (Arc::clone(&a), Arc::clone(&p));
+            async move {
+                let now = a.fetch_add(1, Ordering::SeqCst) + 1;
+                p.fetch_max(now, Ordering::SeqCst);
+                // Later batches finish first.
+                tokio::time::sleep(std::time::Duration::from_millis(40 - v[0] * 3)).await;
+                a.fetch_sub(1, Ordering::SeqCst);
+                Ok(v.into_iter().map(|x| x * 10).collect::<Vec<_>>())
+            }
+        });
+        let out = BatchStep::new(inner, 2)
+            .with_concurrency(3)
+            .run(&ctx(), (0..10).collect())
+            .await
+            .unwrap();
+        assert_eq!(out, (0..10).map(|x| x * 10).collect::<Vec<_>>());
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+    }
 }
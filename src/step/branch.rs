@@ -1,15 +1,21 @@
 //! Conditional step routing.
 
 use async_trait::async_trait;
+use std::future::Future;
 
 use crate::{ExecutionContext, Result};
 use super::Step;
+use super::predicate::{AsyncPredicate, Predicate, StepPredicate};
 
 /// A step that routes to one of two steps based on a predicate over the input.
 ///
 /// If the predicate returns `true`, `left` is executed; otherwise `right` is.
 /// Both branches must have the same input and output types.
 ///
+/// The predicate can be any [`Predicate`]: a synchronous `Fn(&I) -> bool`, or
+/// an async decision built with [`BranchStep::from_step`] or
+/// [`BranchStep::from_async`].
+///
 /// # Example
 ///
 /// ```rust
@@ -42,10 +48,49 @@ impl<P, L, R> BranchStep<P, L, R> {
     }
 }
 
+impl<S, L, R> BranchStep<StepPredicate<S>, L, R> {
+    /// Create a branch step whose predicate is a step run over a clone of the input.
+    pub fn from_step(predicate: S, left: L, right: R) -> Self {
+        Self::new(StepPredicate::new(predicate), left, right)
+    }
+}
+
+impl<I, F, L, R> BranchStep<AsyncPredicate<I, F>, L, R> {
+    /// Create a branch step whose predicate is an async closure over
+    /// a clone of the execution context and input.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use llm_workflow::{LambdaStep, ExecutionContext, Step, step::branch::BranchStep};
+    ///
+    /// # tokio_test::block_on(async {
+    /// let branch = BranchStep::from_async(
+    ///     |_ctx: ExecutionContext, text: String| async move {
+    ///         // e.g. call a moderation API here
+    ///         Ok::<bool, llm_workflow::Error>(text.contains("spam"))
+    ///     },
+    ///     LambdaStep::new(|_t: String| async move { Ok::<&str, llm_workflow::Error>("flagged") }),
+    ///     LambdaStep::new(|_t: String| async move { Ok::<&str, llm_workflow::Error>("clean") }),
+    /// );
+    ///
+    /// let ctx = ExecutionContext::new();
+    /// assert_eq!(branch.run(&ctx, "buy spam now".to_string()).await.unwrap(), "flagged");
+    /// # });
+    /// ```
+    pub fn from_async<Fut>(predicate: F, left: L, right: R) -> Self
+    where
+        F: Fn(ExecutionContext, I) -> Fut + Send + Sync,
+        Fut: Future<Output = Result<bool>> + Send + 'static,
+    {
+        Self::new(AsyncPredicate::new(predicate), left, right)
+    }
+}
+
 #[async_trait]
 impl<P, L, R, I, O> Step for BranchStep<P, L, R>
 where
-    P: Fn(&I) -> bool + Send + Sync + 'static,
+    P: Predicate<I> + 'static,
     L: Step<Input = I, Output = O>,
     R: Step<Input = I, Output = O>,
     I: Send + 'static,
@@ -55,10 +100,81 @@ where
     type Output = O;
 
     async fn run(&self, ctx: &ExecutionContext, input: I) -> Result<O> {
-        if (self.predicate)(&input) {
+        let decision = self.predicate.evaluate(ctx, &input);
+        if decision.await? {
             self.left.run(ctx, input).await
         } else {
             self.right.run(ctx, input).await
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Error, LambdaStep};
+    use crate::step::predicate::ProjectedStepPredicate;
+
+    fn label(tag: &'static str) -> impl Step<Input = String, Output = String> {
+        LambdaStep::new(move |s: String| async move { Ok(format!("{tag}:{s}")) })
+    }
+
+    #[tokio::test]
+    async fn test_branch_with_sync_predicate() {
+        let branch = BranchStep::new(|s: &String| s.is_empty(), label("empty"), label("full"));
+        let ctx = ExecutionContext::new();
+        assert_eq!(branch.run(&ctx, String::new()).await.unwrap(), "empty:");
+        assert_eq!(branch.run(&ctx, "x".to_string()).await.unwrap(), "full:x");
+    }
+
+    #[tokio::test]
+    async fn test_branch_with_step_predicate() {
+        let judge = LambdaStep::new(|s: String| async move { Ok(s.len() > 3) });
+        let branch = BranchStep::from_step(judge, label("long"), label("short"));
+        let ctx = ExecutionContext::new();
+        assert_eq!(branch.run(&ctx, "abcd".to_string()).await.unwrap(), "long:abcd");
+        assert_eq!(branch.run(&ctx, "ab".to_string()).await.unwrap(), "short:ab");
+    }
+
+    #[tokio::test]
+    async fn test_branch_with_projected_predicate() {
+        let first_char_upper = LambdaStep::new(|c: char| async move { Ok(c.is_uppercase()) });
+        let predicate = ProjectedStepPredicate::new(first_char_upper, |s: &String| {
+            s.chars().next().unwrap_or(' ')
+        });
+        let branch = BranchStep::new(predicate, label("upper"), label("lower"));
+        let ctx = ExecutionContext::new();
+        assert_eq!(branch.run(&ctx, "Hi".to_string()).await.unwrap(), "upper:Hi");
+    }
+
+    #[tokio::test]
+    async fn test_branch_async_predicate_sees_context() {
+        let branch = BranchStep::from_async(
+            |ctx: ExecutionContext, _s: String| async move { Ok(ctx.snapshot().steps_completed > 0) },
+            label("warm"),
+            label("cold"),
+        );
+        let ctx = ExecutionContext::new();
+        assert_eq!(branch.run(&ctx, "a".to_string()).await.unwrap(), "cold:a");
+        ctx.record_step();
+        assert_eq!(branch.run(&ctx, "a".to_string()).await.unwrap(), "warm:a");
+    }
+
+    #[tokio::test]
+    async fn test_branch_propagates_predicate_error() {
+        let judge = LambdaStep::new(|_s: String| async move {
+            Err::<bool, _>(Error::Execution("moderation down".to_string()))
+        });
+        let branch = BranchStep::from_step(judge, label("a"), label("b"));
+        assert!(branch.run(&ExecutionContext::new(), "x".to_string()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sync_predicate_accepts_non_sync_input() {
+        use std::cell::Cell;
+
+        let step = |tag: &'static str| LambdaStep::new(move |c: Cell<u32>| async move { Ok(format!("{tag}:{}", c.get())) });
+        let branch = BranchStep::new(|c: &Cell<u32>| c.get() > 1, step("big"), step("small"));
+        assert_eq!(branch.run(&ExecutionContext::new(), Cell::new(2)).await.unwrap(), "big:2");
+    }
+}
@@ -39,7 +39,7 @@ where
 /// A step that fans out a single input to two independent steps and returns both outputs.
 ///
 /// Both steps receive a clone of the input and execute sequentially.
-/// For parallel fan-out, wrap each step in a [`ParallelMapStep`](crate::ParallelMapStep).
+/// To run them concurrently, use [`JoinStep`](crate::JoinStep) instead.
 pub struct ChainTupleStep<A, B> {
     first: A,
     second: B,
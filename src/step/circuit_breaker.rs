@@ -0,0 +1,445 @@
+//! Circuit breaker wrapper for flaky steps.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::{Error, ExecutionContext, Result, WorkflowEvent};
+use super::Step;
+
+/// The state of a circuit breaker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CircuitState {
+    /// Calls pass through; consecutive failures are counted.
+    Closed,
+    /// Calls fail fast with [`Error::CircuitOpen`] until the cooldown elapses.
+    Open,
+    /// A single trial call is allowed through to probe whether the inner step recovered.
+    HalfOpen,
+}
+
+#[derive(Debug)]
+struct Breaker {
+    state: CircuitState,
+    consecutive_failures: usize,
+    opened_at: Option<Instant>,
+    trial_in_flight: bool,
+    /// Bumped by [`CircuitBreakerStep::reset`] so trials started before a
+    /// reset do not act on the state after it.
+    generation: u64,
+}
+
+/// Clears the half-open trial flag if the trial call is dropped before completing.
+struct TrialGuard<'a> {
+    breaker: &'a Mutex<Breaker>,
+    generation: u64,
+    armed: bool,
+}
+
+impl Drop for TrialGuard<'_> {
+    fn drop(&mut self) {
+        if self.armed {
+            let mut breaker = self.breaker.lock().unwrap();
+            if breaker.generation == self.generation {
+                breaker.trial_in_flight = false;
+            }
+        }
+    }
+}
+
+/// Wraps a step with a circuit breaker that fails fast while the inner step is unhealthy.
+///
+/// Failure tracking is shared across runs through an internal `Arc<Mutex<..>>`,
+/// and across clones of the step, so several call sites can guard the same
+/// provider with one circuit:
+///
+/// - **Closed**: calls pass through. After `failure_threshold` consecutive
+///   failures the circuit opens.
+/// - **Open**: calls fail immediately with [`Error::CircuitOpen`]. Once
+///   `cooldown` has elapsed the next call moves the circuit to half-open.
+/// - **HalfOpen**: one trial call is let through; success closes the circuit,
+///   failure re-opens it. Concurrent calls fail fast while the trial runs.
+///
+/// [`Error::Checkpoint`] results are not counted as failures. Every state
+/// transition is emitted as a [`WorkflowEvent::CircuitStateChanged`] event.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+/// use llm_workflow::{LambdaStep, Step, ExecutionContext, Error, step::circuit_breaker::CircuitBreakerStep};
+///
+/// # tokio_test::block_on(async {
+/// let flaky = LambdaStep::new(|_x: i32| async move {
+///     Err::<i32, Error>(Error::Execution("provider down".to_string()))
+/// });
+/// let breaker = CircuitBreakerStep::new(flaky, 2, Duration::from_secs(30));
+/// let ctx = ExecutionContext::new();
+///
+/// assert!(matches!(breaker.run(&ctx, 1).await, Err(Error::Execution(_))));
+/// assert!(matches!(breaker.run(&ctx, 1).await, Err(Error::Execution(_))));
+/// // Threshold reached: the inner step is no longer called.
+/// assert!(matches!(breaker.run(&ctx, 1).await, Err(Error::CircuitOpen { .. })));
+/// # });
+/// ```
+pub struct CircuitBreakerStep<S> {
+    inner: S,
+    name: String,
+    failure_threshold: usize,
+    cooldown: Duration,
+    breaker: Arc<Mutex<Breaker>>,
+}
+
+impl<S: Clone> Clone for CircuitBreakerStep<S> {
+    /// Clone the step; the clone shares this step's circuit.
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            name: self.name.clone(),
+            failure_threshold: self.failure_threshold,
+            cooldown: self.cooldown,
+            breaker: Arc::clone(&self.breaker),
+        }
+    }
+}
+
+impl<S> CircuitBreakerStep<S> {
+    /// Wrap `inner`, opening after `failure_threshold` consecutive failures
+    /// and half-opening after `cooldown`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `failure_threshold` is zero.
+    pub fn new(inner: S, failure_threshold: usize, cooldown: Duration) -> Self {
+        assert!(failure_threshold > 0, "failure_threshold must be greater than zero");
+        Self {
+            inner,
+            name: "circuit_breaker".to_string(),
+            failure_threshold,
+            cooldown,
+            breaker: Arc::new(Mutex::new(Breaker {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                opened_at: None,
+                trial_in_flight: false,
+                generation: 0,
+            })),
+        }
+    }
+
+    /// Set a human-readable name, used in emitted events and errors.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// The current state of the circuit.
+    pub fn state(&self) -> CircuitState {
+        self.breaker.lock().unwrap().state
+    }
+
+    /// Force the circuit closed and clear the failure count.
+    ///
+    /// A half-open trial still running is disowned: its outcome no longer
+    /// affects the circuit. Emits a [`WorkflowEvent::CircuitStateChanged`]
+    /// event if the circuit was not already closed.
+    pub fn reset(&self, ctx: &ExecutionContext) {
+        let from = {
+            let mut breaker = self.breaker.lock().unwrap();
+            let from = breaker.state;
+            breaker.state = CircuitState::Closed;
+            breaker.consecutive_failures = 0;
+            breaker.opened_at = None;
+            breaker.trial_in_flight = false;
+            breaker.generation += 1;
+            from
+        };
+        self.transition(ctx, from, CircuitState::Closed);
+    }
+
+    /// Access the inner step.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    fn transition(&self, ctx: &ExecutionContext, from: CircuitState, to: CircuitState) {
+        if from != to {
+            ctx.emit(WorkflowEvent::CircuitStateChanged {
+                step_name: self.name.clone(),
+                from,
+                to,
+            });
+        }
+    }
+}
+
+#[async_trait]
+impl<S> Step for CircuitBreakerStep<S>
+where
+    S: Step,
+    S::Input: 'static,
+    S::Output: 'static,
+{
+    type Input = S::Input;
+    type Output = S::Output;
+
+    async fn run(&self, ctx: &ExecutionContext, input: S::Input) -> Result<S::Output> {
+        let (is_trial, admitted_from, generation) = {
+            let mut breaker = self.breaker.lock().unwrap();
+            let generation = breaker.generation;
+            let (is_trial, admitted_from) = match breaker.state {
+                CircuitState::Closed => (false, CircuitState::Closed),
+                CircuitState::Open => {
+                    let elapsed = breaker.opened_at.map_or(self.cooldown, |t| t.elapsed());
+                    if elapsed < self.cooldown {
+                        return Err(Error::CircuitOpen {
+                            step_name: self.name.clone(),
+                            retry_after_ms: (self.cooldown - elapsed).as_millis(),
+                        });
+                    }
+                    breaker.state = CircuitState::HalfOpen;
+                    breaker.trial_in_flight = true;
+                    (true, CircuitState::Open)
+                }
+                CircuitState::HalfOpen => {
+                    if breaker.trial_in_flight {
+                        return Err(Error::CircuitOpen {
+                            step_name: self.name.clone(),
+                            retry_after_ms: 0,
+                        });
+                    }
+                    breaker.trial_in_flight = true;
+                    (true, CircuitState::HalfOpen)
+                }
+            };
+            (is_trial, admitted_from, generation)
+        };
+        if admitted_from == CircuitState::Open {
+            self.transition(ctx, CircuitState::Open, CircuitState::HalfOpen);
+        }
+
+        let mut guard = TrialGuard {
+            breaker: &self.breaker,
+            generation,
+            armed: is_trial,
+        };
+        let result = self.inner.run(ctx, input).await;
+        guard.armed = false;
+        drop(guard);
+
+        let (from, to) = {
+            let mut breaker = self.breaker.lock().unwrap();
+            let from = breaker.state;
+            if is_trial && breaker.generation != generation {
+                // The circuit was reset while this trial ran.
+                return result;
+            }
+            if is_trial {
+                breaker.trial_in_flight = false;
+            }
+            match &result {
+                Ok(_) => {
+                    breaker.consecutive_failures = 0;
+                    if is_trial {
+                        breaker.state = CircuitState::Closed;
+                        breaker.opened_at = None;
+                    }
+                }
+                Err(Error::Checkpoint { .. }) => {}
+                Err(_) => {
+                    breaker.consecutive_failures += 1;
+                    let trips = is_trial
+                        || (breaker.state == CircuitState::Closed
+                            && breaker.consecutive_failures >= self.failure_threshold);
+                    if trips {
+                        breaker.state = CircuitState::Open;
+                        breaker.opened_at = Some(Instant::now());
+                    }
+                }
+            }
+            (from, breaker.state)
+        };
+        self.transition(ctx, from, to);
+
+        result
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    struct Flaky {
+        healthy: Arc<AtomicBool>,
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Step for Flaky {
+        type Input = ();
+        type Output = &'static str;
+
+        async fn run(&self, _ctx: &ExecutionContext, _input: ()) -> Result<&'static str> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.healthy.load(Ordering::SeqCst) {
+                Ok("ok")
+            } else {
+                Err(Error::Execution("down".to_string()))
+            }
+        }
+    }
+
+    fn flaky(cooldown_ms: u64) -> (CircuitBreakerStep<Flaky>, Arc<AtomicBool>, Arc<AtomicUsize>) {
+        let healthy = Arc::new(AtomicBool::new(false));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let step = Flaky {
+            healthy: Arc::clone(&healthy),
+            calls: Arc::clone(&calls),
+        };
+        let breaker = CircuitBreakerStep::new(step, 2, Duration::from_millis(cooldown_ms))
+            .with_name("provider");
+        (breaker, healthy, calls)
+    }
+
+    fn transitions(ctx: &ExecutionContext) -> Vec<(CircuitState, CircuitState)> {
+        ctx.trace_snapshot()
+            .into_iter()
+            .filter_map(|entry| match entry.event {
+                WorkflowEvent::CircuitStateChanged { from, to, .. } => Some((from, to)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_opens_after_threshold_and_fails_fast() {
+        let (breaker, _healthy, calls) = flaky(10_000);
+        let ctx = ExecutionContext::new();
+
+        assert!(breaker.run(&ctx, ()).await.is_err());
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert!(breaker.run(&ctx, ()).await.is_err());
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        let err = breaker.run(&ctx, ()).await.unwrap_err();
+        assert!(matches!(err, Error::CircuitOpen { ref step_name, .. } if step_name == "provider"));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(transitions(&ctx), vec![(CircuitState::Closed, CircuitState::Open)]);
+    }
+
+    #[tokio::test]
+    async fn test_half_open_trial_success_closes() {
+        let (breaker, healthy, _calls) = flaky(20);
+        let ctx = ExecutionContext::new();
+        let _ = breaker.run(&ctx, ()).await;
+        let _ = breaker.run(&ctx, ()).await;
+
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        healthy.store(true, Ordering::SeqCst);
+        assert_eq!(breaker.run(&ctx, ()).await.unwrap(), "ok");
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert_eq!(
+            transitions(&ctx),
+            vec![
+                (CircuitState::Closed, CircuitState::Open),
+                (CircuitState::Open, CircuitState::HalfOpen),
+                (CircuitState::HalfOpen, CircuitState::Closed),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_half_open_trial_failure_reopens() {
+        let (breaker, _healthy, calls) = flaky(20);
+        let ctx = ExecutionContext::new();
+        let _ = breaker.run(&ctx, ()).await;
+        let _ = breaker.run(&ctx, ()).await;
+
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(matches!(breaker.run(&ctx, ()).await, Err(Error::Execution(_))));
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(matches!(breaker.run(&ctx, ()).await, Err(Error::CircuitOpen { .. })));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_success_resets_failure_count() {
+        let (breaker, healthy, _calls) = flaky(10_000);
+        let ctx = ExecutionContext::new();
+        let _ = breaker.run(&ctx, ()).await;
+        healthy.store(true, Ordering::SeqCst);
+        breaker.run(&ctx, ()).await.unwrap();
+        healthy.store(false, Ordering::SeqCst);
+        let _ = breaker.run(&ctx, ()).await;
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[tokio::test]
+    async fn test_reset_disowns_running_trial() {
+        struct SlowFailure;
+
+        #[async_trait]
+        impl Step for SlowFailure {
+            type Input = u64;
+            type Output = ();
+
+            async fn run(&self, _ctx: &ExecutionContext, delay_ms: u64) -> Result<()> {
+                tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+                Err(Error::Execution("down".to_string()))
+            }
+        }
+
+        let breaker = CircuitBreakerStep::new(SlowFailure, 1, Duration::from_millis(10));
+        let ctx = ExecutionContext::new();
+        let _ = breaker.run(&ctx, 0).await;
+        assert_eq!(breaker.state(), CircuitState::Open);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        let trial = breaker.run(&ctx, 50);
+        let reset = async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            breaker.reset(&ctx);
+        };
+        let (result, ()) = tokio::join!(trial, reset);
+        assert!(matches!(result, Err(Error::Execution(_))));
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert_eq!(
+            transitions(&ctx),
+            vec![
+                (CircuitState::Closed, CircuitState::Open),
+                (CircuitState::Open, CircuitState::HalfOpen),
+                (CircuitState::HalfOpen, CircuitState::Closed),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_clones_share_the_circuit() {
+        #[derive(Clone)]
+        struct Down;
+
+        #[async_trait]
+        impl Step for Down {
+            type Input = ();
+            type Output = ();
+
+            async fn run(&self, _ctx: &ExecutionContext, _input: ()) -> Result<()> {
+                Err(Error::Execution("down".to_string()))
+            }
+        }
+
+        let first = CircuitBreakerStep::new(Down, 2, Duration::from_secs(10));
+        let second = first.clone();
+        let ctx = ExecutionContext::new();
+        let _ = first.run(&ctx, ()).await;
+        let _ = second.run(&ctx, ()).await;
+        assert_eq!(first.state(), CircuitState::Open);
+        assert!(matches!(second.run(&ctx, ()).await, Err(Error::CircuitOpen { .. })));
+    }
+}
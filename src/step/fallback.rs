@@ -0,0 +1,200 @@
+//! Fallback chains for provider failover.
+
+use async_trait::async_trait;
+
+use crate::{Error, ExecutionContext, Result, WorkflowEvent};
+use super::Step;
+
+type BoxedStep<I, O> = Box<dyn Step<Input = I, Output = O> + Send + Sync>;
+type ErrorPredicate = Box<dyn Fn(&Error) -> bool + Send + Sync>;
+
+/// A step that tries a sequence of steps in order until one succeeds.
+///
+/// Each attempt receives a clone of the input. When an attempt fails and the
+/// error predicate accepts the error, the next step is tried and a
+/// [`WorkflowEvent::Fallback`] event is emitted. By default every error except
+/// [`Error::Checkpoint`] triggers a fallback, since checkpoints are control
+/// flow rather than failures. If the last step fails, its error is returned.
+///
+/// Constructed via [`BoxedStepExt::or_else`](crate::BoxedStepExt::or_else) or
+/// [`FallbackStep::new`].
+///
+/// # Example
+///
+/// ```rust
+/// use llm_workflow::{LambdaStep, Step, ExecutionContext, BoxedStepExt, Error};
+///
+/// # tokio_test::block_on(async {
+/// let primary = LambdaStep::new(|_q: String| async move {
+///     Err::<String, Error>(Error::Execution("primary unavailable".to_string()))
+/// });
+/// let secondary = LambdaStep::new(|q: String| async move { Ok::<String, Error>(format!("secondary: {q}")) });
+///
+/// let step = primary.or_else(secondary);
+/// let out = step.run(&ExecutionContext::new(), "hi".to_string()).await.unwrap();
+/// assert_eq!(out, "secondary: hi");
+/// # });
+/// ```
+pub struct FallbackStep<I, O> {
+    steps: Vec<BoxedStep<I, O>>,
+    should_fallback: ErrorPredicate,
+    name: String,
+}
+
+impl<I, O> FallbackStep<I, O>
+where
+    I: Send + 'static,
+    O: Send + 'static,
+{
+    /// Create a fallback chain starting with `primary`.
+    pub fn new<S>(primary: S) -> Self
+    where
+        S: Step<Input = I, Output = O> + 'static,
+    {
+        Self {
+            steps: vec![Box::new(primary)],
+            should_fallback: Box::new(|e| !matches!(e, Error::Checkpoint { .. })),
+            name: "fallback".to_string(),
+        }
+    }
+
+    /// Append another step to try if all previous steps failed.
+    pub fn or_else<S>(mut self, step: S) -> Self
+    where
+        S: Step<Input = I, Output = O> + 'static,
+    {
+        self.steps.push(Box::new(step));
+        self
+    }
+
+    /// Set which errors trigger a fallback; other errors are returned immediately.
+    pub fn with_predicate<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&Error) -> bool + Send + Sync + 'static,
+    {
+        self.should_fallback = Box::new(predicate);
+        self
+    }
+
+    /// Set a human-readable name, used in emitted events.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+}
+
+#[async_trait]
+impl<I, O> Step for FallbackStep<I, O>
+where
+    I: Clone + Send + Sync + 'static,
+    O: Send + 'static,
+{
+    type Input = I;
+    type Output = O;
+
+    async fn run(&self, ctx: &ExecutionContext, input: I) -> Result<O> {
+        let last = self.steps.len() - 1;
+        for (idx, step) in self.steps.iter().enumerate() {
+            if idx == last {
+                return step.run(ctx, input).await;
+            }
+            match step.run(ctx, input.clone()).await {
+                Ok(output) => return Ok(output),
+                Err(e) if (self.should_fallback)(&e) => {
+                    ctx.emit(WorkflowEvent::Fallback {
+                        step_name: self.name.clone(),
+                        failed_index: idx,
+                        next_index: idx + 1,
+                        error: e.to_string(),
+                    });
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        unreachable!("FallbackStep always holds at least one step")
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoxedStepExt, LambdaStep};
+
+    fn failing(msg: &'static str) -> BoxedStep<i32, String> {
+        LambdaStep::new(move |_x: i32| async move { Err::<String, _>(Error::Execution(msg.to_string())) })
+            .boxed()
+    }
+
+    fn succeeding(label: &'static str) -> BoxedStep<i32, String> {
+        LambdaStep::new(move |x: i32| async move { Ok(format!("{label}:{x}")) }).boxed()
+    }
+
+    fn fallback_events(ctx: &ExecutionContext) -> Vec<(usize, usize)> {
+        ctx.trace_snapshot()
+            .into_iter()
+            .filter_map(|entry| match entry.event {
+                WorkflowEvent::Fallback {
+                    failed_index,
+                    next_index,
+                    ..
+                } => Some((failed_index, next_index)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_primary_success_skips_fallbacks() {
+        let step = succeeding("primary").or_else(failing("unused"));
+        let ctx = ExecutionContext::new();
+        assert_eq!(step.run(&ctx, 1).await.unwrap(), "primary:1");
+        assert!(fallback_events(&ctx).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_falls_through_chain_and_emits_events() {
+        let step = failing("a")
+            .or_else(failing("b"))
+            .or_else(succeeding("c"));
+        let ctx = ExecutionContext::new();
+        assert_eq!(step.run(&ctx, 2).await.unwrap(), "c:2");
+        assert_eq!(fallback_events(&ctx), vec![(0, 1), (1, 2)]);
+    }
+
+    #[tokio::test]
+    async fn test_last_error_returned_when_all_fail() {
+        let step = failing("first").or_else(failing("last"));
+        let err = step.run(&ExecutionContext::new(), 0).await.unwrap_err();
+        assert_eq!(err.to_string(), "Execution error: last");
+    }
+
+    #[tokio::test]
+    async fn test_predicate_limits_fallback() {
+        let step = LambdaStep::new(|_x: i32| async move {
+            Err::<String, _>(Error::Validation("bad input".to_string()))
+        })
+        .or_else(succeeding("backup"))
+        .with_predicate(|e| matches!(e, Error::Execution(_)));
+
+        let err = step.run(&ExecutionContext::new(), 0).await.unwrap_err();
+        assert!(matches!(err, Error::Validation(_)));
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_does_not_trigger_fallback_by_default() {
+        let step = LambdaStep::new(|x: i32| async move {
+            Err::<String, _>(Error::Checkpoint {
+                step_name: "review".to_string(),
+                data: serde_json::json!(x),
+            })
+        })
+        .or_else(succeeding("backup"));
+
+        let err = step.run(&ExecutionContext::new(), 0).await.unwrap_err();
+        assert!(matches!(err, Error::Checkpoint { .. }));
+    }
+}
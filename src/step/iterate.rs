@@ -0,0 +1,230 @@
+//! Iterative refinement loops.
+
+use async_trait::async_trait;
+
+use crate::{ExecutionContext, Result, WorkflowEvent};
+use super::Step;
+use super::predicate::Predicate;
+
+/// A step that repeatedly runs a body step, feeding each output into the next iteration.
+///
+/// After every iteration the `until` [`Predicate`] is evaluated against the
+/// latest output; the loop stops when it returns `true` or after
+/// `max_iterations` rounds, whichever comes first, and returns the last
+/// output. Each iteration emits a [`WorkflowEvent::LoopIteration`] event.
+///
+/// Because `until` is a [`Predicate`], the stop condition may itself be a
+/// model call (e.g. a judge wrapped in a
+/// [`StepPredicate`](crate::StepPredicate)). Use [`LoopStep::collect_all`]
+/// to receive every intermediate output instead of just the last.
+///
+/// # Example
+///
+/// ```rust
+/// use llm_workflow::{LambdaStep, Step, ExecutionContext, step::iterate::LoopStep};
+///
+/// # tokio_test::block_on(async {
+/// // "Rewrite until the score is at least 8, at most 5 rounds."
+/// let rewrite = LambdaStep::new(|score: u32| async move { Ok::<u32, llm_workflow::Error>(score + 3) });
+/// let refine = LoopStep::new(rewrite, |score: &u32| *score >= 8, 5);
+///
+/// let out = refine.run(&ExecutionContext::new(), 1).await.unwrap();
+/// assert_eq!(out, 10);
+/// # });
+/// ```
+pub struct LoopStep<S, P> {
+    body: S,
+    until: P,
+    max_iterations: usize,
+    name: String,
+}
+
+impl<S, P> LoopStep<S, P> {
+    /// Create a loop running `body` until `until` holds or `max_iterations` is reached.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_iterations` is zero.
+    pub fn new(body: S, until: P, max_iterations: usize) -> Self {
+        assert!(max_iterations > 0, "max_iterations must be greater than zero");
+        Self {
+            body,
+            until,
+            max_iterations,
+            name: "loop".to_string(),
+        }
+    }
+
+    /// Set a human-readable name, used in emitted events.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Return every intermediate output rather than only the last.
+    pub fn collect_all(self) -> CollectLoopStep<S, P> {
+        CollectLoopStep { inner: self }
+    }
+}
+
+impl<S, P, T> LoopStep<S, P>
+where
+    S: Step<Input = T, Output = T>,
+    P: Predicate<T>,
+    T: Send + 'static,
+{
+    async fn iterate(
+        &self,
+        ctx: &ExecutionContext,
+        input: T,
+        mut on_iteration: impl FnMut(&T) + Send,
+    ) -> Result<T> {
+        let mut value = input;
+        for iteration in 1..=self.max_iterations {
+            value = self.body.run(ctx, value).await?;
+            on_iteration(&value);
+            let converged = self.until.evaluate(ctx, &value);
+            let converged = converged.await?;
+            ctx.emit(WorkflowEvent::LoopIteration {
+                step_name: self.name.clone(),
+                iteration,
+                converged,
+            });
+            if converged {
+                break;
+            }
+        }
+        Ok(value)
+    }
+}
+
+#[async_trait]
+impl<S, P, T> Step for LoopStep<S, P>
+where
+    S: Step<Input = T, Output = T>,
+    P: Predicate<T> + 'static,
+    T: Send + 'static,
+{
+    type Input = T;
+    type Output = T;
+
+    async fn run(&self, ctx: &ExecutionContext, input: T) -> Result<T> {
+        self.iterate(ctx, input, |_| {}).await
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// A [`LoopStep`] that returns the output of every iteration, in order.
+///
+/// Constructed via [`LoopStep::collect_all`].
+pub struct CollectLoopStep<S, P> {
+    inner: LoopStep<S, P>,
+}
+
+#[async_trait]
+impl<S, P, T> Step for CollectLoopStep<S, P>
+where
+    S: Step<Input = T, Output = T>,
+    P: Predicate<T> + 'static,
+    T: Clone + Send + 'static,
+{
+    type Input = T;
+    type Output = Vec<T>;
+
+    async fn run(&self, ctx: &ExecutionContext, input: T) -> Result<Vec<T>> {
+        let mut history = Vec::new();
+        self.inner
+            .iterate(ctx, input, |value| history.push(value.clone()))
+            .await?;
+        Ok(history)
+    }
+
+    fn name(&self) -> &str {
+        &self.inner.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Error, LambdaStep, StepPredicate};
+
+    fn increment() -> impl Step<Input = u32, Output = u32> {
+        LambdaStep::new(|x: u32| async move { Ok(x + 1) })
+    }
+
+    fn iterations(ctx: &ExecutionContext) -> Vec<(usize, bool)> {
+        ctx.trace_snapshot()
+            .into_iter()
+            .filter_map(|entry| match entry.event {
+                WorkflowEvent::LoopIteration {
+                    iteration,
+                    converged,
+                    ..
+                } => Some((iteration, converged)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_stops_when_predicate_holds() {
+        let ctx = ExecutionContext::new();
+        let out = LoopStep::new(increment(), |x: &u32| *x >= 3, 10)
+            .run(&ctx, 0)
+            .await
+            .unwrap();
+        assert_eq!(out, 3);
+        assert_eq!(iterations(&ctx), vec![(1, false), (2, false), (3, true)]);
+    }
+
+    #[tokio::test]
+    async fn test_stops_at_max_iterations() {
+        let ctx = ExecutionContext::new();
+        let out = LoopStep::new(increment(), |_x: &u32| false, 4)
+            .run(&ctx, 0)
+            .await
+            .unwrap();
+        assert_eq!(out, 4);
+        assert_eq!(iterations(&ctx).len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_collect_all_returns_history() {
+        let out = LoopStep::new(increment(), |x: &u32| *x == 13, 5)
+            .collect_all()
+            .run(&ExecutionContext::new(), 10)
+            .await
+            .unwrap();
+        assert_eq!(out, vec![11, 12, 13]);
+    }
+
+    #[tokio::test]
+    async fn test_async_judge_predicate() {
+        let judge = LambdaStep::new(|draft: String| async move { Ok(draft.len() >= 6) });
+        let rewrite = LambdaStep::new(|draft: String| async move { Ok(format!("{draft}!!")) });
+        let out = LoopStep::new(rewrite, StepPredicate::new(judge), 5)
+            .run(&ExecutionContext::new(), "ok".to_string())
+            .await
+            .unwrap();
+        assert_eq!(out, "ok!!!!");
+    }
+
+    #[tokio::test]
+    async fn test_body_error_aborts_loop() {
+        let body = LambdaStep::new(|x: u32| async move {
+            if x >= 2 {
+                Err(Error::Execution("diverged".to_string()))
+            } else {
+                Ok(x + 1)
+            }
+        });
+        let result = LoopStep::new(body, |_x: &u32| false, 10)
+            .run(&ExecutionContext::new(), 0)
+            .await;
+        assert!(matches!(result, Err(Error::Execution(_))));
+    }
+}
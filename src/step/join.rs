@@ -0,0 +1,199 @@
+//! Concurrent fan-out to heterogeneous steps.
+//!
+//! Join steps run 2..5 independent steps concurrently over clones of the same
+//! input and return their outputs as a tuple. Unlike
+//! [`ChainTupleStep`](crate::ChainTupleStep), total latency is that of the
+//! slowest step rather than the sum.
+
+use async_trait::async_trait;
+use futures::future::{join, join3, join4, join5, try_join, try_join3, try_join4, try_join5};
+
+use crate::{ExecutionContext, Result};
+use super::Step;
+
+/// How a join step reacts when one of its steps fails.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JoinMode {
+    /// Return the first error as soon as it occurs, cancelling the remaining steps.
+    #[default]
+    FailFast,
+    /// Let every step run to completion, then return the first error in step order.
+    WaitAll,
+}
+
+macro_rules! join_step {
+    (
+        $(#[$meta:meta])*
+        $name:ident, $join:ident, $try_join:ident,
+        $($ty:ident => $field:ident),+
+    ) => {
+        $(#[$meta])*
+        pub struct $name<$($ty),+> {
+            $($field: $ty,)+
+            mode: JoinMode,
+        }
+
+        impl<$($ty),+> $name<$($ty),+> {
+            /// Create a join step with [`JoinMode::FailFast`] semantics.
+            pub fn new($($field: $ty),+) -> Self {
+                Self {
+                    $($field,)+
+                    mode: JoinMode::FailFast,
+                }
+            }
+
+            /// Set how failures are handled.
+            pub fn with_mode(mut self, mode: JoinMode) -> Self {
+                self.mode = mode;
+                self
+            }
+        }
+
+        #[async_trait]
+        impl<I, $($ty),+> Step for $name<$($ty),+>
+        where
+            I: Clone + Send + Sync + 'static,
+            $($ty: Step<Input = I>, $ty::Output: 'static,)+
+        {
+            type Input = I;
+            type Output = ($($ty::Output,)+);
+
+            async fn run(&self, ctx: &ExecutionContext, input: I) -> Result<Self::Output> {
+                match self.mode {
+                    JoinMode::FailFast => {
+                        $try_join($(self.$field.run(ctx, input.clone())),+).await
+                    }
+                    JoinMode::WaitAll => {
+                        let ($($field,)+) = $join($(self.$field.run(ctx, input.clone())),+).await;
+                        Ok(($($field?,)+))
+                    }
+                }
+            }
+        }
+    };
+}
+
+join_step! {
+    /// Runs two steps concurrently over clones of the input, returning both outputs.
+    ///
+    /// Constructed via [`BoxedStepExt::join`](crate::BoxedStepExt::join).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use llm_workflow::{LambdaStep, Step, ExecutionContext, BoxedStepExt};
+    ///
+    /// # tokio_test::block_on(async {
+    /// let summary = LambdaStep::new(|s: String| async move { Ok::<usize, llm_workflow::Error>(s.len()) });
+    /// let upper = LambdaStep::new(|s: String| async move { Ok::<String, llm_workflow::Error>(s.to_uppercase()) });
+    ///
+    /// let joined = summary.join(upper);
+    /// let (len, text) = joined.run(&ExecutionContext::new(), "abc".to_string()).await.unwrap();
+    /// assert_eq!((len, text.as_str()), (3, "ABC"));
+    /// # });
+    /// ```
+    JoinStep, join, try_join,
+    A => first, B => second
+}
+
+join_step! {
+    /// Runs three steps concurrently over clones of the input, returning all outputs.
+    Join3Step, join3, try_join3,
+    A => first, B => second, C => third
+}
+
+join_step! {
+    /// Runs four steps concurrently over clones of the input, returning all outputs.
+    Join4Step, join4, try_join4,
+    A => first, B => second, C => third, D => fourth
+}
+
+join_step! {
+    /// Runs five steps concurrently over clones of the input, returning all outputs.
+    Join5Step, join5, try_join5,
+    A => first, B => second, C => third, D => fourth, E => fifth
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Error, LambdaStep};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    fn sleepy(ms: u64) -> impl Step<Input = u64, Output = u64> {
+        LambdaStep::new(move |x: u64| async move {
+            tokio::time::sleep(Duration::from_millis(ms)).await;
+            Ok(x + ms)
+        })
+    }
+
+    #[tokio::test]
+    async fn test_join_runs_concurrently() {
+        let joined = Join3Step::new(sleepy(100), sleepy(100), sleepy(100));
+        let start = Instant::now();
+        let out = joined.run(&ExecutionContext::new(), 1).await.unwrap();
+        assert_eq!(out, (101, 101, 101));
+        assert!(start.elapsed() < Duration::from_millis(250));
+    }
+
+    #[tokio::test]
+    async fn test_join_heterogeneous_outputs() {
+        let len = LambdaStep::new(|s: String| async move { Ok(s.len()) });
+        let rev = LambdaStep::new(|s: String| async move { Ok(s.chars().rev().collect::<String>()) });
+        let (n, r) = JoinStep::new(len, rev)
+            .run(&ExecutionContext::new(), "abc".to_string())
+            .await
+            .unwrap();
+        assert_eq!(n, 3);
+        assert_eq!(r, "cba");
+    }
+
+    #[tokio::test]
+    async fn test_fail_fast_cancels_remaining() {
+        let finished = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&finished);
+        let slow = LambdaStep::new(move |x: u64| {
+            let flag = Arc::clone(&flag);
+            async move {
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                flag.store(true, Ordering::SeqCst);
+                Ok(x)
+            }
+        });
+        let failing = LambdaStep::new(|_x: u64| async move {
+            Err::<u64, _>(Error::Execution("fast failure".to_string()))
+        });
+
+        let start = Instant::now();
+        let result = JoinStep::new(slow, failing).run(&ExecutionContext::new(), 0).await;
+        assert!(result.is_err());
+        assert!(start.elapsed() < Duration::from_millis(150));
+        assert!(!finished.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_wait_all_lets_others_finish() {
+        let finished = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&finished);
+        let slow = LambdaStep::new(move |x: u64| {
+            let flag = Arc::clone(&flag);
+            async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                flag.store(true, Ordering::SeqCst);
+                Ok(x)
+            }
+        });
+        let failing = LambdaStep::new(|_x: u64| async move {
+            Err::<u64, _>(Error::Execution("fast failure".to_string()))
+        });
+
+        let result = JoinStep::new(slow, failing)
+            .with_mode(JoinMode::WaitAll)
+            .run(&ExecutionContext::new(), 0)
+            .await;
+        assert!(matches!(result, Err(Error::Execution(_))));
+        assert!(finished.load(Ordering::SeqCst));
+    }
+}
@@ -13,9 +13,18 @@ use crate::{ExecutionContext, Result};
 pub mod batch;
 pub mod branch;
 pub mod chain;
+pub mod circuit_breaker;
+pub mod fallback;
+pub mod iterate;
+pub mod join;
 pub mod map;
 pub mod parallel;
+pub mod pipeline;
+pub mod predicate;
+pub mod race;
 pub mod reduce;
+pub mod router;
+pub mod single_flight;
 pub mod tap;
 
 pub use map::MapStep;
@@ -111,6 +120,8 @@ where
 /// - [`BoxedStepExt::then`]: Chain two steps sequentially
 /// - [`BoxedStepExt::map`]: Transform the output with a closure
 /// - [`BoxedStepExt::tap`]: Inspect the output without modifying it
+/// - [`BoxedStepExt::join`]: Run two steps concurrently over the same input
+/// - [`BoxedStepExt::or_else`]: Fall back to another step when this one fails
 /// - [`BoxedStepExt::boxed`]: Erase the concrete type behind a `Box<dyn Step<...>>`
 pub trait BoxedStepExt: Step + Sized {
     /// Chain this step with another, feeding this step's output into `next`.
@@ -168,6 +179,52 @@ pub trait BoxedStepExt: Step + Sized {
         tap::TapStep::new(self, f)
     }
 
+    /// Run this step and `other` concurrently over clones of the same input,
+    /// returning both outputs as a tuple.
+    ///
+    /// Fails fast by default; see [`join::JoinStep::with_mode`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use llm_workflow::{LambdaStep, BoxedStepExt};
+    ///
+    /// let both = LambdaStep::new(|x: i32| async move { Ok::<i32, llm_workflow::Error>(x + 1) })
+    ///     .join(LambdaStep::new(|x: i32| async move { Ok::<String, llm_workflow::Error>(x.to_string()) }));
+    /// ```
+    fn join<S>(self, other: S) -> join::JoinStep<Self, S>
+    where
+        S: Step<Input = Self::Input>,
+        Self::Input: Clone + Sync + 'static,
+        Self::Output: 'static,
+        S::Output: 'static,
+    {
+        join::JoinStep::new(self, other)
+    }
+
+    /// Try `fallback` with a clone of the input if this step fails.
+    ///
+    /// Chained calls (`a.or_else(b).or_else(c)`) build a single flat
+    /// [`fallback::FallbackStep`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use llm_workflow::{LambdaStep, BoxedStepExt};
+    ///
+    /// let step = LambdaStep::new(|x: i32| async move { Ok::<i32, llm_workflow::Error>(x) })
+    ///     .or_else(LambdaStep::new(|x: i32| async move { Ok::<i32, llm_workflow::Error>(-x) }));
+    /// ```
+    fn or_else<S>(self, fallback: S) -> fallback::FallbackStep<Self::Input, Self::Output>
+    where
+        Self: 'static,
+        S: Step<Input = Self::Input, Output = Self::Output> + 'static,
+        Self::Input: 'static,
+        Self::Output: 'static,
+    {
+        fallback::FallbackStep::new(self).or_else(fallback)
+    }
+
     /// Erase the concrete step type, returning a trait object.
     fn boxed(self) -> Box<dyn Step<Input = Self::Input, Output = Self::Output> + Send + Sync>
     where
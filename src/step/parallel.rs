@@ -1,18 +1,64 @@
 //! Parallel step execution over collections.
 
 use async_trait::async_trait;
-use futures::future::join_all;
+use futures::stream::{self, StreamExt};
+use std::future::Future;
 use std::sync::Arc;
 
 use crate::{ExecutionContext, Result};
 use super::Step;
+use super::join::JoinMode;
+
+/// Run `tasks` with at most `limit` in flight, returning outputs in task order.
+///
+/// With [`JoinMode::FailFast`] the first error to occur is returned and the
+/// remaining tasks are dropped; with [`JoinMode::WaitAll`] every task runs to
+/// completion and the first error in task order is returned.
+pub(crate) async fn run_ordered<T, F>(
+    tasks: impl IntoIterator<Item = F>,
+    limit: Option<usize>,
+    mode: JoinMode,
+) -> Result<Vec<T>>
+where
+    F: Future<Output = Result<T>>,
+{
+    let tasks: Vec<F> = tasks.into_iter().collect();
+    let len = tasks.len();
+    let limit = limit.unwrap_or(len).max(1);
+    let mut completed = stream::iter(
+        tasks
+            .into_iter()
+            .enumerate()
+            .map(|(index, task)| async move { (index, task.await) }),
+    )
+    .buffer_unordered(limit);
+
+    let mut slots: Vec<Option<Result<T>>> = (0..len).map(|_| None).collect();
+    while let Some((index, result)) = completed.next().await {
+        if mode == JoinMode::FailFast {
+            if let Err(e) = result {
+                return Err(e);
+            }
+        }
+        slots[index] = Some(result);
+    }
+    slots
+        .into_iter()
+        .map(|slot| slot.expect("every task completes"))
+        .collect()
+}
 
 /// A step that applies an inner step to each element of a `Vec` concurrently.
 ///
-/// All items are processed in parallel using `join_all`. If any step fails,
-/// the first error is returned.
+/// By default all items are processed at once and every item runs to
+/// completion before the first error (in input order) is returned. Use
+/// [`ParallelMapStep::with_concurrency`] to bound the number of items in
+/// flight and [`ParallelMapStep::with_mode`] to fail fast. Output order
+/// always matches input order.
 pub struct ParallelMapStep<S> {
     step: Arc<S>,
+    concurrency: Option<usize>,
+    mode: JoinMode,
 }
 
 impl<S> ParallelMapStep<S> {
@@ -20,9 +66,28 @@ impl<S> ParallelMapStep<S> {
     pub fn new(step: S) -> Self {
         Self {
             step: Arc::new(step),
+            concurrency: None,
+            mode: JoinMode::WaitAll,
         }
     }
 
+    /// Run at most `limit` items at once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is zero.
+    pub fn with_concurrency(mut self, limit: usize) -> Self {
+        assert!(limit > 0, "concurrency must be greater than zero");
+        self.concurrency = Some(limit);
+        self
+    }
+
+    /// Set how failures are handled.
+    pub fn with_mode(mut self, mode: JoinMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
     /// Access the inner step.
     pub fn inner(&self) -> &S {
         &self.step
@@ -40,14 +105,13 @@ where
     type Output = Vec<S::Output>;
 
     async fn run(&self, ctx: &ExecutionContext, input: Vec<S::Input>) -> Result<Vec<S::Output>> {
-        let futures = input.into_iter().map(|item| {
+        let tasks = input.into_iter().map(|item| {
             let step = Arc::clone(&self.step);
             let ctx = ctx.clone();
             async move { step.run(&ctx, item).await }
         });
 
-        let results = join_all(futures).await;
-        results.into_iter().collect()
+        run_ordered(tasks, self.concurrency, self.mode).await
     }
 }
 
@@ -59,20 +123,128 @@ where
 /// use llm_workflow::{LambdaStep, step::parallel::ParallelMapBuilder};
 ///
 /// let step = LambdaStep::new(|x: i32| async move { Ok::<i32, llm_workflow::Error>(x * 2) });
-/// let parallel = ParallelMapBuilder::new(step).build();
+/// let parallel = ParallelMapBuilder::new(step).concurrency(4).build();
 /// ```
 pub struct ParallelMapBuilder<S> {
     step: S,
+    concurrency: Option<usize>,
+    mode: JoinMode,
 }
 
 impl<S: Step> ParallelMapBuilder<S> {
     /// Create a builder for the given step.
     pub fn new(step: S) -> Self {
-        Self { step }
+        Self {
+            step,
+            concurrency: None,
+            mode: JoinMode::WaitAll,
+        }
+    }
+
+    /// Run at most `limit` items at once.
+    pub fn concurrency(mut self, limit: usize) -> Self {
+        self.concurrency = Some(limit);
+        self
+    }
+
+    /// Set how failures are handled.
+    pub fn mode(mut self, mode: JoinMode) -> Self {
+        self.mode = mode;
+        self
     }
 
     /// Build the [`ParallelMapStep`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if a concurrency limit of zero was set.
     pub fn build(self) -> ParallelMapStep<S> {
-        ParallelMapStep::new(self.step)
+        let step = ParallelMapStep::new(self.step).with_mode(self.mode);
+        match self.concurrency {
+            Some(limit) => step.with_concurrency(limit),
+            None => step,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Error, LambdaStep};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    fn ctx() -> ExecutionContext {
+        ExecutionContext::default()
+    }
+
+    #[tokio::test]
+    async fn test_bounded_concurrency_preserves_order() {
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let (a, p) = (Arc::clone(&active), Arc::clone(&peak));
+        let step = LambdaStep::new(move |x: u64| {
+            let (a, p) = (Arc::clone(&a), Arc::clone(&p));
+            async move {
+                let now = a.fetch_add(1, Ordering::SeqCst) + 1;
+                p.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(10 - x)).await;
+                a.fetch_sub(1, Ordering::SeqCst);
+                Ok(x * 2)
+            }
+        });
+        let out = ParallelMapBuilder::new(step)
+            .concurrency(2)
+            .build()
+            .run(&ctx(), (0..8).collect())
+            .await
+            .unwrap();
+        assert_eq!(out, vec![0, 2, 4, 6, 8, 10, 12, 14]);
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_wait_all_runs_every_item() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let step = LambdaStep::new(move |x: u64| {
+            let counter = Arc::clone(&counter);
+            async move {
+                tokio::time::sleep(Duration::from_millis(x * 5)).await;
+                counter.fetch_add(1, Ordering::SeqCst);
+                if x == 0 {
+                    Err(Error::Execution("first".to_string()))
+                } else {
+                    Ok(x)
+                }
+            }
+        });
+        let result = ParallelMapStep::new(step).run(&ctx(), vec![0, 1, 2, 3]).await;
+        assert_eq!(result.unwrap_err().to_string(), "Execution error: first");
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_fail_fast_stops_launching() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let step = LambdaStep::new(move |x: u64| {
+            let counter = Arc::clone(&counter);
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                if x == 1 {
+                    Err(Error::Execution("boom".to_string()))
+                } else {
+                    Ok(x)
+                }
+            }
+        });
+        let result = ParallelMapStep::new(step)
+            .with_concurrency(1)
+            .with_mode(JoinMode::FailFast)
+            .run(&ctx(), vec![0, 1, 2, 3])
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
     }
 }
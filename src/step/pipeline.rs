@@ -0,0 +1,396 @@
+//! Stream-processing steps for unbounded inputs.
+//!
+//! These are the streaming counterparts of [`ParallelMapStep`](crate::ParallelMapStep),
+//! [`BatchStep`](crate::BatchStep) and [`ReduceStep`](crate::ReduceStep). Each
+//! stage consumes a [`ResultStream`] lazily rather than a `Vec`, so inputs
+//! never need to be held in memory at once and slow stages apply backpressure
+//! to the source. Stages are ordinary [`Step`]s and compose with
+//! [`BoxedStepExt::then`](crate::BoxedStepExt::then).
+
+use async_trait::async_trait;
+use futures::stream::{self, BoxStream, Stream, StreamExt};
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{ExecutionContext, Result};
+use super::Step;
+
+/// A boxed stream of fallible items flowing between pipeline stages.
+pub type ResultStream<T> = BoxStream<'static, Result<T>>;
+
+/// Wrap a stream of plain items as a [`ResultStream`] pipeline source.
+pub fn source<T: Send + 'static>(items: impl Stream<Item = T> + Send + 'static) -> ResultStream<T> {
+    items.map(Ok).boxed()
+}
+
+/// Applies an inner step to every item of a stream with bounded concurrency.
+///
+/// At most `concurrency` items are in flight at once; the source is only
+/// polled as slots free up. Output order matches input order unless
+/// [`StreamParallelMapStep::unordered`] is set. Errors from the source or the inner
+/// step are passed downstream as stream items.
+///
+/// # Example
+///
+/// ```rust
+/// use futures::{stream, TryStreamExt};
+/// use llm_workflow::{BoxedStepExt, LambdaStep, Step, ExecutionContext};
+/// use llm_workflow::step::pipeline::{source, StreamFoldStep, StreamParallelMapStep};
+///
+/// # tokio_test::block_on(async {
+/// let embed = LambdaStep::new(|line: String| async move { Ok::<usize, llm_workflow::Error>(line.len()) });
+/// let sum = LambdaStep::new(|(acc, n): (usize, usize)| async move { Ok::<usize, llm_workflow::Error>(acc + n) });
+/// let job = StreamParallelMapStep::new(embed, 8).then(StreamFoldStep::new(sum, 0));
+///
+/// let lines = stream::iter(vec!["a".to_string(), "bcd".to_string()]);
+/// let total = job.run(&ExecutionContext::new(), source(lines)).await.unwrap();
+/// assert_eq!(total, 4);
+/// # });
+/// ```
+pub struct StreamParallelMapStep<S> {
+    step: Arc<S>,
+    concurrency: usize,
+    ordered: bool,
+}
+
+impl<S> StreamParallelMapStep<S> {
+    /// Create a stream map running up to `concurrency` items at once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `concurrency` is zero.
+    pub fn new(step: S, concurrency: usize) -> Self {
+        assert!(concurrency > 0, "concurrency must be greater than zero");
+        Self {
+            step: Arc::new(step),
+            concurrency,
+            ordered: true,
+        }
+    }
+
+    /// Yield outputs as soon as they complete instead of in input order.
+    pub fn unordered(mut self) -> Self {
+        self.ordered = false;
+        self
+    }
+
+    /// Access the inner step.
+    pub fn inner(&self) -> &S {
+        &self.step
+    }
+}
+
+#[async_trait]
+impl<S> Step for StreamParallelMapStep<S>
+where
+    S: Step + 'static,
+    S::Input: 'static,
+    S::Output: 'static,
+{
+    type Input = ResultStream<S::Input>;
+    type Output = ResultStream<S::Output>;
+
+    async fn run(
+        &self,
+        ctx: &ExecutionContext,
+        input: ResultStream<S::Input>,
+    ) -> Result<ResultStream<S::Output>> {
+        let step = Arc::clone(&self.step);
+        let ctx = ctx.clone();
+        let calls = input.map(move |item| {
+            let step = Arc::clone(&step);
+            let ctx = ctx.clone();
+            async move { step.run(&ctx, item?).await }
+        });
+
+        Ok(if self.ordered {
+            calls.buffered(self.concurrency).boxed()
+        } else {
+            calls.buffer_unordered(self.concurrency).boxed()
+        })
+    }
+}
+
+/// Groups a stream into `Vec` batches by count and, optionally, by time window.
+///
+/// A batch is emitted once it holds `batch_size` items, or — if a window is
+/// set with [`StreamBatchStep::with_window`] — once the window has elapsed
+/// since its first item arrived, whichever comes first. A trailing partial
+/// batch is emitted when the source ends. A source error flushes the pending
+/// batch and is then passed downstream.
+pub struct StreamBatchStep<T> {
+    batch_size: usize,
+    window: Option<Duration>,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> StreamBatchStep<T> {
+    /// Create a batcher emitting batches of up to `batch_size` items.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `batch_size` is zero.
+    pub fn new(batch_size: usize) -> Self {
+        assert!(batch_size > 0, "batch_size must be greater than zero");
+        Self {
+            batch_size,
+            window: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Also emit a partial batch once `window` has elapsed since its first item.
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = Some(window);
+        self
+    }
+}
+
+struct Batcher<T> {
+    input: ResultStream<T>,
+    pending_error: Option<crate::Error>,
+    exhausted: bool,
+}
+
+#[async_trait]
+impl<T> Step for StreamBatchStep<T>
+where
+    T: Send + 'static,
+{
+    type Input = ResultStream<T>;
+    type Output = ResultStream<Vec<T>>;
+
+    async fn run(&self, _ctx: &ExecutionContext, input: ResultStream<T>) -> Result<ResultStream<Vec<T>>> {
+        let batch_size = self.batch_size;
+        let window = self.window;
+        let state = Batcher {
+            input,
+            pending_error: None,
+            exhausted: false,
+        };
+
+        Ok(stream::unfold(state, move |mut state| async move {
+            if let Some(e) = state.pending_error.take() {
+                return Some((Err(e), state));
+            }
+            if state.exhausted {
+                return None;
+            }
+
+            let mut batch = Vec::with_capacity(batch_size);
+            let mut deadline = None;
+            while batch.len() < batch_size {
+                let next = match deadline {
+                    Some(deadline) => {
+                        match tokio::time::timeout_at(deadline, state.input.next()).await {
+                            Ok(next) => next,
+                            Err(_) => break,
+                        }
+                    }
+                    None => state.input.next().await,
+                };
+                match next {
+                    Some(Ok(item)) => {
+                        if batch.is_empty() {
+                            deadline = window.map(|w| tokio::time::Instant::now() + w);
+                        }
+                        batch.push(item);
+                    }
+                    Some(Err(e)) if batch.is_empty() => return Some((Err(e), state)),
+                    Some(Err(e)) => {
+                        state.pending_error = Some(e);
+                        break;
+                    }
+                    None => {
+                        state.exhausted = true;
+                        break;
+                    }
+                }
+            }
+
+            if batch.is_empty() {
+                None
+            } else {
+                Some((Ok(batch), state))
+            }
+        })
+        .boxed())
+    }
+}
+
+/// Folds a stream into a single value, one item at a time.
+///
+/// The inner step receives `(accumulator, item)` and returns the new
+/// accumulator, so only the accumulator is held in memory. The fold stops at
+/// the first error, from either the source or the inner step.
+pub struct StreamFoldStep<S, A> {
+    step: S,
+    init: A,
+}
+
+impl<S, A> StreamFoldStep<S, A> {
+    /// Create a fold starting each run from a clone of `init`.
+    pub fn new(step: S, init: A) -> Self {
+        Self { step, init }
+    }
+}
+
+#[async_trait]
+impl<S, A, T> Step for StreamFoldStep<S, A>
+where
+    S: Step<Input = (A, T), Output = A>,
+    A: Clone + Send + Sync + 'static,
+    T: Send + 'static,
+{
+    type Input = ResultStream<T>;
+    type Output = A;
+
+    async fn run(&self, ctx: &ExecutionContext, mut input: ResultStream<T>) -> Result<A> {
+        let mut acc = self.init.clone();
+        while let Some(item) = input.next().await {
+            acc = self.step.run(ctx, (acc, item?)).await?;
+        }
+        Ok(acc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoxedStepExt, Error, LambdaStep};
+    use futures::TryStreamExt;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn ctx() -> ExecutionContext {
+        ExecutionContext::default()
+    }
+
+    fn numbers(n: u64) -> ResultStream<u64> {
+        source(stream::iter(0..n))
+    }
+
+    #[tokio::test]
+    async fn test_stream_map_preserves_order() {
+        let step = LambdaStep::new(|x: u64| async move {
+            tokio::time::sleep(Duration::from_millis(10 * (5 - x))).await;
+            Ok(x * 10)
+        });
+        let out: Vec<u64> = StreamParallelMapStep::new(step, 5)
+            .run(&ctx(), numbers(5))
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(out, vec![0, 10, 20, 30, 40]);
+    }
+
+    #[tokio::test]
+    async fn test_stream_map_bounds_concurrency() {
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let (a, p) = (Arc::clone(&active), Arc::clone(&peak));
+        let step = LambdaStep::new(move |x: u64| {
+            let (a, p) = (Arc::clone(&a), Arc::clone(&p));
+            async move {
+                let now = a.fetch_add(1, Ordering::SeqCst) + 1;
+                p.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                a.fetch_sub(1, Ordering::SeqCst);
+                Ok(x)
+            }
+        });
+        let out: Vec<u64> = StreamParallelMapStep::new(step, 3)
+            .unordered()
+            .run(&ctx(), numbers(20))
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(out.len(), 20);
+        assert!(peak.load(Ordering::SeqCst) <= 3);
+    }
+
+    #[tokio::test]
+    async fn test_batch_by_count_with_trailing_partial() {
+        let batches: Vec<Vec<u64>> = StreamBatchStep::new(3)
+            .run(&ctx(), numbers(7))
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(batches, vec![vec![0, 1, 2], vec![3, 4, 5], vec![6]]);
+    }
+
+    #[tokio::test]
+    async fn test_batch_by_time_window() {
+        let slow = stream::iter(0..4u64)
+            .then(|x| async move {
+                if x == 2 {
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+                x
+            });
+        let batches: Vec<Vec<u64>> = StreamBatchStep::new(10)
+            .with_window(Duration::from_millis(30))
+            .run(&ctx(), source(slow))
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(batches, vec![vec![0, 1], vec![2, 3]]);
+    }
+
+    #[tokio::test]
+    async fn test_batch_flushes_before_error() {
+        let input: ResultStream<u64> = stream::iter(vec![
+            Ok(1),
+            Err(Error::Execution("bad line".to_string())),
+            Ok(2),
+        ])
+        .boxed();
+        let items: Vec<Result<Vec<u64>>> = StreamBatchStep::new(5)
+            .run(&ctx(), input)
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].as_ref().unwrap(), &vec![1]);
+        assert!(items[1].is_err());
+        assert_eq!(items[2].as_ref().unwrap(), &vec![2]);
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_map_batch_fold() {
+        let double = LambdaStep::new(|x: u64| async move { Ok(x * 2) });
+        let count_batches = LambdaStep::new(|(acc, batch): (Vec<usize>, Vec<u64>)| async move {
+            let mut acc = acc;
+            acc.push(batch.len());
+            Ok(acc)
+        });
+        let job = StreamParallelMapStep::new(double, 4)
+            .then(StreamBatchStep::new(4))
+            .then(StreamFoldStep::new(count_batches, Vec::new()));
+
+        let sizes = job.run(&ctx(), numbers(10)).await.unwrap();
+        assert_eq!(sizes, vec![4, 4, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_fold_stops_at_first_error() {
+        let step = LambdaStep::new(|(acc, x): (u64, u64)| async move {
+            if x == 3 {
+                Err(Error::Execution("boom".to_string()))
+            } else {
+                Ok(acc + x)
+            }
+        });
+        let result = StreamFoldStep::new(step, 0).run(&ctx(), numbers(10)).await;
+        assert!(matches!(result, Err(Error::Execution(_))));
+    }
+}
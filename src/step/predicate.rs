@@ -0,0 +1,148 @@
+//! Predicates used for conditional steps.
+//!
+//! [`Predicate`] abstracts over synchronous closures, steps, and async closures
+//! so that decisions such as branching or checkpointing can involve a model call,
+//! a moderation API, or a cache lookup.
+
+use futures::future::BoxFuture;
+use std::future::Future;
+use std::marker::PhantomData;
+
+use crate::{ExecutionContext, Result};
+use super::Step;
+
+/// A yes/no decision over a borrowed input.
+///
+/// Implemented for any synchronous `Fn(&I) -> bool` closure. Use
+/// [`StepPredicate`], [`ProjectedStepPredicate`], or [`AsyncPredicate`] for
+/// decisions that need to await.
+///
+/// The input is only borrowed while the returned future is created, so
+/// callers need not hold `&I` across an await and `I` need not be `Sync`;
+/// implementations that await must clone or project what they need first.
+pub trait Predicate<I>: Send + Sync {
+    /// Evaluate the predicate against the input.
+    fn evaluate<'a>(&'a self, ctx: &'a ExecutionContext, input: &I) -> BoxFuture<'a, Result<bool>>;
+}
+
+impl<I, F> Predicate<I> for F
+where
+    F: Fn(&I) -> bool + Send + Sync,
+{
+    fn evaluate<'a>(&'a self, _ctx: &'a ExecutionContext, input: &I) -> BoxFuture<'a, Result<bool>> {
+        let decision = self(input);
+        Box::pin(async move { Ok(decision) })
+    }
+}
+
+/// A predicate backed by a step that receives a clone of the input.
+///
+/// # Example
+///
+/// ```rust
+/// use llm_workflow::{LambdaStep, ExecutionContext};
+/// use llm_workflow::step::predicate::{Predicate, StepPredicate};
+///
+/// # tokio_test::block_on(async {
+/// let is_long = StepPredicate::new(LambdaStep::new(|s: String| async move {
+///     Ok::<bool, llm_workflow::Error>(s.len() > 5)
+/// }));
+/// let ctx = ExecutionContext::new();
+/// assert!(is_long.evaluate(&ctx, &"a long sentence".to_string()).await.unwrap());
+/// # });
+/// ```
+pub struct StepPredicate<S> {
+    step: S,
+}
+
+impl<S> StepPredicate<S> {
+    /// Wrap a step producing `bool`.
+    pub fn new(step: S) -> Self {
+        Self { step }
+    }
+}
+
+impl<S, I> Predicate<I> for StepPredicate<S>
+where
+    S: Step<Input = I, Output = bool>,
+    I: Clone + Send + 'static,
+{
+    fn evaluate<'a>(&'a self, ctx: &'a ExecutionContext, input: &I) -> BoxFuture<'a, Result<bool>> {
+        self.step.run(ctx, input.clone())
+    }
+}
+
+/// A predicate backed by a step that receives a value projected from the input by reference.
+///
+/// Useful when the input is large or not `Clone`, and the decision only needs
+/// part of it (e.g. the text field to send to a moderation API).
+pub struct ProjectedStepPredicate<S, F> {
+    step: S,
+    project: F,
+}
+
+impl<S, F> ProjectedStepPredicate<S, F> {
+    /// Wrap a step producing `bool`, fed by `project(&input)`.
+    pub fn new(step: S, project: F) -> Self {
+        Self { step, project }
+    }
+}
+
+impl<S, F, I> Predicate<I> for ProjectedStepPredicate<S, F>
+where
+    S: Step<Output = bool>,
+    S::Input: 'static,
+    F: Fn(&I) -> S::Input + Send + Sync,
+{
+    fn evaluate<'a>(&'a self, ctx: &'a ExecutionContext, input: &I) -> BoxFuture<'a, Result<bool>> {
+        self.step.run(ctx, (self.project)(input))
+    }
+}
+
+/// A predicate backed by an async closure with access to the execution context.
+///
+/// The closure receives a clone of the context and of the input.
+///
+/// # Example
+///
+/// ```rust
+/// use llm_workflow::ExecutionContext;
+/// use llm_workflow::step::predicate::{AsyncPredicate, Predicate};
+///
+/// # tokio_test::block_on(async {
+/// let over_budget = AsyncPredicate::new(|ctx: ExecutionContext, _x: i32| async move {
+///     Ok::<bool, llm_workflow::Error>(ctx.snapshot().total_tokens() > 1000)
+/// });
+/// let ctx = ExecutionContext::new();
+/// assert!(!over_budget.evaluate(&ctx, &1).await.unwrap());
+/// # });
+/// ```
+pub struct AsyncPredicate<I, F> {
+    f: F,
+    _phantom: PhantomData<fn(I)>,
+}
+
+impl<I, F, Fut> AsyncPredicate<I, F>
+where
+    F: Fn(ExecutionContext, I) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<bool>> + Send + 'static,
+{
+    /// Create a predicate from an async closure.
+    pub fn new(f: F) -> Self {
+        Self {
+            f,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<I, F, Fut> Predicate<I> for AsyncPredicate<I, F>
+where
+    F: Fn(ExecutionContext, I) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<bool>> + Send + 'static,
+    I: Clone,
+{
+    fn evaluate<'a>(&'a self, ctx: &'a ExecutionContext, input: &I) -> BoxFuture<'a, Result<bool>> {
+        Box::pin((self.f)(ctx.clone(), input.clone()))
+    }
+}
@@ -0,0 +1,403 @@
+//! Race and hedged-request combinators for latency-critical calls.
+//!
+//! Both combinators run the same input through several same-typed candidate
+//! steps and return the first successful output, cancelling the rest. Each
+//! candidate runs under its own [`ExecutionContext::child`] scope so the
+//! token usage of losing candidates can be reported separately.
+
+use async_trait::async_trait;
+use futures::stream::{FuturesUnordered, StreamExt};
+use std::time::Duration;
+
+use crate::{Error, ExecutionContext, Result, WorkflowEvent};
+use super::Step;
+
+/// Runs every candidate concurrently and returns the first successful output.
+///
+/// Candidates that fail are ignored while others are still running; if all of
+/// them fail, the last error is returned. Once a winner is found the remaining
+/// candidates are dropped (cancelled). A [`WorkflowEvent::RaceFinished`] event
+/// records the winner and the tokens spent by the losers.
+///
+/// # Example
+///
+/// ```rust
+/// use llm_workflow::{LambdaStep, Step, ExecutionContext, BoxedStepExt, step::race::RaceStep};
+///
+/// # tokio_test::block_on(async {
+/// let race = RaceStep::new(vec![
+///     LambdaStep::new(|q: String| async move { Ok::<String, llm_workflow::Error>(format!("primary: {q}")) }).boxed(),
+///     LambdaStep::new(|q: String| async move { Ok::<String, llm_workflow::Error>(format!("secondary: {q}")) }).boxed(),
+/// ]);
+///
+/// let out = race.run(&ExecutionContext::new(), "hi".to_string()).await.unwrap();
+/// assert!(out.ends_with("hi"));
+/// # });
+/// ```
+pub struct RaceStep<S> {
+    steps: Vec<S>,
+    name: String,
+}
+
+impl<S> RaceStep<S> {
+    /// Create a race over the given candidates.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `steps` is empty.
+    pub fn new(steps: Vec<S>) -> Self {
+        assert!(!steps.is_empty(), "RaceStep requires at least one candidate");
+        Self {
+            steps,
+            name: "race".to_string(),
+        }
+    }
+
+    /// Set a human-readable name, used in emitted events.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+}
+
+#[async_trait]
+impl<S> Step for RaceStep<S>
+where
+    S: Step,
+    S::Input: Clone + Sync + 'static,
+    S::Output: 'static,
+{
+    type Input = S::Input;
+    type Output = S::Output;
+
+    async fn run(&self, ctx: &ExecutionContext, input: S::Input) -> Result<S::Output> {
+        let children: Vec<ExecutionContext> = self.steps.iter().map(|_| ctx.child()).collect();
+        let mut in_flight: FuturesUnordered<_> = self
+            .steps
+            .iter()
+            .zip(&children)
+            .enumerate()
+            .map(|(idx, (step, child))| {
+                let input = input.clone();
+                async move { (idx, step.run(child, input).await) }
+            })
+            .collect();
+
+        let mut outcome = None;
+        while let Some((idx, result)) = in_flight.next().await {
+            match result {
+                Ok(output) => {
+                    outcome = Some(Ok((idx, output)));
+                    break;
+                }
+                Err(e) => outcome = Some(Err(e)),
+            }
+        }
+        drop(in_flight);
+
+        finish(ctx, &self.name, &children, outcome)
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Sends the input to the primary candidate, and to each backup only if no
+/// answer has arrived within `delay` of the previous launch.
+///
+/// A failing candidate triggers the next backup immediately rather than
+/// waiting for the delay, even while earlier candidates are still running. The first successful output wins and in-flight
+/// candidates are cancelled; if every candidate fails, the last error is
+/// returned. Like [`RaceStep`], a [`WorkflowEvent::RaceFinished`] event
+/// records the winner and the tokens spent by the losers.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+/// use llm_workflow::{LambdaStep, Step, ExecutionContext, BoxedStepExt, step::race::HedgeStep};
+///
+/// # tokio_test::block_on(async {
+/// let hedge = HedgeStep::new(
+///     vec![
+///         LambdaStep::new(|q: String| async move { Ok::<String, llm_workflow::Error>(format!("primary: {q}")) }).boxed(),
+///         LambdaStep::new(|q: String| async move { Ok::<String, llm_workflow::Error>(format!("backup: {q}")) }).boxed(),
+///     ],
+///     Duration::from_millis(200),
+/// );
+///
+/// let out = hedge.run(&ExecutionContext::new(), "hi".to_string()).await.unwrap();
+/// assert_eq!(out, "primary: hi");
+/// # });
+/// ```
+pub struct HedgeStep<S> {
+    steps: Vec<S>,
+    delay: Duration,
+    name: String,
+}
+
+impl<S> HedgeStep<S> {
+    /// Create a hedged step; `steps[0]` is the primary, the rest are backups in order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `steps` is empty.
+    pub fn new(steps: Vec<S>, delay: Duration) -> Self {
+        assert!(!steps.is_empty(), "HedgeStep requires at least one candidate");
+        Self {
+            steps,
+            delay,
+            name: "hedge".to_string(),
+        }
+    }
+
+    /// Set a human-readable name, used in emitted events.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+}
+
+#[async_trait]
+impl<S> Step for HedgeStep<S>
+where
+    S: Step,
+    S::Input: Clone + Sync + 'static,
+    S::Output: 'static,
+{
+    type Input = S::Input;
+    type Output = S::Output;
+
+    async fn run(&self, ctx: &ExecutionContext, input: S::Input) -> Result<S::Output> {
+        let children: Vec<ExecutionContext> = self.steps.iter().map(|_| ctx.child()).collect();
+        let launch = |idx: usize| {
+            let step = &self.steps[idx];
+            let child = &children[idx];
+            let input = input.clone();
+            async move { (idx, step.run(child, input).await) }
+        };
+
+        let mut in_flight = FuturesUnordered::new();
+        in_flight.push(launch(0));
+        let mut launched = 1;
+        let mut next_launch = tokio::time::Instant::now() + self.delay;
+        let mut outcome = None;
+
+        loop {
+            let has_backup = launched < self.steps.len();
+            tokio::select! {
+                Some((idx, result)) = in_flight.next() => match result {
+                    Ok(output) => {
+                        outcome = Some(Ok((idx, output)));
+                        break;
+                    }
+                    Err(e) => {
+                        outcome = Some(Err(e));
+                        if has_backup {
+                            in_flight.push(launch(launched));
+                            launched += 1;
+                            next_launch = tokio::time::Instant::now() + self.delay;
+                        }
+                    }
+                },
+                _ = tokio::time::sleep_until(next_launch), if has_backup => {
+                    in_flight.push(launch(launched));
+                    launched += 1;
+                    next_launch = tokio::time::Instant::now() + self.delay;
+                }
+                else => break,
+            }
+        }
+        drop(in_flight);
+
+        finish(ctx, &self.name, &children[..launched], outcome)
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Fold candidate scopes into `ctx`, emit the race summary, and produce the result.
+fn finish<O>(
+    ctx: &ExecutionContext,
+    step_name: &str,
+    children: &[ExecutionContext],
+    outcome: Option<Result<(usize, O)>>,
+) -> Result<O> {
+    let winner = match &outcome {
+        Some(Ok((idx, _))) => Some(*idx),
+        _ => None,
+    };
+
+    let mut wasted_prompt_tokens = 0;
+    let mut wasted_completion_tokens = 0;
+    for (idx, child) in children.iter().enumerate() {
+        if Some(idx) != winner {
+            let usage = child.snapshot();
+            wasted_prompt_tokens += usage.prompt_token_count;
+            wasted_completion_tokens += usage.completion_token_count;
+        }
+        ctx.merge(child);
+    }
+
+    ctx.emit(WorkflowEvent::RaceFinished {
+        step_name: step_name.to_string(),
+        winner,
+        launched: children.len(),
+        wasted_prompt_tokens,
+        wasted_completion_tokens,
+    });
+
+    match outcome {
+        Some(Ok((_, output))) => Ok(output),
+        Some(Err(e)) => Err(e),
+        None => Err(Error::Execution(format!(
+            "'{step_name}' finished without any candidate result"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BoxedStepExt;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    type Candidate = Box<dyn Step<Input = u32, Output = String> + Send + Sync>;
+
+    struct Provider {
+        label: &'static str,
+        latency_ms: u64,
+        fail: bool,
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Step for Provider {
+        type Input = u32;
+        type Output = String;
+
+        async fn run(&self, ctx: &ExecutionContext, input: u32) -> Result<String> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            ctx.record_tokens(10, 0);
+            tokio::time::sleep(Duration::from_millis(self.latency_ms)).await;
+            if self.fail {
+                return Err(Error::Execution(format!("{} failed", self.label)));
+            }
+            ctx.record_tokens(0, 5);
+            Ok(format!("{}:{input}", self.label))
+        }
+    }
+
+    fn provider(label: &'static str, latency_ms: u64, fail: bool) -> (Candidate, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let step = Provider {
+            label,
+            latency_ms,
+            fail,
+            calls: Arc::clone(&calls),
+        };
+        (step.boxed(), calls)
+    }
+
+    fn race_event(ctx: &ExecutionContext) -> (Option<usize>, usize, usize) {
+        ctx.trace_snapshot()
+            .into_iter()
+            .find_map(|entry| match entry.event {
+                WorkflowEvent::RaceFinished {
+                    winner,
+                    launched,
+                    wasted_prompt_tokens,
+                    ..
+                } => Some((winner, launched, wasted_prompt_tokens)),
+                _ => None,
+            })
+            .expect("RaceFinished event")
+    }
+
+    #[tokio::test]
+    async fn test_race_returns_fastest_and_reports_waste() {
+        let (slow, _) = provider("slow", 200, false);
+        let (fast, _) = provider("fast", 10, false);
+        let race = RaceStep::new(vec![slow, fast]);
+
+        let ctx = ExecutionContext::new();
+        assert_eq!(race.run(&ctx, 1).await.unwrap(), "fast:1");
+
+        let (winner, launched, wasted) = race_event(&ctx);
+        assert_eq!(winner, Some(1));
+        assert_eq!(launched, 2);
+        assert_eq!(wasted, 10, "the cancelled loser had recorded its prompt tokens");
+        assert_eq!(ctx.snapshot().prompt_token_count, 20);
+    }
+
+    #[tokio::test]
+    async fn test_race_skips_failures() {
+        let (broken, _) = provider("broken", 1, true);
+        let (ok, _) = provider("ok", 30, false);
+        let race = RaceStep::new(vec![broken, ok]);
+        assert_eq!(race.run(&ExecutionContext::new(), 2).await.unwrap(), "ok:2");
+    }
+
+    #[tokio::test]
+    async fn test_race_all_fail_returns_error() {
+        let (a, _) = provider("a", 1, true);
+        let (b, _) = provider("b", 5, true);
+        let ctx = ExecutionContext::new();
+        assert!(RaceStep::new(vec![a, b]).run(&ctx, 0).await.is_err());
+        assert_eq!(race_event(&ctx).0, None);
+    }
+
+    #[tokio::test]
+    async fn test_hedge_skips_backup_when_primary_is_fast() {
+        let (primary, _) = provider("primary", 10, false);
+        let (backup, backup_calls) = provider("backup", 10, false);
+        let hedge = HedgeStep::new(vec![primary, backup], Duration::from_millis(100));
+
+        let ctx = ExecutionContext::new();
+        assert_eq!(hedge.run(&ctx, 3).await.unwrap(), "primary:3");
+        assert_eq!(backup_calls.load(Ordering::SeqCst), 0);
+        assert_eq!(race_event(&ctx), (Some(0), 1, 0));
+    }
+
+    #[tokio::test]
+    async fn test_hedge_launches_backup_after_delay() {
+        let (primary, _) = provider("primary", 300, false);
+        let (backup, backup_calls) = provider("backup", 10, false);
+        let hedge = HedgeStep::new(vec![primary, backup], Duration::from_millis(50));
+
+        let ctx = ExecutionContext::new();
+        assert_eq!(hedge.run(&ctx, 4).await.unwrap(), "backup:4");
+        assert_eq!(backup_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(race_event(&ctx), (Some(1), 2, 10));
+    }
+
+    #[tokio::test]
+    async fn test_hedge_fails_over_immediately_on_error() {
+        let (primary, _) = provider("primary", 1, true);
+        let (backup, _) = provider("backup", 1, false);
+        let hedge = HedgeStep::new(vec![primary, backup], Duration::from_secs(10));
+
+        let start = std::time::Instant::now();
+        assert_eq!(hedge.run(&ExecutionContext::new(), 5).await.unwrap(), "backup:5");
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_hedge_failing_backup_launches_next_while_primary_runs() {
+        let (primary, _) = provider("primary", 2_000, false);
+        let (flaky, _) = provider("flaky", 1, true);
+        let (backup, _) = provider("backup", 1, false);
+        let hedge = HedgeStep::new(vec![primary, flaky, backup], Duration::from_millis(200));
+
+        let start = std::time::Instant::now();
+        let ctx = ExecutionContext::new();
+        assert_eq!(hedge.run(&ctx, 6).await.unwrap(), "backup:6");
+        // The last backup follows the flaky one's failure, not a second delay.
+        assert!(start.elapsed() < Duration::from_millis(350));
+        assert_eq!(race_event(&ctx).1, 3);
+    }
+}
@@ -1,12 +1,90 @@
-//! Reduce step for aggregating collections into a single value.
+//! Reduce steps for aggregating collections into a single value.
+//!
+//! [`Reducer`] abstracts over synchronous closures, fallible closures and
+//! steps, so the aggregation may itself be a model call (e.g. summarizing a
+//! set of partial summaries). [`TreeReduceStep`] applies a reducer
+//! hierarchically for inputs too large to reduce in one call.
 
 use async_trait::async_trait;
 use std::marker::PhantomData;
 
-use crate::{ExecutionContext, Result};
+use crate::{Error, ExecutionContext, Result};
 use super::Step;
+use super::join::JoinMode;
+use super::parallel::run_ordered;
 
-/// A step that reduces a `Vec<I>` into a single value `O` using a synchronous function.
+/// Aggregates a `Vec<I>` into a single `O`.
+///
+/// Implemented for any synchronous `Fn(Vec<I>) -> O` closure. Use
+/// [`FallibleReducer`] for closures that can fail and [`StepReducer`] for
+/// reducers that need to await.
+#[async_trait]
+pub trait Reducer<I, O>: Send + Sync {
+    /// Reduce the items into a single value.
+    async fn reduce(&self, ctx: &ExecutionContext, items: Vec<I>) -> Result<O>;
+}
+
+#[async_trait]
+impl<I, O, F> Reducer<I, O> for F
+where
+    F: Fn(Vec<I>) -> O + Send + Sync,
+    I: Send + 'static,
+    O: Send + 'static,
+{
+    async fn reduce(&self, _ctx: &ExecutionContext, items: Vec<I>) -> Result<O> {
+        Ok(self(items))
+    }
+}
+
+/// A reducer backed by a synchronous closure returning [`Result`].
+pub struct FallibleReducer<F> {
+    f: F,
+}
+
+impl<F> FallibleReducer<F> {
+    /// Wrap a fallible closure.
+    pub fn new(f: F) -> Self {
+        Self { f }
+    }
+}
+
+#[async_trait]
+impl<I, O, F> Reducer<I, O> for FallibleReducer<F>
+where
+    F: Fn(Vec<I>) -> Result<O> + Send + Sync,
+    I: Send + 'static,
+    O: Send + 'static,
+{
+    async fn reduce(&self, _ctx: &ExecutionContext, items: Vec<I>) -> Result<O> {
+        (self.f)(items)
+    }
+}
+
+/// A reducer backed by a step accepting `Vec<I>`.
+pub struct StepReducer<S> {
+    step: S,
+}
+
+impl<S> StepReducer<S> {
+    /// Wrap a step.
+    pub fn new(step: S) -> Self {
+        Self { step }
+    }
+}
+
+#[async_trait]
+impl<S, I, O> Reducer<I, O> for StepReducer<S>
+where
+    S: Step<Input = Vec<I>, Output = O>,
+    I: Send + 'static,
+    O: Send + 'static,
+{
+    async fn reduce(&self, ctx: &ExecutionContext, items: Vec<I>) -> Result<O> {
+        self.step.run(ctx, items).await
+    }
+}
+
+/// A step that reduces a `Vec<I>` into a single value `O` using a [`Reducer`].
 ///
 /// Useful for aggregating results from a parallel or batch step.
 ///
@@ -14,9 +92,14 @@ use super::Step;
 ///
 /// ```rust
 /// use llm_workflow::step::reduce::ReduceStep;
-/// use llm_workflow::Step;
+/// use llm_workflow::{LambdaStep, Step};
 ///
 /// let summer = ReduceStep::<_, i32, i32>::new(|items: Vec<i32>| items.into_iter().sum::<i32>());
+///
+/// // The reducer may also be a step, e.g. an LLM call combining summaries.
+/// let combine = ReduceStep::from_step(LambdaStep::new(|parts: Vec<String>| async move {
+///     Ok::<String, llm_workflow::Error>(parts.join("\n"))
+/// }));
 /// ```
 pub struct ReduceStep<F, I, O> {
     f: F,
@@ -38,17 +121,307 @@ where
     }
 }
 
+impl<F, I, O> ReduceStep<FallibleReducer<F>, I, O>
+where
+    F: Fn(Vec<I>) -> Result<O> + Send + Sync + 'static,
+    I: Send + 'static,
+    O: Send + 'static,
+{
+    /// Create a reduce step from an aggregation function that can fail.
+    pub fn try_new(f: F) -> Self {
+        Self {
+            f: FallibleReducer::new(f),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<S, I, O> ReduceStep<StepReducer<S>, I, O>
+where
+    S: Step<Input = Vec<I>, Output = O>,
+    I: Send + 'static,
+    O: Send + 'static,
+{
+    /// Create a reduce step whose aggregation is performed by another step.
+    pub fn from_step(step: S) -> Self {
+        Self {
+            f: StepReducer::new(step),
+            _phantom: PhantomData,
+        }
+    }
+}
+
 #[async_trait]
 impl<F, I, O> Step for ReduceStep<F, I, O>
 where
-    F: Fn(Vec<I>) -> O + Send + Sync + 'static,
+    F: Reducer<I, O> + 'static,
     I: Send + 'static,
     O: Send + 'static,
 {
     type Input = Vec<I>;
     type Output = O;
 
-    async fn run(&self, _ctx: &ExecutionContext, input: Vec<I>) -> Result<O> {
-        Ok((self.f)(input))
+    async fn run(&self, ctx: &ExecutionContext, input: Vec<I>) -> Result<O> {
+        self.f.reduce(ctx, input).await
+    }
+}
+
+/// A step that folds a `Vec<I>` into an accumulator, one item at a time.
+///
+/// The inner step receives `(accumulator, item)` and returns the new
+/// accumulator — e.g. a "refine" summarization that updates a running summary
+/// with each document. Items are processed in order; the first error aborts.
+pub struct FoldStep<S, A> {
+    step: S,
+    init: A,
+}
+
+impl<S, A> FoldStep<S, A> {
+    /// Create a fold starting each run from a clone of `init`.
+    pub fn new(step: S, init: A) -> Self {
+        Self { step, init }
+    }
+}
+
+#[async_trait]
+impl<S, A, I> Step for FoldStep<S, A>
+where
+    S: Step<Input = (A, I), Output = A>,
+    A: Clone + Send + Sync + 'static,
+    I: Send + 'static,
+{
+    type Input = Vec<I>;
+    type Output = A;
+
+    async fn run(&self, ctx: &ExecutionContext, input: Vec<I>) -> Result<A> {
+        let mut acc = self.init.clone();
+        for item in input {
+            acc = self.step.run(ctx, (acc, item)).await?;
+        }
+        Ok(acc)
+    }
+}
+
+/// A step that reduces a `Vec<T>` hierarchically in groups of `fan_in`.
+///
+/// Each level splits the current values into chunks of at most `fan_in`,
+/// reduces the chunks concurrently, and feeds the results into the next level
+/// until a single value remains. This is the standard pattern for summarizing
+/// documents that exceed a model's context window. A trailing chunk holding a
+/// single value is carried up to the next level without a reducer call; the
+/// reducer still runs at least once, so a single input is passed through it.
+/// Use [`TreeReduceStep::with_concurrency`] to bound the reducer calls in
+/// flight per level.
+///
+/// Returns an [`Error::Validation`] for empty input.
+///
+/// # Example
+///
+/// ```rust
+/// use llm_workflow::{LambdaStep, Step, ExecutionContext};
+/// use llm_workflow::step::reduce::TreeReduceStep;
+///
+/// # tokio_test::block_on(async {
+/// let summarize = LambdaStep::new(|parts: Vec<String>| async move {
+///     Ok::<String, llm_workflow::Error>(format!("({})", parts.join(" ")))
+/// });
+/// let tree = TreeReduceStep::new(summarize, 2);
+///
+/// let input = vec!["a", "b", "c"].into_iter().map(String::from).collect();
+/// let out = tree.run(&ExecutionContext::new(), input).await.unwrap();
+/// assert_eq!(out, "((a b) c)");
+/// # });
+/// ```
+pub struct TreeReduceStep<S> {
+    step: S,
+    fan_in: usize,
+    concurrency: Option<usize>,
+}
+
+impl<S> TreeReduceStep<S> {
+    /// Create a tree reduce combining at most `fan_in` values per reducer call.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fan_in` is less than 2.
+    pub fn new(step: S, fan_in: usize) -> Self {
+        assert!(fan_in >= 2, "fan_in must be at least 2");
+        Self {
+            step,
+            fan_in,
+            concurrency: None,
+        }
+    }
+
+    /// Run at most `limit` reducer calls at once within each level.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is zero.
+    pub fn with_concurrency(mut self, limit: usize) -> Self {
+        assert!(limit > 0, "concurrency must be greater than zero");
+        self.concurrency = Some(limit);
+        self
+    }
+}
+
+#[async_trait]
+impl<S, T> Step for TreeReduceStep<S>
+where
+    S: Step<Input = Vec<T>, Output = T>,
+    T: Send + 'static,
+{
+    type Input = Vec<T>;
+    type Output = T;
+
+    async fn run(&self, ctx: &ExecutionContext, input: Vec<T>) -> Result<T> {
+        if input.is_empty() {
+            return Err(Error::Validation("tree reduce requires at least one input".to_string()));
+        }
+
+        if input.len() == 1 {
+            return self.step.run(ctx, input).await;
+        }
+
+        let mut level = input;
+        while level.len() > 1 {
+            let mut chunks = Vec::with_capacity(level.len().div_ceil(self.fan_in));
+            let mut remaining = level.into_iter();
+            loop {
+                let chunk: Vec<T> = remaining.by_ref().take(self.fan_in).collect();
+                if chunk.is_empty() {
+                    break;
+                }
+                chunks.push(chunk);
+            }
+
+            let tasks = chunks.into_iter().map(|mut chunk| async move {
+                if chunk.len() == 1 {
+                    Ok(chunk.pop().expect("chunk has one value"))
+                } else {
+                    self.step.run(ctx, chunk).await
+                }
+            });
+            level = run_ordered(tasks, self.concurrency, JoinMode::FailFast).await?;
+        }
+        Ok(level.pop().expect("level has one value"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LambdaStep;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn ctx() -> ExecutionContext {
+        ExecutionContext::default()
+    }
+
+    #[tokio::test]
+    async fn test_closure_reduce() {
+        let step = ReduceStep::<_, i32, i32>::new(|items: Vec<i32>| items.into_iter().sum());
+        assert_eq!(step.run(&ctx(), vec![1, 2, 3]).await.unwrap(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_try_new_propagates_error() {
+        let step = ReduceStep::try_new(|items: Vec<i32>| {
+            items
+                .into_iter()
+                .max()
+                .ok_or_else(|| Error::Validation("empty".to_string()))
+        });
+        assert_eq!(step.run(&ctx(), vec![4, 9, 2]).await.unwrap(), 9);
+        assert!(matches!(step.run(&ctx(), vec![]).await, Err(Error::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_step_reducer_can_fail() {
+        let step = ReduceStep::from_step(LambdaStep::new(|parts: Vec<String>| async move {
+            if parts.is_empty() {
+                Err(Error::Execution("nothing to summarize".to_string()))
+            } else {
+                Ok(parts.join("+"))
+            }
+        }));
+        assert_eq!(
+            step.run(&ctx(), vec!["a".to_string(), "b".to_string()]).await.unwrap(),
+            "a+b"
+        );
+        assert!(step.run(&ctx(), vec![]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fold_refines_in_order() {
+        let refine = LambdaStep::new(|(summary, doc): (String, String)| async move {
+            Ok(format!("{summary}{doc}"))
+        });
+        let out = FoldStep::new(refine, ">".to_string())
+            .run(&ctx(), vec!["x".to_string(), "y".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(out, ">xy");
+    }
+
+    #[tokio::test]
+    async fn test_tree_reduce_levels() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let sum = LambdaStep::new(move |items: Vec<u32>| {
+            let counter = Arc::clone(&counter);
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                assert!(items.len() <= 3);
+                Ok(items.into_iter().sum())
+            }
+        });
+        let out = TreeReduceStep::new(sum, 3)
+            .run(&ctx(), (1..=10).collect())
+            .await
+            .unwrap();
+        assert_eq!(out, 55);
+        // 10 -> 4 (3 calls, one carried) -> 2 (1 call, one carried) -> 1 (1 call)
+        assert_eq!(calls.load(Ordering::SeqCst), 5);
+    }
+
+    #[tokio::test]
+    async fn test_tree_reduce_bounds_concurrency() {
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let (a, p) = (Arc::clone(&active), Arc::clone(&peak));
+        let sum = LambdaStep::new(move |items: Vec<u64>| {
+            let (a, p) = (Arc::clone(&a), Arc::clone(&p));
+            async move {
+                let now = a.fetch_add(1, Ordering::SeqCst) + 1;
+                p.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+                a.fetch_sub(1, Ordering::SeqCst);
+                Ok(items.into_iter().sum())
+            }
+        });
+        let out = TreeReduceStep::new(sum, 2)
+            .with_concurrency(2)
+            .run(&ctx(), (1..=16).collect())
+            .await
+            .unwrap();
+        assert_eq!(out, 136);
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_tree_reduce_single_and_empty() {
+        let wrap = LambdaStep::new(|items: Vec<String>| async move { Ok(format!("[{}]", items.join(","))) });
+        let tree = TreeReduceStep::new(wrap, 4);
+        assert_eq!(tree.run(&ctx(), vec!["a".to_string()]).await.unwrap(), "[a]");
+        assert!(matches!(tree.run(&ctx(), vec![]).await, Err(Error::Validation(_))));
+    }
+
+    #[test]
+    #[should_panic(expected = "fan_in must be at least 2")]
+    fn test_tree_reduce_rejects_fan_in_one() {
+        let step = LambdaStep::new(|v: Vec<i32>| async move { Ok(v[0]) });
+        let _ = TreeReduceStep::new(step, 1);
     }
 }
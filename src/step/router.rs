@@ -0,0 +1,292 @@
+//! Multi-way step routing.
+
+use async_trait::async_trait;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use crate::{Error, ExecutionContext, Result, WorkflowEvent};
+use super::Step;
+
+/// Decides which route an input should take by producing a route key.
+///
+/// Implemented for any synchronous `Fn(&I) -> K` closure. Wrap a [`Step`] in a
+/// [`StepClassifier`] to classify asynchronously, e.g. with a model call.
+#[async_trait]
+pub trait RouteClassifier<I, K>: Send + Sync {
+    /// Produce the route key for the given input.
+    async fn classify(&self, ctx: &ExecutionContext, input: &I) -> Result<K>;
+}
+
+#[async_trait]
+impl<I, K, F> RouteClassifier<I, K> for F
+where
+    F: Fn(&I) -> K + Send + Sync,
+    I: Sync,
+    K: Send,
+{
+    async fn classify(&self, _ctx: &ExecutionContext, input: &I) -> Result<K> {
+        Ok(self(input))
+    }
+}
+
+/// A classifier that runs a step over a clone of the input to produce the route key.
+///
+/// Use this when the routing decision needs to await, such as an LLM intent classifier.
+pub struct StepClassifier<S> {
+    step: S,
+}
+
+impl<S> StepClassifier<S> {
+    /// Wrap a step whose output is the route key.
+    pub fn new(step: S) -> Self {
+        Self { step }
+    }
+}
+
+#[async_trait]
+impl<S, I, K> RouteClassifier<I, K> for StepClassifier<S>
+where
+    S: Step<Input = I, Output = K>,
+    I: Clone + Send + Sync + 'static,
+    K: Send + 'static,
+{
+    async fn classify(&self, ctx: &ExecutionContext, input: &I) -> Result<K> {
+        self.step.run(ctx, input.clone()).await
+    }
+}
+
+type BoxedRoute<I, O> = Box<dyn Step<Input = I, Output = O> + Send + Sync>;
+
+/// A step that routes each input to one of many steps based on a classifier.
+///
+/// The classifier produces a route key; the step registered for that key is
+/// executed. If no route matches, the default route runs, or a
+/// [`Error::Validation`] is returned when no default is set. Each decision is
+/// recorded as a [`WorkflowEvent::RouteSelected`] event.
+///
+/// # Example
+///
+/// ```rust
+/// use llm_workflow::{LambdaStep, Step, ExecutionContext, step::router::RouterStep};
+///
+/// # tokio_test::block_on(async {
+/// let router = RouterStep::new(|text: &String| {
+///     if text.contains("refund") { "billing" } else { "general" }
+/// })
+/// .route("billing", LambdaStep::new(|t: String| async move {
+///     Ok::<String, llm_workflow::Error>(format!("billing: {t}"))
+/// }))
+/// .default_route(LambdaStep::new(|t: String| async move {
+///     Ok::<String, llm_workflow::Error>(format!("general: {t}"))
+/// }));
+///
+/// let ctx = ExecutionContext::new();
+/// let out = router.run(&ctx, "I want a refund".to_string()).await.unwrap();
+/// assert_eq!(out, "billing: I want a refund");
+/// # });
+/// ```
+pub struct RouterStep<C, K, I, O> {
+    name: String,
+    classifier: C,
+    routes: HashMap<K, BoxedRoute<I, O>>,
+    default: Option<BoxedRoute<I, O>>,
+    _phantom: PhantomData<fn(I) -> O>,
+}
+
+impl<C, K, I, O> RouterStep<C, K, I, O>
+where
+    K: Eq + Hash,
+{
+    /// Create a router with the given classifier and no routes.
+    pub fn new(classifier: C) -> Self {
+        Self {
+            name: "router".to_string(),
+            classifier,
+            routes: HashMap::new(),
+            default: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Set a human-readable name, used in emitted events and errors.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Register the step to run when the classifier returns `key`.
+    ///
+    /// Registering the same key twice replaces the earlier step.
+    pub fn route<S>(mut self, key: K, step: S) -> Self
+    where
+        S: Step<Input = I, Output = O> + 'static,
+    {
+        self.routes.insert(key, Box::new(step));
+        self
+    }
+
+    /// Set the step to run when no registered route matches.
+    pub fn default_route<S>(mut self, step: S) -> Self
+    where
+        S: Step<Input = I, Output = O> + 'static,
+    {
+        self.default = Some(Box::new(step));
+        self
+    }
+}
+
+impl<S, K, I, O> RouterStep<StepClassifier<S>, K, I, O>
+where
+    K: Eq + Hash,
+{
+    /// Create a router whose classifier is an async step over a clone of the input.
+    pub fn from_step(classifier: S) -> Self {
+        Self::new(StepClassifier::new(classifier))
+    }
+}
+
+#[async_trait]
+impl<C, K, I, O> Step for RouterStep<C, K, I, O>
+where
+    C: RouteClassifier<I, K>,
+    K: Eq + Hash + Serialize + Send + Sync + 'static,
+    I: Send + Sync + 'static,
+    O: Send + 'static,
+{
+    type Input = I;
+    type Output = O;
+
+    async fn run(&self, ctx: &ExecutionContext, input: I) -> Result<O> {
+        let key = self.classifier.classify(ctx, &input).await?;
+        let route = serde_json::to_value(&key)
+            .unwrap_or_else(|_| serde_json::json!("<serialization_error>"));
+
+        let (step, is_default) = match self.routes.get(&key) {
+            Some(step) => (step, false),
+            None => match &self.default {
+                Some(step) => (step, true),
+                None => {
+                    return Err(Error::Validation(format!(
+                        "router '{}' has no route for key {route} and no default",
+                        self.name
+                    )))
+                }
+            },
+        };
+
+        ctx.emit(WorkflowEvent::RouteSelected {
+            step_name: self.name.clone(),
+            route,
+            is_default,
+        });
+        step.run(ctx, input).await
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoxedStepExt, LambdaStep};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+    enum Intent {
+        Billing,
+        Support,
+        Sales,
+    }
+
+    fn tag(label: &'static str) -> BoxedRoute<String, String> {
+        LambdaStep::new(move |t: String| async move { Ok(format!("{label}:{t}")) }).boxed()
+    }
+
+    #[tokio::test]
+    async fn test_router_dispatches_by_enum_key() {
+        let router = RouterStep::new(|t: &String| {
+            if t.starts_with('$') {
+                Intent::Billing
+            } else if t.ends_with('?') {
+                Intent::Support
+            } else {
+                Intent::Sales
+            }
+        })
+        .route(Intent::Billing, tag("billing"))
+        .route(Intent::Support, tag("support"))
+        .route(Intent::Sales, tag("sales"));
+
+        let ctx = ExecutionContext::new();
+        assert_eq!(router.run(&ctx, "$5".to_string()).await.unwrap(), "billing:$5");
+        assert_eq!(router.run(&ctx, "why?".to_string()).await.unwrap(), "support:why?");
+        assert_eq!(router.run(&ctx, "buy".to_string()).await.unwrap(), "sales:buy");
+    }
+
+    #[tokio::test]
+    async fn test_router_emits_route_event() {
+        let router = RouterStep::new(|_t: &String| Intent::Support)
+            .with_name("intent")
+            .route(Intent::Support, tag("support"));
+
+        let ctx = ExecutionContext::new();
+        router.run(&ctx, "hi".to_string()).await.unwrap();
+
+        let traces = ctx.trace_snapshot();
+        assert_eq!(traces.len(), 1);
+        match &traces[0].event {
+            WorkflowEvent::RouteSelected {
+                step_name,
+                route,
+                is_default,
+            } => {
+                assert_eq!(step_name, "intent");
+                assert_eq!(route, &serde_json::json!("Support"));
+                assert!(!is_default);
+            }
+            other => panic!("expected RouteSelected, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_router_falls_back_to_default() {
+        let router = RouterStep::new(|t: &String| t.clone())
+            .route("a".to_string(), tag("a"))
+            .default_route(tag("default"));
+
+        let ctx = ExecutionContext::new();
+        let out = router.run(&ctx, "zzz".to_string()).await.unwrap();
+        assert_eq!(out, "default:zzz");
+        assert!(matches!(
+            ctx.trace_snapshot()[0].event,
+            WorkflowEvent::RouteSelected { is_default: true, .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_router_without_default_errors() {
+        let router = RouterStep::new(|_t: &String| "missing").route("present", tag("present"));
+        let err = router
+            .run(&ExecutionContext::new(), "x".to_string())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Validation(_)));
+    }
+
+    #[tokio::test]
+    async fn test_router_with_async_step_classifier() {
+        let classifier = LambdaStep::new(|t: String| async move {
+            Ok::<_, Error>(if t.len() > 3 { Intent::Sales } else { Intent::Billing })
+        });
+        let router = RouterStep::from_step(classifier)
+            .route(Intent::Billing, tag("billing"))
+            .route(Intent::Sales, tag("sales"));
+
+        let ctx = ExecutionContext::new();
+        assert_eq!(router.run(&ctx, "abc".to_string()).await.unwrap(), "billing:abc");
+        assert_eq!(router.run(&ctx, "abcdef".to_string()).await.unwrap(), "sales:abcdef");
+    }
+}
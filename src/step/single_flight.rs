@@ -0,0 +1,295 @@
+//! Request deduplication for concurrent identical inputs.
+
+use async_trait::async_trait;
+use futures::future::{BoxFuture, FutureExt, Shared};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::{Error, ExecutionContext, Result};
+use super::Step;
+
+type Flight<O> = Shared<BoxFuture<'static, std::result::Result<O, Arc<Error>>>>;
+type KeyFn<I, K> = Box<dyn Fn(&I) -> K + Send + Sync>;
+type Registry<K, O> = Arc<Mutex<HashMap<K, InFlight<O>>>>;
+
+/// A registered flight and the number of callers awaiting it.
+struct InFlight<O> {
+    id: u64,
+    flight: Flight<O>,
+    waiters: usize,
+}
+
+/// Releases a caller's interest in a flight; the last waiter to leave an
+/// unfinished flight removes it, dropping the run.
+struct Waiter<K: Eq + Hash, O> {
+    registry: Registry<K, O>,
+    key: Option<K>,
+    id: u64,
+}
+
+impl<K: Eq + Hash, O> Drop for Waiter<K, O> {
+    fn drop(&mut self) {
+        let Some(key) = self.key.take() else { return };
+        let mut registry = self.registry.lock().unwrap();
+        if let Some(entry) = registry.get_mut(&key).filter(|entry| entry.id == self.id) {
+            entry.waiters -= 1;
+            if entry.waiters == 0 {
+                registry.remove(&key);
+            }
+        }
+    }
+}
+
+/// Coalesces concurrent runs with identical keys into a single inner run.
+///
+/// The first caller for a key runs the inner step; callers arriving while that
+/// run is in flight wait for it and receive a clone of its output, or a copy of
+/// its error. Once the run completes the key is released, so later calls run
+/// the step again (combine with [`CachedStep`](crate::CachedStep) for
+/// persistence). The inner run records metrics and traces against the context
+/// of the caller that started it. If every caller waiting on a run is
+/// cancelled, the run is dropped and the next caller starts a fresh one.
+///
+/// # Example
+///
+/// ```rust
+/// use llm_workflow::{LambdaStep, ParallelMapStep, Step, ExecutionContext};
+/// use llm_workflow::step::single_flight::SingleFlightStep;
+///
+/// # tokio_test::block_on(async {
+/// let embed = LambdaStep::new(|chunk: String| async move {
+///     Ok::<usize, llm_workflow::Error>(chunk.len())
+/// });
+/// let deduped = ParallelMapStep::new(SingleFlightStep::new(embed));
+///
+/// let out = deduped
+///     .run(&ExecutionContext::new(), vec!["a".to_string(), "bb".to_string(), "a".to_string()])
+///     .await
+///     .unwrap();
+/// assert_eq!(out, vec![1, 2, 1]);
+/// # });
+/// ```
+pub struct SingleFlightStep<S: Step, K> {
+    inner: Arc<S>,
+    key_fn: KeyFn<S::Input, K>,
+    in_flight: Registry<K, S::Output>,
+    next_id: AtomicU64,
+}
+
+impl<S> SingleFlightStep<S, S::Input>
+where
+    S: Step,
+    S::Input: Clone + Eq + Hash + 'static,
+{
+    /// Coalesce runs whose inputs are equal.
+    pub fn new(step: S) -> Self {
+        Self::by_key(step, S::Input::clone)
+    }
+}
+
+impl<S, K> SingleFlightStep<S, K>
+where
+    S: Step,
+    K: Eq + Hash,
+{
+    /// Coalesce runs whose inputs map to the same key.
+    pub fn by_key<F>(step: S, key_fn: F) -> Self
+    where
+        F: Fn(&S::Input) -> K + Send + Sync + 'static,
+    {
+        Self {
+            inner: Arc::new(step),
+            key_fn: Box::new(key_fn),
+            in_flight: Arc::new(Mutex::new(HashMap::new())),
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    /// Access the inner step.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Number of keys with a run currently in flight.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.lock().unwrap().len()
+    }
+}
+
+#[async_trait]
+impl<S, K> Step for SingleFlightStep<S, K>
+where
+    S: Step + 'static,
+    S::Input: 'static,
+    S::Output: Clone + Sync + 'static,
+    K: Eq + Hash + Clone + Send + Sync + 'static,
+{
+    type Input = S::Input;
+    type Output = S::Output;
+
+    async fn run(&self, ctx: &ExecutionContext, input: S::Input) -> Result<S::Output> {
+        let key = (self.key_fn)(&input);
+        let (flight, id) = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            match in_flight.get_mut(&key) {
+                Some(entry) => {
+                    entry.waiters += 1;
+                    (entry.flight.clone(), entry.id)
+                }
+                None => {
+                    let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+                    let step = Arc::clone(&self.inner);
+                    let registry = Arc::clone(&self.in_flight);
+                    let ctx = ctx.clone();
+                    let release = key.clone();
+                    let flight = async move {
+                        let result = step.run(&ctx, input).await.map_err(Arc::new);
+                        let mut registry = registry.lock().unwrap();
+                        if registry.get(&release).is_some_and(|entry| entry.id == id) {
+                            registry.remove(&release);
+                        }
+                        result
+                    }
+                    .boxed()
+                    .shared();
+                    in_flight.insert(
+                        key.clone(),
+                        InFlight {
+                            id,
+                            flight: flight.clone(),
+                            waiters: 1,
+                        },
+                    );
+                    (flight, id)
+                }
+            }
+        };
+
+        let _waiter = Waiter {
+            registry: Arc::clone(&self.in_flight),
+            key: Some(key),
+            id,
+        };
+        flight
+            .await
+            .map_err(|e| Arc::try_unwrap(e).unwrap_or_else(|shared| shared.replicate()))
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ParallelMapStep;
+    use std::sync::atomic::AtomicUsize;
+    use std::time::Duration;
+
+    struct Slow {
+        calls: Arc<AtomicUsize>,
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl Step for Slow {
+        type Input = String;
+        type Output = String;
+
+        async fn run(&self, _ctx: &ExecutionContext, input: String) -> Result<String> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(30)).await;
+            if self.fail {
+                Err(Error::Execution(format!("failed on {input}")))
+            } else {
+                Ok(input.to_uppercase())
+            }
+        }
+    }
+
+    fn slow(fail: bool) -> (Slow, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        (
+            Slow {
+                calls: Arc::clone(&calls),
+                fail,
+            },
+            calls,
+        )
+    }
+
+    fn inputs(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_duplicates_run_once() {
+        let (step, calls) = slow(false);
+        let fan_out = ParallelMapStep::new(SingleFlightStep::new(step));
+
+        let out = fan_out
+            .run(&ExecutionContext::new(), inputs(&["a", "b", "a", "a"]))
+            .await
+            .unwrap();
+        assert_eq!(out, inputs(&["A", "B", "A", "A"]));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(fan_out.inner().in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_sequential_calls_are_not_coalesced() {
+        let (step, calls) = slow(false);
+        let single = SingleFlightStep::new(step);
+        let ctx = ExecutionContext::new();
+        single.run(&ctx, "a".to_string()).await.unwrap();
+        single.run(&ctx, "a".to_string()).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_custom_key_coalesces_case_insensitively() {
+        let (step, calls) = slow(false);
+        let fan_out = ParallelMapStep::new(SingleFlightStep::by_key(step, |s: &String| {
+            s.to_lowercase()
+        }));
+        let out = fan_out
+            .run(&ExecutionContext::new(), inputs(&["x", "X"]))
+            .await
+            .unwrap();
+        assert_eq!(out.len(), 2);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_errors_propagate_to_all_waiters() {
+        let (step, calls) = slow(true);
+        let single = SingleFlightStep::new(step);
+        let ctx = ExecutionContext::new();
+
+        let (a, b) = tokio::join!(
+            single.run(&ctx, "a".to_string()),
+            single.run(&ctx, "a".to_string())
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        for result in [a, b] {
+            assert_eq!(result.unwrap_err().to_string(), "Execution error: failed on a");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_flight_is_released() {
+        let (step, calls) = slow(false);
+        let single = SingleFlightStep::new(step);
+        let abandoned = ExecutionContext::new();
+        let timed_out = tokio::time::timeout(Duration::from_millis(5), single.run(&abandoned, "a".to_string())).await;
+        assert!(timed_out.is_err());
+        assert_eq!(single.in_flight(), 0);
+
+        // The next caller starts its own run rather than resuming the abandoned one.
+        assert_eq!(single.run(&ExecutionContext::new(), "a".to_string()).await.unwrap(), "A");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+}
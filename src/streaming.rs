@@ -0,0 +1,543 @@
+//! Streaming workflow steps.
+//!
+//! This module provides [`StreamingStep`] for steps that produce output
+//! incrementally — typically model tokens — so callers can forward chunks to
+//! a user while a pipeline is still running. A streaming step yields zero or
+//! more [`StreamEvent::Chunk`] items followed by exactly one
+//! [`StreamEvent::Done`] carrying the final aggregated value.
+
+use async_trait::async_trait;
+use futures::stream::{self, BoxStream, Stream, StreamExt};
+use serde::Serialize;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use crate::{Error, ExecutionContext, Result, WorkflowEvent, step::Step};
+
+/// An item yielded by a [`StreamingStep`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamEvent<C, O> {
+    /// An incremental chunk of output.
+    Chunk(C),
+    /// The final aggregated output; always the last item of the stream.
+    Done(O),
+}
+
+/// The stream returned by [`StreamingStep::stream`].
+pub type StepStream<C, O> = BoxStream<'static, Result<StreamEvent<C, O>>>;
+
+/// A step that produces its output as a stream of chunks plus a final value.
+///
+/// Implementors typically wrap a provider's streaming API and build the
+/// returned stream with [`aggregate`] or [`concat_text`], which take care of
+/// emitting the final [`StreamEvent::Done`]. Use [`StreamingAdapter`] to run a
+/// streaming step where a regular [`Step`] is expected.
+///
+/// # Example
+///
+/// ```rust
+/// use async_trait::async_trait;
+/// use futures::{stream, StreamExt};
+/// use llm_workflow::{ExecutionContext, Result};
+/// use llm_workflow::streaming::{concat_text, StepStream, StreamingStep, StreamEvent};
+///
+/// struct Echo;
+///
+/// #[async_trait]
+/// impl StreamingStep for Echo {
+///     type Input = String;
+///     type Chunk = String;
+///     type Output = String;
+///
+///     async fn stream(&self, _ctx: &ExecutionContext, input: String) -> Result<StepStream<String, String>> {
+///         let words: Vec<Result<String>> = input.split(' ').map(|w| Ok(format!("{w} "))).collect();
+///         Ok(concat_text(stream::iter(words)))
+///     }
+/// }
+///
+/// # tokio_test::block_on(async {
+/// let mut s = Echo.stream(&ExecutionContext::new(), "hello world".to_string()).await.unwrap();
+/// assert_eq!(s.next().await.unwrap().unwrap(), StreamEvent::Chunk("hello ".to_string()));
+/// # });
+/// ```
+#[async_trait]
+pub trait StreamingStep: Send + Sync {
+    /// The input type for this step.
+    type Input: Send;
+    /// The type of each incremental chunk.
+    type Chunk: Send;
+    /// The final aggregated output type.
+    type Output: Send;
+
+    /// Start the step, returning a stream of chunks ending in a final value.
+    async fn stream(
+        &self,
+        ctx: &ExecutionContext,
+        input: Self::Input,
+    ) -> Result<StepStream<Self::Chunk, Self::Output>>;
+
+    /// Returns a human-readable name for this step. Defaults to the type name.
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+}
+
+/// Build a [`StepStream`] from a stream of chunks, folding each chunk into an
+/// accumulator and emitting `finish(accumulator)` as the final value.
+///
+/// An error from `chunks` is forwarded and ends the stream without a final value.
+pub fn aggregate<C, A, O, F, G>(
+    chunks: impl Stream<Item = Result<C>> + Send + 'static,
+    init: A,
+    fold: F,
+    finish: G,
+) -> StepStream<C, O>
+where
+    C: Send + 'static,
+    A: Send + 'static,
+    O: Send + 'static,
+    F: FnMut(&mut A, &C) + Send + 'static,
+    G: FnOnce(A) -> O + Send + 'static,
+{
+    let state = Some((chunks.boxed(), init, fold, finish));
+    stream::unfold(state, |state| async move {
+        let (mut chunks, mut acc, mut fold, finish) = state?;
+        match chunks.next().await {
+            Some(Ok(chunk)) => {
+                fold(&mut acc, &chunk);
+                Some((Ok(StreamEvent::Chunk(chunk)), Some((chunks, acc, fold, finish))))
+            }
+            Some(Err(e)) => Some((Err(e), None)),
+            None => Some((Ok(StreamEvent::Done(finish(acc))), None)),
+        }
+    })
+    .boxed()
+}
+
+/// Build a [`StepStream`] of text chunks whose final value is their concatenation.
+pub fn concat_text(chunks: impl Stream<Item = Result<String>> + Send + 'static) -> StepStream<String, String> {
+    aggregate(chunks, String::new(), |acc, chunk| acc.push_str(chunk), |acc| acc)
+}
+
+/// Drain a [`StepStream`], returning its final value.
+///
+/// Returns an [`Error::Execution`] if the stream ends without a
+/// [`StreamEvent::Done`].
+pub async fn collect<C, O>(mut stream: StepStream<C, O>) -> Result<O> {
+    while let Some(event) = stream.next().await {
+        if let StreamEvent::Done(output) = event? {
+            return Ok(output);
+        }
+    }
+    Err(Error::Execution("stream ended without a final value".to_string()))
+}
+
+/// Adapts a [`StreamingStep`] into a regular [`Step`] by draining its stream.
+///
+/// Chunks are discarded; the step's output is the stream's final value.
+pub struct StreamingAdapter<S> {
+    inner: S,
+}
+
+impl<S> StreamingAdapter<S> {
+    /// Wrap a streaming step.
+    pub fn new(inner: S) -> Self {
+        Self { inner }
+    }
+
+    /// Access the inner streaming step.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+}
+
+#[async_trait]
+impl<S> Step for StreamingAdapter<S>
+where
+    S: StreamingStep,
+    S::Input: 'static,
+    S::Chunk: 'static,
+    S::Output: 'static,
+{
+    type Input = S::Input;
+    type Output = S::Output;
+
+    async fn run(&self, ctx: &ExecutionContext, input: S::Input) -> Result<S::Output> {
+        let stream = self.inner.stream(ctx, input).await?;
+        collect(stream).await
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+}
+
+/// Adapts a regular [`Step`] into a [`StreamingStep`] that emits its output as a single chunk.
+///
+/// Useful for mixing non-streaming steps into stream-aware pipelines.
+pub struct SingleChunkStream<S> {
+    inner: S,
+}
+
+impl<S> SingleChunkStream<S> {
+    /// Wrap a regular step.
+    pub fn new(inner: S) -> Self {
+        Self { inner }
+    }
+}
+
+#[async_trait]
+impl<S> StreamingStep for SingleChunkStream<S>
+where
+    S: Step,
+    S::Input: 'static,
+    S::Output: Clone + 'static,
+{
+    type Input = S::Input;
+    type Chunk = S::Output;
+    type Output = S::Output;
+
+    async fn stream(
+        &self,
+        ctx: &ExecutionContext,
+        input: S::Input,
+    ) -> Result<StepStream<S::Output, S::Output>> {
+        let output = self.inner.run(ctx, input).await?;
+        let events = vec![
+            Ok(StreamEvent::Chunk(output.clone())),
+            Ok(StreamEvent::Done(output)),
+        ];
+        Ok(stream::iter(events).boxed())
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+}
+
+/// A streaming step that transforms each chunk, leaving the final value unchanged.
+///
+/// The stream-aware counterpart of [`MapStep`](crate::MapStep). Constructed via
+/// [`StreamingStepExt::map_chunks`].
+pub struct StreamMapStep<S, F, C2> {
+    inner: S,
+    f: Arc<F>,
+    _phantom: PhantomData<fn() -> C2>,
+}
+
+#[async_trait]
+impl<S, F, C2> StreamingStep for StreamMapStep<S, F, C2>
+where
+    S: StreamingStep,
+    S::Chunk: 'static,
+    S::Output: 'static,
+    F: Fn(S::Chunk) -> C2 + Send + Sync + 'static,
+    C2: Send + 'static,
+{
+    type Input = S::Input;
+    type Chunk = C2;
+    type Output = S::Output;
+
+    async fn stream(&self, ctx: &ExecutionContext, input: S::Input) -> Result<StepStream<C2, S::Output>> {
+        let f = Arc::clone(&self.f);
+        let stream = self.inner.stream(ctx, input).await?;
+        Ok(stream
+            .map(move |event| {
+                event.map(|event| match event {
+                    StreamEvent::Chunk(chunk) => StreamEvent::Chunk(f(chunk)),
+                    StreamEvent::Done(output) => StreamEvent::Done(output),
+                })
+            })
+            .boxed())
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+}
+
+/// A streaming step that runs a side-effect closure on each chunk as it passes.
+///
+/// The stream-aware counterpart of [`TapStep`](crate::TapStep). Constructed via
+/// [`StreamingStepExt::tap_chunks`].
+pub struct StreamTapStep<S, F> {
+    inner: S,
+    f: Arc<F>,
+}
+
+#[async_trait]
+impl<S, F> StreamingStep for StreamTapStep<S, F>
+where
+    S: StreamingStep,
+    S::Chunk: 'static,
+    S::Output: 'static,
+    F: Fn(&S::Chunk) + Send + Sync + 'static,
+{
+    type Input = S::Input;
+    type Chunk = S::Chunk;
+    type Output = S::Output;
+
+    async fn stream(
+        &self,
+        ctx: &ExecutionContext,
+        input: S::Input,
+    ) -> Result<StepStream<S::Chunk, S::Output>> {
+        let f = Arc::clone(&self.f);
+        let stream = self.inner.stream(ctx, input).await?;
+        Ok(stream
+            .inspect(move |event| {
+                if let Ok(StreamEvent::Chunk(chunk)) = event {
+                    f(chunk);
+                }
+            })
+            .boxed())
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+}
+
+/// A streaming step that records each chunk in the trace as it passes.
+///
+/// Emits a [`WorkflowEvent::StreamChunk`] per chunk and a
+/// [`WorkflowEvent::StepEnd`] when the final value arrives. Constructed via
+/// [`StreamingStepExt::traced`].
+pub struct TracedStream<S> {
+    inner: S,
+    name: String,
+}
+
+#[async_trait]
+impl<S> StreamingStep for TracedStream<S>
+where
+    S: StreamingStep,
+    S::Input: 'static,
+    S::Chunk: Serialize + 'static,
+    S::Output: 'static,
+{
+    type Input = S::Input;
+    type Chunk = S::Chunk;
+    type Output = S::Output;
+
+    async fn stream(
+        &self,
+        ctx: &ExecutionContext,
+        input: S::Input,
+    ) -> Result<StepStream<S::Chunk, S::Output>> {
+        ctx.emit(WorkflowEvent::StepStart {
+            step_name: self.name.clone(),
+            input_type: std::any::type_name::<S::Input>().to_string(),
+        });
+        let start = std::time::Instant::now();
+        let trace_ctx = ctx.clone();
+        let name = self.name.clone();
+        let mut index = 0;
+        let stream = match self.inner.stream(ctx, input).await {
+            Ok(stream) => stream,
+            Err(e) => {
+                ctx.emit(WorkflowEvent::Error {
+                    step_name: self.name.clone(),
+                    message: e.to_string(),
+                });
+                return Err(e);
+            }
+        };
+        Ok(stream
+            .inspect(move |event| match event {
+                Ok(StreamEvent::Chunk(chunk)) => {
+                    let data = serde_json::to_value(chunk)
+                        .unwrap_or_else(|_| serde_json::json!("<serialization_error>"));
+                    trace_ctx.emit(WorkflowEvent::StreamChunk {
+                        step_name: name.clone(),
+                        index,
+                        data,
+                    });
+                    index += 1;
+                }
+                Ok(StreamEvent::Done(_)) => trace_ctx.emit(WorkflowEvent::StepEnd {
+                    step_name: name.clone(),
+                    duration_ms: start.elapsed().as_millis(),
+                }),
+                Err(e) => trace_ctx.emit(WorkflowEvent::Error {
+                    step_name: name.clone(),
+                    message: e.to_string(),
+                }),
+            })
+            .boxed())
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Extension trait providing fluent composition methods for all [`StreamingStep`] implementors.
+pub trait StreamingStepExt: StreamingStep + Sized {
+    /// Transform each chunk with a synchronous closure.
+    fn map_chunks<F, C2>(self, f: F) -> StreamMapStep<Self, F, C2>
+    where
+        F: Fn(Self::Chunk) -> C2 + Send + Sync + 'static,
+    {
+        StreamMapStep {
+            inner: self,
+            f: Arc::new(f),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Inspect each chunk via a side-effect closure, passing it through unchanged.
+    fn tap_chunks<F>(self, f: F) -> StreamTapStep<Self, F>
+    where
+        F: Fn(&Self::Chunk) + Send + Sync + 'static,
+    {
+        StreamTapStep {
+            inner: self,
+            f: Arc::new(f),
+        }
+    }
+
+    /// Record each chunk in the trace under `name`.
+    fn traced(self, name: impl Into<String>) -> TracedStream<Self> {
+        TracedStream {
+            inner: self,
+            name: name.into(),
+        }
+    }
+
+    /// Convert into a regular [`Step`] that returns the final value.
+    fn collected(self) -> StreamingAdapter<Self> {
+        StreamingAdapter::new(self)
+    }
+}
+
+impl<T: StreamingStep + Sized> StreamingStepExt for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoxedStepExt, LambdaStep};
+    use std::sync::Mutex;
+
+    struct Words;
+
+    #[async_trait]
+    impl StreamingStep for Words {
+        type Input = String;
+        type Chunk = String;
+        type Output = String;
+
+        async fn stream(&self, _ctx: &ExecutionContext, input: String) -> Result<StepStream<String, String>> {
+            let words: Vec<Result<String>> = input
+                .split_whitespace()
+                .map(|w| Ok(format!("{w} ")))
+                .collect();
+            Ok(concat_text(stream::iter(words)))
+        }
+    }
+
+    async fn drain<C, O>(stream: StepStream<C, O>) -> Vec<StreamEvent<C, O>> {
+        stream.map(|e| e.unwrap()).collect().await
+    }
+
+    #[tokio::test]
+    async fn test_concat_text_emits_chunks_then_done() {
+        let events = drain(Words.stream(&ExecutionContext::new(), "a b".to_string()).await.unwrap()).await;
+        assert_eq!(
+            events,
+            vec![
+                StreamEvent::Chunk("a ".to_string()),
+                StreamEvent::Chunk("b ".to_string()),
+                StreamEvent::Done("a b ".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_adapter_collects_final_value_and_composes() {
+        let pipeline = Words
+            .collected()
+            .then(LambdaStep::new(|s: String| async move { Ok(s.trim().len()) }));
+        let out = pipeline.run(&ExecutionContext::new(), "one two".to_string()).await.unwrap();
+        assert_eq!(out, 7);
+    }
+
+    #[tokio::test]
+    async fn test_map_and_tap_chunks() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&seen);
+        let step = Words
+            .tap_chunks(move |c: &String| sink.lock().unwrap().push(c.clone()))
+            .map_chunks(|c: String| c.len());
+
+        let events = drain(step.stream(&ExecutionContext::new(), "ab c".to_string()).await.unwrap()).await;
+        assert_eq!(
+            events,
+            vec![
+                StreamEvent::Chunk(3),
+                StreamEvent::Chunk(2),
+                StreamEvent::Done("ab c ".to_string()),
+            ]
+        );
+        assert_eq!(*seen.lock().unwrap(), vec!["ab ".to_string(), "c ".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_traced_records_chunks() {
+        let ctx = ExecutionContext::new();
+        let step = Words.traced("Generate");
+        collect(step.stream(&ctx, "x y z".to_string()).await.unwrap()).await.unwrap();
+
+        let chunks: Vec<(usize, serde_json::Value)> = ctx
+            .trace_snapshot()
+            .into_iter()
+            .filter_map(|entry| match entry.event {
+                WorkflowEvent::StreamChunk { index, data, .. } => Some((index, data)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[2], (2, serde_json::json!("z ")));
+        assert_eq!(ctx.trace_snapshot().len(), 5, "StepStart + 3 chunks + StepEnd");
+    }
+
+    #[tokio::test]
+    async fn test_traced_records_error_when_stream_fails_to_open() {
+        struct Unavailable;
+
+        #[async_trait]
+        impl StreamingStep for Unavailable {
+            type Input = String;
+            type Chunk = String;
+            type Output = String;
+
+            async fn stream(&self, _ctx: &ExecutionContext, _input: String) -> Result<StepStream<String, String>> {
+                Err(Error::Execution("connection refused".to_string()))
+            }
+        }
+
+        let ctx = ExecutionContext::new();
+        assert!(Unavailable.traced("Generate").stream(&ctx, String::new()).await.is_err());
+
+        let trace = ctx.trace_snapshot();
+        assert_eq!(trace.len(), 2, "StepStart + Error");
+        assert!(matches!(
+            &trace[1].event,
+            WorkflowEvent::Error { step_name, message }
+                if step_name == "Generate" && message.contains("connection refused")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_single_chunk_stream_wraps_step() {
+        let step = SingleChunkStream::new(LambdaStep::new(|x: i32| async move { Ok(x * 2) }));
+        let events = drain(step.stream(&ExecutionContext::new(), 4).await.unwrap()).await;
+        assert_eq!(events, vec![StreamEvent::Chunk(8), StreamEvent::Done(8)]);
+    }
+
+    #[tokio::test]
+    async fn test_collect_errors_without_done() {
+        let stream: StepStream<i32, i32> = stream::iter(vec![Ok(StreamEvent::Chunk(1))]).boxed();
+        assert!(matches!(collect(stream).await, Err(Error::Execution(_))));
+    }
+}
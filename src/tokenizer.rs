@@ -0,0 +1,259 @@
+//! Token counting ahead of model calls.
+//!
+//! [`Tokenizer`] estimates how many tokens a piece of text will cost, so
+//! budgets, batches and context windows can be planned before a call is made
+//! (see [`TokenBudget`](crate::TokenBudget)). [`HeuristicTokenizer`] needs no
+//! vocabulary; enable the `tiktoken` feature for exact counts with OpenAI BPE
+//! encodings, or wrap any other tokenizer with [`FnTokenizer`].
+
+use std::borrow::Cow;
+use std::sync::Arc;
+
+/// Tokens added per chat message for role and framing, following OpenAI's
+/// published accounting for chat models.
+pub const MESSAGE_OVERHEAD: usize = 3;
+
+/// Tokens added once per chat request to prime the assistant's reply.
+pub const REPLY_PRIMING: usize = 3;
+
+/// A chat message whose tokens a [`Tokenizer`] can count.
+///
+/// Implemented for `(role, content)` string pairs. Richer message types
+/// report every piece of text sent with the message — content, sender name,
+/// tool call names and arguments — through [`CountableMessage::segments`].
+pub trait CountableMessage {
+    /// The role name, e.g. `"user"`.
+    fn role(&self) -> &str;
+
+    /// The text sent with the message, excluding the role.
+    fn segments(&self) -> Vec<Cow<'_, str>>;
+}
+
+impl<R: AsRef<str>, C: AsRef<str>> CountableMessage for (R, C) {
+    fn role(&self) -> &str {
+        self.0.as_ref()
+    }
+
+    fn segments(&self) -> Vec<Cow<'_, str>> {
+        vec![Cow::Borrowed(self.1.as_ref())]
+    }
+}
+
+/// Counts the tokens in a piece of text.
+///
+/// # Example
+///
+/// ```rust
+/// use llm_workflow::tokenizer::{count_messages, HeuristicTokenizer, Tokenizer};
+///
+/// let tokenizer = HeuristicTokenizer::default();
+/// assert_eq!(tokenizer.count("abcdefgh"), 2);
+///
+/// let prompt = [("system", "Be brief."), ("user", "Hi there")];
+/// assert!(count_messages(&tokenizer, &prompt) > 0);
+/// ```
+pub trait Tokenizer: Send + Sync {
+    /// Number of tokens in `text`.
+    fn count(&self, text: &str) -> usize;
+
+    /// Number of tokens for a single chat message, including framing overhead.
+    fn count_message(&self, message: &dyn CountableMessage) -> usize {
+        let segments: usize = message.segments().iter().map(|segment| self.count(segment)).sum();
+        MESSAGE_OVERHEAD + self.count(message.role()) + segments
+    }
+}
+
+impl<T: Tokenizer + ?Sized> Tokenizer for Arc<T> {
+    fn count(&self, text: &str) -> usize {
+        (**self).count(text)
+    }
+
+    fn count_message(&self, message: &dyn CountableMessage) -> usize {
+        (**self).count_message(message)
+    }
+}
+
+impl<T: Tokenizer + ?Sized> Tokenizer for Box<T> {
+    fn count(&self, text: &str) -> usize {
+        (**self).count(text)
+    }
+
+    fn count_message(&self, message: &dyn CountableMessage) -> usize {
+        (**self).count_message(message)
+    }
+}
+
+/// Count the tokens for a chat request.
+///
+/// Includes per-message overhead and the reply priming tokens.
+pub fn count_messages<T, M>(tokenizer: &T, messages: &[M]) -> usize
+where
+    T: Tokenizer + ?Sized,
+    M: CountableMessage,
+{
+    messages.iter().map(|m| tokenizer.count_message(m)).sum::<usize>() + REPLY_PRIMING
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Heuristic {
+    Chars(f64),
+    Words(f64),
+}
+
+/// A vocabulary-free token estimate.
+///
+/// The default of four characters per token is a reasonable approximation for
+/// English text with most BPE encodings. Estimates are rounded up, so any
+/// non-empty text counts as at least one token.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeuristicTokenizer {
+    heuristic: Heuristic,
+}
+
+impl HeuristicTokenizer {
+    /// Estimate one token per `chars_per_token` characters.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chars_per_token` is not positive.
+    pub fn chars(chars_per_token: f64) -> Self {
+        assert!(chars_per_token > 0.0, "chars_per_token must be greater than zero");
+        Self {
+            heuristic: Heuristic::Chars(chars_per_token),
+        }
+    }
+
+    /// Estimate `tokens_per_word` tokens per whitespace-separated word.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tokens_per_word` is not positive.
+    pub fn words(tokens_per_word: f64) -> Self {
+        assert!(tokens_per_word > 0.0, "tokens_per_word must be greater than zero");
+        Self {
+            heuristic: Heuristic::Words(tokens_per_word),
+        }
+    }
+}
+
+impl Default for HeuristicTokenizer {
+    fn default() -> Self {
+        Self::chars(4.0)
+    }
+}
+
+impl Tokenizer for HeuristicTokenizer {
+    fn count(&self, text: &str) -> usize {
+        let estimate = match self.heuristic {
+            Heuristic::Chars(per_token) => text.chars().count() as f64 / per_token,
+            Heuristic::Words(per_word) => text.split_whitespace().count() as f64 * per_word,
+        };
+        estimate.ceil() as usize
+    }
+}
+
+/// A tokenizer backed by a closure, for plugging in any external tokenizer.
+pub struct FnTokenizer<F> {
+    f: F,
+}
+
+impl<F> FnTokenizer<F>
+where
+    F: Fn(&str) -> usize + Send + Sync,
+{
+    /// Wrap a counting function.
+    pub fn new(f: F) -> Self {
+        Self { f }
+    }
+}
+
+impl<F> Tokenizer for FnTokenizer<F>
+where
+    F: Fn(&str) -> usize + Send + Sync,
+{
+    fn count(&self, text: &str) -> usize {
+        (self.f)(text)
+    }
+}
+
+/// Exact token counts using OpenAI BPE encodings via `tiktoken-rs`.
+///
+/// Requires the `tiktoken` feature.
+#[cfg(feature = "tiktoken")]
+pub struct TiktokenTokenizer {
+    bpe: tiktoken_rs::CoreBPE,
+}
+
+#[cfg(feature = "tiktoken")]
+impl TiktokenTokenizer {
+    /// The `cl100k_base` encoding (GPT-4, GPT-3.5).
+    pub fn cl100k_base() -> crate::Result<Self> {
+        tiktoken_rs::cl100k_base()
+            .map(|bpe| Self { bpe })
+            .map_err(|e| crate::Error::Message(e.to_string()))
+    }
+
+    /// The `o200k_base` encoding (GPT-4o).
+    pub fn o200k_base() -> crate::Result<Self> {
+        tiktoken_rs::o200k_base()
+            .map(|bpe| Self { bpe })
+            .map_err(|e| crate::Error::Message(e.to_string()))
+    }
+
+    /// The encoding used by the named model.
+    pub fn for_model(model: &str) -> crate::Result<Self> {
+        tiktoken_rs::get_bpe_from_model(model)
+            .map(|bpe| Self { bpe })
+            .map_err(|e| crate::Error::Message(e.to_string()))
+    }
+}
+
+#[cfg(feature = "tiktoken")]
+impl Tokenizer for TiktokenTokenizer {
+    fn count(&self, text: &str) -> usize {
+        self.bpe.encode_with_special_tokens(text).len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chars_heuristic_rounds_up() {
+        let tokenizer = HeuristicTokenizer::default();
+        assert_eq!(tokenizer.count(""), 0);
+        assert_eq!(tokenizer.count("a"), 1);
+        assert_eq!(tokenizer.count("abcd"), 1);
+        assert_eq!(tokenizer.count("abcde"), 2);
+        // Counts characters, not bytes.
+        assert_eq!(tokenizer.count("ééééé"), 2);
+    }
+
+    #[test]
+    fn test_words_heuristic() {
+        let tokenizer = HeuristicTokenizer::words(1.5);
+        assert_eq!(tokenizer.count("one  two\nthree"), 5);
+    }
+
+    #[test]
+    fn test_count_messages_adds_overhead() {
+        let tokenizer = FnTokenizer::new(|text: &str| text.len());
+        let total = count_messages(&tokenizer, &[("user", "hello"), ("assistant", "hi")]);
+        assert_eq!(total, (3 + 4 + 5) + (3 + 9 + 2) + REPLY_PRIMING);
+    }
+
+    #[test]
+    fn test_shared_tokenizer_object() {
+        let tokenizer: Arc<dyn Tokenizer> = Arc::new(HeuristicTokenizer::chars(2.0));
+        assert_eq!(tokenizer.count("abcd"), 2);
+        assert_eq!(count_messages::<_, (&str, &str)>(&tokenizer, &[]), REPLY_PRIMING);
+    }
+
+    #[cfg(feature = "tiktoken")]
+    #[test]
+    fn test_tiktoken_counts() {
+        let tokenizer = TiktokenTokenizer::cl100k_base().unwrap();
+        assert_eq!(tokenizer.count("hello world"), 2);
+    }
+}
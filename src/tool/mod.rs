@@ -0,0 +1,595 @@
+//! Tool (function) calling.
+//!
+//! A [`Tool`] is a named, described operation with JSON Schema parameters
+//! that a model can ask to invoke. Tools are collected in a [`ToolRegistry`],
+//! whose [`ToolDefinition`]s are sent to the model; the model's
+//! [`ToolCall`]s are then executed by a [`ToolExecutorStep`], which returns
+//! one tool-result [`Message`] per call.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::chat::{Message, ToolCall};
+use crate::checkpoint::{ApprovalStore, Decision};
+use crate::step::join::JoinMode;
+use crate::step::parallel::run_ordered;
+use crate::{Error, ExecutionContext, Result, WorkflowEvent, step::Step};
+
+pub mod schema;
+
+/// An operation a model can invoke.
+#[async_trait]
+pub trait Tool: Send + Sync {
+    /// The name the model uses to call the tool.
+    fn name(&self) -> &str;
+
+    /// A description telling the model when and how to use the tool.
+    fn description(&self) -> &str;
+
+    /// JSON Schema for the tool's arguments.
+    fn parameters(&self) -> Value;
+
+    /// Run the tool with arguments that have already been validated against
+    /// [`Tool::parameters`].
+    async fn invoke(&self, ctx: &ExecutionContext, arguments: Value) -> Result<Value>;
+
+    /// The definition advertised to the model.
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: self.name().to_string(),
+            description: self.description().to_string(),
+            parameters: self.parameters(),
+        }
+    }
+}
+
+/// The provider-facing description of a [`Tool`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolDefinition {
+    /// The tool name.
+    pub name: String,
+    /// What the tool does.
+    pub description: String,
+    /// JSON Schema for the arguments.
+    pub parameters: Value,
+}
+
+impl ToolDefinition {
+    /// The OpenAI `tools` array entry for this definition.
+    pub fn to_openai(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": self.name,
+                "description": self.description,
+                "parameters": self.parameters,
+            }
+        })
+    }
+
+    /// The Anthropic `tools` array entry for this definition.
+    pub fn to_anthropic(&self) -> Value {
+        json!({
+            "name": self.name,
+            "description": self.description,
+            "input_schema": self.parameters,
+        })
+    }
+}
+
+/// A tool backed by an async closure.
+///
+/// The closure receives a clone of the context and the validated arguments.
+///
+/// # Example
+///
+/// ```rust
+/// use serde_json::json;
+/// use llm_workflow::ExecutionContext;
+/// use llm_workflow::tool::{FnTool, Tool};
+///
+/// # tokio_test::block_on(async {
+/// let add = FnTool::new(
+///     "add",
+///     "Add two integers.",
+///     json!({
+///         "type": "object",
+///         "properties": {"a": {"type": "integer"}, "b": {"type": "integer"}},
+///         "required": ["a", "b"]
+///     }),
+///     |_ctx: ExecutionContext, args: serde_json::Value| async move {
+///         Ok(json!(args["a"].as_i64().unwrap() + args["b"].as_i64().unwrap()))
+///     },
+/// );
+/// let out = add.invoke(&ExecutionContext::new(), json!({"a": 2, "b": 3})).await.unwrap();
+/// assert_eq!(out, json!(5));
+/// # });
+/// ```
+pub struct FnTool<F> {
+    name: String,
+    description: String,
+    parameters: Value,
+    f: F,
+}
+
+impl<F, Fut> FnTool<F>
+where
+    F: Fn(ExecutionContext, Value) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<Value>> + Send,
+{
+    /// Create a tool from its definition and an async closure.
+    pub fn new(name: impl Into<String>, description: impl Into<String>, parameters: Value, f: F) -> Self {
+        Self {
+            name: name.into(),
+            description: description.into(),
+            parameters,
+            f,
+        }
+    }
+}
+
+#[async_trait]
+impl<F, Fut> Tool for FnTool<F>
+where
+    F: Fn(ExecutionContext, Value) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<Value>> + Send,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters(&self) -> Value {
+        self.parameters.clone()
+    }
+
+    async fn invoke(&self, ctx: &ExecutionContext, arguments: Value) -> Result<Value> {
+        (self.f)(ctx.clone(), arguments).await
+    }
+}
+
+/// A cheaply cloneable set of tools, keyed by name.
+#[derive(Clone, Default)]
+pub struct ToolRegistry {
+    tools: BTreeMap<String, Arc<dyn Tool>>,
+}
+
+impl ToolRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a tool, replacing any existing tool with the same name.
+    pub fn with(mut self, tool: impl Tool + 'static) -> Self {
+        self.register(tool);
+        self
+    }
+
+    /// Add a tool in place, replacing any existing tool with the same name.
+    pub fn register(&mut self, tool: impl Tool + 'static) {
+        self.tools.insert(tool.name().to_string(), Arc::new(tool));
+    }
+
+    /// Look up a tool by name.
+    pub fn get(&self, name: &str) -> Option<&Arc<dyn Tool>> {
+        self.tools.get(name)
+    }
+
+    /// Names of the registered tools, sorted.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.tools.keys().map(String::as_str)
+    }
+
+    /// Definitions of the registered tools, sorted by name.
+    pub fn definitions(&self) -> Vec<ToolDefinition> {
+        self.tools.values().map(|tool| tool.definition()).collect()
+    }
+
+    /// Number of registered tools.
+    pub fn len(&self) -> usize {
+        self.tools.len()
+    }
+
+    /// Whether the registry has no tools.
+    pub fn is_empty(&self) -> bool {
+        self.tools.is_empty()
+    }
+}
+
+/// Executes a model's tool calls against a [`ToolRegistry`].
+///
+/// Each call's arguments are validated against the tool's schema before it
+/// runs. The output holds one tool-result [`Message`] per call, in call order.
+/// By default failures — unknown tools, invalid arguments, and tool errors —
+/// become error results for the model to react to; use
+/// [`ToolExecutorStep::fail_on_error`] to abort the run instead.
+/// [`Error::Checkpoint`] always propagates so tools can pause for a human.
+///
+/// Tools named with [`ToolExecutorStep::require_approval`] only run once a
+/// reviewer has approved the call in the executor's [`ApprovalStore`] with
+/// [`ApprovalStore::approve_call`]. Before any call in a batch runs, the first
+/// call still awaiting a decision raises an [`Error::Checkpoint`] whose `data`
+/// is that [`ToolCall`]; record a decision for it and run the batch again to
+/// resume. A decision only applies to the call it was made for — same id,
+/// tool and arguments — and is consumed when the call executes; a call whose
+/// decision was consumed by a concurrent run raises the checkpoint again.
+/// Rejected calls are skipped and reported to the model as errors.
+///
+/// Every call emits a [`WorkflowEvent::ToolInvocation`] event.
+///
+/// # Example
+///
+/// ```rust
+/// use serde_json::json;
+/// use llm_workflow::{Step, ExecutionContext};
+/// use llm_workflow::chat::ToolCall;
+/// use llm_workflow::tool::{FnTool, ToolExecutorStep, ToolRegistry};
+///
+/// # tokio_test::block_on(async {
+/// let echo = FnTool::new("echo", "Echo the input.", json!({"type": "object"}),
+///     |_ctx: ExecutionContext, args: serde_json::Value| async move { Ok(args) });
+/// let executor = ToolExecutorStep::new(ToolRegistry::new().with(echo));
+///
+/// let calls = vec![
+///     ToolCall::new("1", "echo", json!({"x": 1})),
+///     ToolCall::new("2", "missing", json!({})),
+/// ];
+/// let results = executor.run(&ExecutionContext::new(), calls).await.unwrap();
+/// assert_eq!(results[0].text(), r#"{"x":1}"#);
+/// assert!(results[1].tool_result.as_ref().unwrap().is_error);
+/// # });
+/// ```
+pub struct ToolExecutorStep {
+    registry: ToolRegistry,
+    concurrency: usize,
+    fail_on_error: bool,
+    approval_required: BTreeSet<String>,
+    approvals: ApprovalStore,
+    name: String,
+}
+
+impl ToolExecutorStep {
+    /// Execute calls against `registry`, one at a time.
+    pub fn new(registry: ToolRegistry) -> Self {
+        Self {
+            registry,
+            concurrency: 1,
+            fail_on_error: false,
+            approval_required: BTreeSet::new(),
+            approvals: ApprovalStore::new(),
+            name: "tools".to_string(),
+        }
+    }
+
+    /// Require a reviewer's approval before each call to the named tool.
+    pub fn require_approval(mut self, tool_name: impl Into<String>) -> Self {
+        self.approval_required.insert(tool_name.into());
+        self
+    }
+
+    /// Use `approvals` as the decision store, e.g. one shared with a review UI.
+    pub fn with_approvals(mut self, approvals: ApprovalStore) -> Self {
+        self.approvals = approvals;
+        self
+    }
+
+    /// The store consulted for approval decisions.
+    pub fn approvals(&self) -> &ApprovalStore {
+        &self.approvals
+    }
+
+    /// Run up to `limit` tool calls at once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is zero.
+    pub fn with_concurrency(mut self, limit: usize) -> Self {
+        assert!(limit > 0, "concurrency must be greater than zero");
+        self.concurrency = limit;
+        self
+    }
+
+    /// Abort the run on the first failed call instead of reporting it to the model.
+    pub fn fail_on_error(mut self) -> Self {
+        self.fail_on_error = true;
+        self
+    }
+
+    /// Set a human-readable name, used in emitted events.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// The registry calls are executed against.
+    pub fn registry(&self) -> &ToolRegistry {
+        &self.registry
+    }
+
+    async fn call(&self, ctx: &ExecutionContext, call: &ToolCall) -> Result<Value> {
+        let tool = self
+            .registry
+            .get(&call.name)
+            .ok_or_else(|| Error::Validation(format!("unknown tool '{}'", call.name)))?;
+        schema::validate(&tool.parameters(), &call.arguments)
+            .map_err(|e| Error::Validation(format!("invalid arguments for '{}': {e}", call.name)))?;
+        tool.invoke(ctx, call.arguments.clone()).await
+    }
+
+    fn needs_approval(&self, call: &ToolCall) -> bool {
+        self.approval_required.contains(&call.name) && self.approvals.call_decision(call).is_none()
+    }
+
+    async fn execute(&self, ctx: &ExecutionContext, call: ToolCall) -> Result<Message> {
+        let start = Instant::now();
+        let result = match self.approval_required.contains(&call.name) {
+            true => match self.approvals.take_call(&call) {
+                Some(Decision::Reject { reason }) => Err(Error::Execution(match reason {
+                    Some(reason) => format!("call rejected by reviewer: {reason}"),
+                    None => "call rejected by reviewer".to_string(),
+                })),
+                Some(Decision::Approve) => self.call(ctx, &call).await,
+                // Another run consumed the approval after this batch was
+                // checked; pause again rather than run the call unreviewed.
+                None => Err(Error::Checkpoint {
+                    step_name: self.name.clone(),
+                    data: serde_json::to_value(&call)?,
+                }),
+            },
+            false => self.call(ctx, &call).await,
+        };
+        ctx.emit(WorkflowEvent::ToolInvocation {
+            step_name: self.name.clone(),
+            tool_name: call.name.clone(),
+            call_id: call.id.clone(),
+            arguments: call.arguments.clone(),
+            result: result.as_ref().ok().cloned(),
+            error: result.as_ref().err().map(ToString::to_string),
+            duration_ms: start.elapsed().as_millis(),
+        });
+
+        match result {
+            Ok(Value::String(text)) => Ok(Message::tool_result(call.id, text)),
+            Ok(value) => Ok(Message::tool_result(call.id, value.to_string())),
+            Err(e @ Error::Checkpoint { .. }) => Err(e),
+            Err(e) if self.fail_on_error => Err(e),
+            Err(e) => Ok(Message::tool_error(call.id, e.to_string())),
+        }
+    }
+}
+
+#[async_trait]
+impl Step for ToolExecutorStep {
+    type Input = Vec<ToolCall>;
+    type Output = Vec<Message>;
+
+    async fn run(&self, ctx: &ExecutionContext, input: Vec<ToolCall>) -> Result<Vec<Message>> {
+        if let Some(call) = input.iter().find(|call| self.needs_approval(call)) {
+            return Err(Error::Checkpoint {
+                step_name: self.name.clone(),
+                data: serde_json::to_value(call)?,
+            });
+        }
+
+        let tasks = input.into_iter().map(|call| self.execute(ctx, call));
+        run_ordered(tasks, Some(self.concurrency), JoinMode::FailFast).await
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn ctx() -> ExecutionContext {
+        ExecutionContext::default()
+    }
+
+    fn slow_double() -> impl Tool {
+        FnTool::new(
+            "double",
+            "Double a number after a delay.",
+            json!({"type": "object", "properties": {"n": {"type": "integer"}}, "required": ["n"]}),
+            |_ctx: ExecutionContext, args: Value| async move {
+                let n = args["n"].as_i64().unwrap_or_default();
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                if n < 0 {
+                    return Err(Error::Execution("negative input".to_string()));
+                }
+                Ok(json!(n * 2))
+            },
+        )
+    }
+
+    fn registry() -> ToolRegistry {
+        ToolRegistry::new().with(slow_double())
+    }
+
+    fn invocations(ctx: &ExecutionContext) -> Vec<(String, Option<Value>, Option<String>)> {
+        ctx.trace_snapshot()
+            .into_iter()
+            .filter_map(|entry| match entry.event {
+                WorkflowEvent::ToolInvocation {
+                    call_id,
+                    result,
+                    error,
+                    ..
+                } => Some((call_id, result, error)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_executes_and_traces_calls() {
+        let ctx = ctx();
+        let out = ToolExecutorStep::new(registry())
+            .run(&ctx, vec![ToolCall::new("c1", "double", json!({"n": 4}))])
+            .await
+            .unwrap();
+        assert_eq!(out, vec![Message::tool_result("c1", "8")]);
+        assert_eq!(invocations(&ctx), vec![("c1".to_string(), Some(json!(8)), None)]);
+    }
+
+    #[tokio::test]
+    async fn test_errors_are_returned_to_model() {
+        let ctx = ctx();
+        let calls = vec![
+            ToolCall::new("bad_args", "double", json!({"n": "four"})),
+            ToolCall::new("failing", "double", json!({"n": -1})),
+            ToolCall::new("unknown", "triple", json!({"n": 1})),
+        ];
+        let out = ToolExecutorStep::new(registry()).run(&ctx, calls).await.unwrap();
+        let texts: Vec<String> = out.iter().map(Message::text).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "Validation error: invalid arguments for 'double': $.n: expected integer, got \"four\"",
+                "Execution error: negative input",
+                "Validation error: unknown tool 'triple'",
+            ]
+        );
+        assert!(out.iter().all(|m| m.tool_result.as_ref().unwrap().is_error));
+        assert_eq!(invocations(&ctx).len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_fail_on_error_aborts() {
+        let result = ToolExecutorStep::new(registry())
+            .fail_on_error()
+            .run(&ctx(), vec![ToolCall::new("c1", "double", json!({"n": -1}))])
+            .await;
+        assert!(matches!(result, Err(Error::Execution(_))));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_calls_keep_order() {
+        let calls: Vec<ToolCall> = (0..5)
+            .map(|n| ToolCall::new(format!("c{n}"), "double", json!({"n": n})))
+            .collect();
+        let start = Instant::now();
+        let out = ToolExecutorStep::new(registry())
+            .with_concurrency(5)
+            .run(&ctx(), calls)
+            .await
+            .unwrap();
+        assert!(start.elapsed() < Duration::from_millis(90));
+        let texts: Vec<String> = out.iter().map(Message::text).collect();
+        assert_eq!(texts, vec!["0", "2", "4", "6", "8"]);
+    }
+
+    #[tokio::test]
+    async fn test_approval_gate_pauses_then_follows_decision() {
+        let sent = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = Arc::clone(&sent);
+        let email = FnTool::new("send_email", "Send an email.", json!({"type": "object"}), move |_ctx: ExecutionContext, _args: Value| {
+            let counter = Arc::clone(&counter);
+            async move {
+                counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                Ok(json!("sent"))
+            }
+        });
+        let executor = ToolExecutorStep::new(registry().with(email)).require_approval("send_email");
+        let calls = vec![
+            ToolCall::new("d1", "double", json!({"n": 1})),
+            ToolCall::new("e1", "send_email", json!({"to": "a@b.c"})),
+            ToolCall::new("e2", "send_email", json!({"to": "x@y.z"})),
+        ];
+
+        // Nothing runs until every gated call has a decision.
+        let err = executor.run(&ctx(), calls.clone()).await.unwrap_err();
+        let Error::Checkpoint { step_name, data } = err else {
+            panic!("expected checkpoint");
+        };
+        assert_eq!(step_name, "tools");
+        assert_eq!(serde_json::from_value::<ToolCall>(data).unwrap(), calls[1]);
+        executor.approvals().approve_call(&calls[1]);
+        assert!(executor.run(&ctx(), calls.clone()).await.is_err());
+        assert_eq!(sent.load(std::sync::atomic::Ordering::SeqCst), 0);
+
+        executor.approvals().reject_call(&calls[2], "wrong recipient");
+        let out = executor.run(&ctx(), calls.clone()).await.unwrap();
+        assert_eq!(sent.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert_eq!(out[0], Message::tool_result("d1", "2"));
+        assert_eq!(out[1], Message::tool_result("e1", "sent"));
+        assert_eq!(
+            out[2],
+            Message::tool_error("e2", "Execution error: call rejected by reviewer: wrong recipient")
+        );
+
+        // Decisions are consumed, so replaying the batch asks again.
+        assert!(executor.run(&ctx(), calls).await.is_err());
+        assert_eq!(sent.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_approval_is_bound_to_call_arguments() {
+        let executor = ToolExecutorStep::new(registry()).require_approval("double");
+        let approved = ToolCall::new("c1", "double", json!({"n": 1}));
+        executor.approvals().approve_call(&approved);
+
+        // The same id coming back with other arguments needs its own decision.
+        let altered = ToolCall::new("c1", "double", json!({"n": 1000}));
+        let err = executor.run(&ctx(), vec![altered.clone()]).await.unwrap_err();
+        let Error::Checkpoint { data, .. } = err else {
+            panic!("expected checkpoint");
+        };
+        assert_eq!(serde_json::from_value::<ToolCall>(data).unwrap(), altered);
+
+        // A plain decision keyed only by id does not unlock the call either.
+        executor.approvals().approve("c1");
+        assert!(executor.run(&ctx(), vec![altered]).await.is_err());
+
+        executor.approvals().approve_call(&approved);
+        let out = executor.run(&ctx(), vec![approved]).await.unwrap();
+        assert_eq!(out[0], Message::tool_result("c1", "2"));
+    }
+
+    #[tokio::test]
+    async fn test_one_approval_runs_the_call_once_across_concurrent_runs() {
+        let refunds = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = Arc::clone(&refunds);
+        let refund = FnTool::new("refund", "Refund an order.", json!({"type": "object"}), move |_ctx: ExecutionContext, _args: Value| {
+            let counter = Arc::clone(&counter);
+            async move {
+                counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                Ok(json!("refunded"))
+            }
+        });
+        let executor = ToolExecutorStep::new(registry().with(refund)).require_approval("refund");
+        let lookup = ToolCall::new("c1", "double", json!({"n": 1}));
+        let call = ToolCall::new("c2", "refund", json!({"order": 7}));
+        executor.approvals().approve_call(&call);
+
+        // Both batches pass the up-front check; the first reaches the refund
+        // only after its slow lookup, by which time the second has used the approval.
+        let ctx = ctx();
+        let (first, second) = tokio::join!(
+            executor.run(&ctx, vec![lookup, call.clone()]),
+            executor.run(&ctx, vec![call]),
+        );
+        assert!(matches!(first, Err(Error::Checkpoint { .. })));
+        assert_eq!(second.unwrap(), vec![Message::tool_result("c2", "refunded")]);
+        assert_eq!(refunds.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_definitions_for_providers() {
+        let defs = registry().definitions();
+        assert_eq!(defs[0].to_openai()["function"]["name"], "double");
+        assert_eq!(defs[0].to_anthropic()["input_schema"]["required"], json!(["n"]));
+    }
+}
@@ -0,0 +1,185 @@
+//! Minimal JSON Schema validation for tool arguments.
+//!
+//! Supports the subset of JSON Schema that model providers accept for tool
+//! parameters: `type` (single or list), `properties`, `required`,
+//! `additionalProperties: false`, `items`, `enum`, `minimum` / `maximum` and
+//! `minLength` / `maxLength`. Unknown keywords are ignored.
+
+use serde_json::Value;
+
+/// Validate `value` against `schema`, returning a description of the first violation.
+///
+/// Paths in messages are written as `$.field[0].nested`.
+///
+/// # Example
+///
+/// ```rust
+/// use serde_json::json;
+/// use llm_workflow::tool::schema::validate;
+///
+/// let schema = json!({
+///     "type": "object",
+///     "properties": {"city": {"type": "string"}},
+///     "required": ["city"]
+/// });
+/// assert!(validate(&schema, &json!({"city": "Paris"})).is_ok());
+/// assert_eq!(
+///     validate(&schema, &json!({})).unwrap_err(),
+///     "$: missing required property 'city'"
+/// );
+/// ```
+pub fn validate(schema: &Value, value: &Value) -> Result<(), String> {
+    validate_at(schema, value, "$")
+}
+
+fn type_matches(expected: &str, value: &Value) -> bool {
+    match expected {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        // JSON Schema counts any number with a zero fractional part, such as
+        // `2.0`, as an integer.
+        "integer" => value.as_f64().is_some_and(|n| n.fract() == 0.0),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn validate_at(schema: &Value, value: &Value, path: &str) -> Result<(), String> {
+    let Some(schema) = schema.as_object() else {
+        return Ok(());
+    };
+
+    match schema.get("type") {
+        Some(Value::String(expected)) if !type_matches(expected, value) => {
+            return Err(format!("{path}: expected {expected}, got {value}"));
+        }
+        Some(Value::Array(options))
+            if !options
+                .iter()
+                .filter_map(Value::as_str)
+                .any(|expected| type_matches(expected, value)) =>
+        {
+            return Err(format!("{path}: value {value} does not match any allowed type"));
+        }
+        _ => {}
+    }
+
+    if let Some(Value::Array(allowed)) = schema.get("enum") {
+        if !allowed.contains(value) {
+            return Err(format!("{path}: value {value} is not one of {}", Value::Array(allowed.clone())));
+        }
+    }
+
+    if let Some(n) = value.as_f64() {
+        if let Some(min) = schema.get("minimum").and_then(Value::as_f64) {
+            if n < min {
+                return Err(format!("{path}: {n} is less than minimum {min}"));
+            }
+        }
+        if let Some(max) = schema.get("maximum").and_then(Value::as_f64) {
+            if n > max {
+                return Err(format!("{path}: {n} is greater than maximum {max}"));
+            }
+        }
+    }
+
+    if let Some(s) = value.as_str() {
+        let len = s.chars().count() as u64;
+        if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+            if len < min {
+                return Err(format!("{path}: string shorter than {min} characters"));
+            }
+        }
+        if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+            if len > max {
+                return Err(format!("{path}: string longer than {max} characters"));
+            }
+        }
+    }
+
+    if let Some(object) = value.as_object() {
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(name) {
+                    return Err(format!("{path}: missing required property '{name}'"));
+                }
+            }
+        }
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for (name, field) in object {
+            match properties.and_then(|p| p.get(name)) {
+                Some(field_schema) => validate_at(field_schema, field, &format!("{path}.{name}"))?,
+                None if schema.get("additionalProperties") == Some(&Value::Bool(false)) => {
+                    return Err(format!("{path}: unexpected property '{name}'"));
+                }
+                None => {}
+            }
+        }
+    }
+
+    if let (Some(items), Some(array)) = (schema.get("items"), value.as_array()) {
+        for (index, item) in array.iter().enumerate() {
+            validate_at(items, item, &format!("{path}[{index}]"))?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": {"type": "string", "minLength": 1},
+                "limit": {"type": "integer", "minimum": 1, "maximum": 50},
+                "sort": {"enum": ["relevance", "date"]},
+                "tags": {"type": "array", "items": {"type": "string"}},
+                "cursor": {"type": ["string", "null"]}
+            },
+            "required": ["query"],
+            "additionalProperties": false
+        })
+    }
+
+    #[test]
+    fn test_accepts_valid_arguments() {
+        let args = json!({"query": "rust", "limit": 10, "sort": "date", "tags": ["a"], "cursor": null});
+        assert_eq!(validate(&schema(), &args), Ok(()));
+    }
+
+    #[test]
+    fn test_integral_float_is_an_integer() {
+        let args = json!({"query": "rust", "limit": 2.0});
+        assert_eq!(validate(&schema(), &args), Ok(()));
+    }
+
+    #[test]
+    fn test_reports_first_violation_with_path() {
+        let cases = [
+            (json!({"query": "x", "limit": 2.5}), "$.limit: expected integer, got 2.5"),
+            (json!({"query": "x", "limit": 99}), "$.limit: 99 is greater than maximum 50"),
+            (json!({"query": ""}), "$.query: string shorter than 1 characters"),
+            (json!({"query": "x", "tags": ["a", 3]}), "$.tags[1]: expected string, got 3"),
+            (json!({"query": "x", "extra": true}), "$: unexpected property 'extra'"),
+            (json!({"query": "x", "cursor": 1}), "$.cursor: value 1 does not match any allowed type"),
+            (json!("query"), "$: expected object, got \"query\""),
+        ];
+        for (args, expected) in cases {
+            assert_eq!(validate(&schema(), &args).unwrap_err(), expected);
+        }
+    }
+
+    #[test]
+    fn test_enum_violation() {
+        let err = validate(&schema(), &json!({"query": "x", "sort": "random"})).unwrap_err();
+        assert_eq!(err, r#"$.sort: value "random" is not one of ["relevance","date"]"#);
+    }
+}
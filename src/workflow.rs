@@ -1,6 +1,9 @@
 //! High-level workflow container with automatic metrics collection.
 
-use crate::{ExecutionContext, Result, WorkflowMetrics, step::Step};
+use async_trait::async_trait;
+use std::time::Instant;
+
+use crate::{ExecutionContext, Result, WorkflowEvent, WorkflowMetrics, step::Step};
 
 /// A high-level workflow wrapper that runs a step and collects execution metrics.
 ///
@@ -20,6 +23,33 @@ use crate::{ExecutionContext, Result, WorkflowMetrics, step::Step};
 /// assert_eq!(result, 10);
 /// # });
 /// ```
+///
+/// # Nesting
+///
+/// `Workflow` itself implements [`Step`], so smaller workflows can be composed
+/// into larger pipelines. When run as a step, the child executes under its own
+/// [`ExecutionContext::child`] scope; its metrics are rolled up into the parent
+/// and kept as a per-child breakdown in [`WorkflowMetrics::children`], and its
+/// trace entries are wrapped in a [`WorkflowEvent::SubWorkflow`] event.
+///
+/// ```rust
+/// use llm_workflow::{LambdaStep, Workflow, BoxedStepExt};
+///
+/// # tokio_test::block_on(async {
+/// let inner = Workflow::new(LambdaStep::new(|x: i32| async move {
+///     Ok::<i32, llm_workflow::Error>(x + 1)
+/// }))
+/// .with_name("Increment");
+///
+/// let outer = Workflow::new(inner.then(LambdaStep::new(|x: i32| async move {
+///     Ok::<i32, llm_workflow::Error>(x * 2)
+/// })));
+///
+/// let (result, metrics) = outer.run(4).await.unwrap();
+/// assert_eq!(result, 10);
+/// assert_eq!(metrics.children["Increment"].steps_completed, 1);
+/// # });
+/// ```
 pub struct Workflow<S> {
     step: S,
     name: String,
@@ -60,7 +90,9 @@ impl<S: Step> Workflow<S> {
     /// Run the workflow with a caller-provided execution context.
     ///
     /// Useful when you want to share a context across multiple workflow runs
-    /// to accumulate metrics.
+    /// to accumulate metrics. Metrics and traces are recorded directly into
+    /// `ctx` without attribution; run the workflow as a [`Step`] to keep them
+    /// scoped under its name.
     pub async fn run_with_ctx(
         &self,
         ctx: &ExecutionContext,
@@ -79,3 +111,126 @@ impl<S: Step> Workflow<S> {
         self.step
     }
 }
+
+#[async_trait]
+impl<S> Step for Workflow<S>
+where
+    S: Step,
+    S::Input: 'static,
+    S::Output: 'static,
+{
+    type Input = S::Input;
+    type Output = S::Output;
+
+    async fn run(&self, ctx: &ExecutionContext, input: S::Input) -> Result<S::Output> {
+        let child = ctx.child();
+        let start = Instant::now();
+        let result = self.step.run(&child, input).await;
+        let duration_ms = start.elapsed().as_millis();
+
+        if result.is_ok() {
+            child.record_step();
+        }
+
+        let child_metrics = child.snapshot();
+        ctx.metrics
+            .lock()
+            .unwrap()
+            .record_child(self.name.clone(), &child_metrics);
+        ctx.emit(WorkflowEvent::SubWorkflow {
+            workflow_name: self.name.clone(),
+            duration_ms,
+            success: result.is_ok(),
+            entries: child.trace_snapshot(),
+        });
+
+        result
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoxedStepExt, Error, InstrumentedStep, LambdaStep};
+
+    fn tokens_step(prompt: usize, completion: usize) -> impl Step<Input = i32, Output = i32> {
+        struct Tokens(usize, usize);
+
+        #[async_trait]
+        impl Step for Tokens {
+            type Input = i32;
+            type Output = i32;
+
+            async fn run(&self, ctx: &ExecutionContext, input: i32) -> Result<i32> {
+                ctx.record_tokens(self.0, self.1);
+                Ok(input + 1)
+            }
+        }
+
+        Tokens(prompt, completion)
+    }
+
+    #[tokio::test]
+    async fn test_sub_workflow_rolls_up_metrics_per_child() {
+        let a = Workflow::new(tokens_step(10, 5)).with_name("a");
+        let b = Workflow::new(tokens_step(20, 10)).with_name("b");
+        let outer = Workflow::new(a.then(b)).with_name("outer");
+
+        let (result, metrics) = outer.run(0).await.unwrap();
+        assert_eq!(result, 2);
+        assert_eq!(metrics.total_tokens(), 45);
+        assert_eq!(metrics.steps_completed, 3, "two children plus the outer run");
+        assert_eq!(metrics.children["a"].total_tokens(), 15);
+        assert_eq!(metrics.children["b"].total_tokens(), 30);
+    }
+
+    #[tokio::test]
+    async fn test_sub_workflow_wraps_trace_entries() {
+        let inner = Workflow::new(InstrumentedStep::new(tokens_step(1, 1), "Inner"))
+            .with_name("child");
+        let ctx = ExecutionContext::new();
+        Step::run(&inner, &ctx, 0).await.unwrap();
+        let traces = ctx.trace_snapshot();
+        assert_eq!(traces.len(), 1);
+        match &traces[0].event {
+            WorkflowEvent::SubWorkflow {
+                workflow_name,
+                success,
+                entries,
+                ..
+            } => {
+                assert_eq!(workflow_name, "child");
+                assert!(success);
+                assert_eq!(entries.len(), 2, "StepStart + StepEnd from the child");
+            }
+            other => panic!("expected SubWorkflow event, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sub_workflow_failure_still_rolls_up() {
+        let failing = Workflow::new(InstrumentedStep::new(
+            LambdaStep::new(|_x: i32| async move {
+                Err::<i32, _>(Error::Execution("boom".to_string()))
+            }),
+            "Failing",
+        ))
+        .with_name("child");
+
+        let ctx = ExecutionContext::new();
+        assert!(Step::run(&failing, &ctx, 0).await.is_err());
+
+        let metrics = ctx.snapshot();
+        assert_eq!(metrics.steps_completed, 0);
+        assert_eq!(metrics.failures.len(), 1);
+        assert_eq!(metrics.children["child"].failures.len(), 1);
+        assert!(matches!(
+            ctx.trace_snapshot()[0].event,
+            WorkflowEvent::SubWorkflow { success: false, .. }
+        ));
+    }
+}